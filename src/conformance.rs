@@ -0,0 +1,273 @@
+use std::fmt;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+
+use crate::lameboy::Lameboy;
+
+/// Roughly two minutes of emulated time, plenty for any single test ROM to report a result.
+pub const DEFAULT_MAX_CYCLES: u64 = 4_194_304 * 120;
+
+const MOONEYE_BREAKPOINT_OPCODE: u8 = 0x40;
+const MOONEYE_PASS_SIGNATURE: [u8; 6] = [3, 5, 8, 13, 21, 34];
+const MOONEYE_FAIL_SIGNATURE: [u8; 6] = [0x42; 6];
+
+#[derive(Debug, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    Failed(String),
+    TimedOut,
+    Crashed(String),
+}
+
+impl TestOutcome {
+    pub fn is_pass(&self) -> bool {
+        matches!(self, TestOutcome::Passed)
+    }
+}
+
+impl fmt::Display for TestOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestOutcome::Passed => write!(f, "PASS"),
+            TestOutcome::Failed(reason) => write!(f, "FAIL - {reason}"),
+            TestOutcome::TimedOut => write!(f, "TIMEOUT"),
+            TestOutcome::Crashed(reason) => write!(f, "CRASH - {reason}"),
+        }
+    }
+}
+
+/// Run a single test ROM headlessly until it reports a result or the cycle limit is reached.
+///
+/// Blargg's suites print their results over the serial port, ending with either "Passed" or
+/// "Failed". Mooneye's suites execute `ld b, b` once finished, with the Fibonacci sequence in the
+/// registers on success and 0x42 in every register on failure.
+///
+/// Any panic from inside the emulator, such as an unsupported MBC or an undefined opcode, is
+/// reported as a crash rather than taking down the whole run.
+pub fn run_test_rom(rom_data: Vec<u8>, max_cycles: u64) -> TestOutcome {
+    let result = panic::catch_unwind(move || {
+        let mut lameboy = Lameboy::new(rom_data);
+        lameboy.reset();
+        lameboy.get_serial().record_output = true;
+
+        run_until_result(&mut lameboy, max_cycles)
+    });
+
+    match result {
+        Ok(outcome) => outcome,
+        Err(cause) => TestOutcome::Crashed(panic_message(cause.as_ref())),
+    }
+}
+
+fn run_until_result(lameboy: &mut Lameboy, max_cycles: u64) -> TestOutcome {
+    let mut cycles: u64 = 0;
+    let mut serial_length = 0;
+
+    while cycles < max_cycles {
+        let pc = lameboy.get_cpu().registers.pc;
+        let opcode = lameboy.get_mmu().read8_safe(pc);
+
+        cycles += u64::from(lameboy.step());
+
+        if opcode == MOONEYE_BREAKPOINT_OPCODE {
+            if let Some(outcome) = check_mooneye_signature(lameboy) {
+                return outcome;
+            }
+        }
+
+        let serial = &lameboy.get_serial().output;
+        if serial.len() != serial_length {
+            serial_length = serial.len();
+            if let Some(outcome) = check_blargg_output(&lameboy.get_serial().output_string()) {
+                return outcome;
+            }
+        }
+    }
+
+    TestOutcome::TimedOut
+}
+
+fn check_mooneye_signature(lameboy: &mut Lameboy) -> Option<TestOutcome> {
+    let registers = &lameboy.get_cpu().registers;
+    let signature = [
+        registers.b,
+        registers.c,
+        registers.d,
+        registers.e,
+        registers.h,
+        registers.l,
+    ];
+
+    if signature == MOONEYE_PASS_SIGNATURE {
+        Some(TestOutcome::Passed)
+    } else if signature == MOONEYE_FAIL_SIGNATURE {
+        Some(TestOutcome::Failed(String::from(
+            "mooneye failure signature",
+        )))
+    } else {
+        None
+    }
+}
+
+fn check_blargg_output(output: &str) -> Option<TestOutcome> {
+    if output.contains("Passed") {
+        Some(TestOutcome::Passed)
+    } else if output.contains("Failed") {
+        Some(TestOutcome::Failed(output.trim().replace('\n', " ")))
+    } else {
+        None
+    }
+}
+
+fn panic_message(cause: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = cause.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = cause.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+/// Find every `.gb` file under a directory, sorted so the results table is stable.
+pub fn find_test_roms(dir: &Path) -> Vec<PathBuf> {
+    let mut roms = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                roms.append(&mut find_test_roms(&path));
+            } else if path.extension().is_some_and(|ext| ext == "gb") {
+                roms.push(path);
+            }
+        }
+    }
+
+    roms.sort();
+    roms
+}
+
+/// Run every test ROM under a directory, returning the outcome for each.
+pub fn run_test_rom_dir(dir: &Path, max_cycles: u64) -> Vec<(PathBuf, TestOutcome)> {
+    find_test_roms(dir)
+        .into_iter()
+        .map(|path| {
+            let outcome = match fs::read(&path) {
+                Ok(data) => run_test_rom(data, max_cycles),
+                Err(e) => TestOutcome::Crashed(format!("Unable to read ROM: {e}")),
+            };
+            (path, outcome)
+        })
+        .collect()
+}
+
+/// Format results as a table with a pass count summary at the end.
+pub fn format_results_table(dir: &Path, results: &[(PathBuf, TestOutcome)]) -> String {
+    let names: Vec<String> = results
+        .iter()
+        .map(|(path, _)| path.strip_prefix(dir).unwrap_or(path).display().to_string())
+        .collect();
+    let name_width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+
+    let mut table = String::new();
+    for (name, (_, outcome)) in names.iter().zip(results) {
+        table.push_str(&format!("{name: <name_width$} | {outcome}\n"));
+    }
+
+    let passed = results
+        .iter()
+        .filter(|(_, outcome)| outcome.is_pass())
+        .count();
+    table.push_str(&format!("{passed}/{} passed\n", results.len()));
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::program_rom;
+
+    #[test]
+    fn mooneye_pass_signature() {
+        let code = [
+            0x06, 3, 0x0E, 5, 0x16, 8, 0x1E, 13, 0x26, 21, 0x2E, 34,   // ld r8, fibonacci
+            0x40, // ld b, b
+        ];
+
+        let rom = program_rom(&[(0x0100, &code)]);
+        assert_eq!(TestOutcome::Passed, run_test_rom(rom, 1_000));
+    }
+
+    #[test]
+    fn mooneye_fail_signature() {
+        let code = [
+            0x06, 0x42, 0x48, 0x51, 0x5A, 0x63, 0x6C, // ld b, 0x42 then copy it along
+            0x40, // ld b, b
+        ];
+
+        let rom = program_rom(&[(0x0100, &code)]);
+        assert!(matches!(run_test_rom(rom, 1_000), TestOutcome::Failed(_)));
+    }
+
+    #[test]
+    fn blargg_serial_pass() {
+        let mut code = Vec::new();
+        for c in "Passed".bytes() {
+            // ld a, c; ldh [rSB], a; ld a, 0x81; ldh [rSC], a
+            code.extend_from_slice(&[0x3E, c, 0xE0, 0x01, 0x3E, 0x81, 0xE0, 0x02]);
+        }
+
+        let rom = program_rom(&[(0x0100, &code)]);
+        assert_eq!(TestOutcome::Passed, run_test_rom(rom, 10_000));
+    }
+
+    #[test]
+    fn time_out_without_result() {
+        // jr -2
+        let rom = program_rom(&[(0x0100, &[0x18, 0xFE])]);
+
+        assert_eq!(TestOutcome::TimedOut, run_test_rom(rom, 1_000));
+    }
+
+    #[test]
+    fn crash_on_unsupported_cart() {
+        let mut rom = program_rom(&[]);
+        rom[0x0147] = 0xFF;
+
+        assert!(matches!(run_test_rom(rom, 1_000), TestOutcome::Crashed(_)));
+    }
+
+    #[test]
+    fn blargg_output_detection() {
+        assert_eq!(None, check_blargg_output("01-special\n\n"));
+        assert_eq!(
+            Some(TestOutcome::Passed),
+            check_blargg_output("01-special\n\n\nPassed\n")
+        );
+        assert_eq!(
+            Some(TestOutcome::Failed(String::from(
+                "02-interrupts  EI Failed #2"
+            ))),
+            check_blargg_output("02-interrupts\n\nEI\nFailed #2\n")
+        );
+    }
+
+    /// Run every ROM in the directory named by `LAMEBOY_TEST_ROMS` (defaulting to `roms/`) and
+    /// print a results table. Ignored by default, run with `cargo test -- --ignored test_roms`.
+    #[test]
+    #[ignore]
+    fn test_roms() {
+        let dir = PathBuf::from(std::env::var("LAMEBOY_TEST_ROMS").unwrap_or("roms".into()));
+        let results = run_test_rom_dir(&dir, DEFAULT_MAX_CYCLES);
+
+        println!("{}", format_results_table(&dir, &results));
+
+        assert!(
+            results.iter().all(|(_, outcome)| outcome.is_pass()),
+            "Not all test ROMs passed"
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::dis::SymbolTable;
+use crate::lameboy::mmu::location::Location;
+use crate::lameboy::mmu::Mmu;
+
+/// Directory the per ROM annotation files are kept in.
+const ANNOTATION_DIR: &str = "annotations";
+
+/// A user's own label and comment on an address, either of which may be empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotation {
+    pub label: String,
+    pub comment: String,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.label.is_empty() && self.comment.is_empty()
+    }
+}
+
+/// Labels and comments added by the user, keyed by bank and address like the symbol file.
+///
+/// Saved on every change to a tab separated sidecar file named after the ROM's global checksum,
+/// one `bank:address`, label and comment per line.
+pub struct Annotations {
+    entries: BTreeMap<(u16, u16), Annotation>,
+    path: Option<PathBuf>,
+}
+
+impl Annotations {
+    pub fn new() -> Annotations {
+        Annotations {
+            entries: BTreeMap::new(),
+            path: None,
+        }
+    }
+
+    /// Where the annotations for a ROM are saved.
+    pub fn sidecar_path(global_checksum: u16) -> PathBuf {
+        Path::new(ANNOTATION_DIR).join(format!("{global_checksum:04X}.txt"))
+    }
+
+    /// Load the annotations for a ROM, starting empty if it has none yet, and save any changes
+    /// back to the same file.
+    pub fn load_for_rom(global_checksum: u16) -> Result<Annotations, String> {
+        let path = Annotations::sidecar_path(global_checksum);
+        let mut annotations = if path.is_file() {
+            let text = fs::read_to_string(&path)
+                .map_err(|e| format!("Unable to read annotations {}: {e}", path.display()))?;
+            let annotations = Annotations::parse(&text)
+                .map_err(|e| format!("Annotations {}: {e}", path.display()))?;
+            info!(
+                "Loaded {} annotations from {}",
+                annotations.entries.len(),
+                path.display()
+            );
+            annotations
+        } else {
+            Annotations::new()
+        };
+        annotations.path = Some(path);
+
+        Ok(annotations)
+    }
+
+    pub fn parse(text: &str) -> Result<Annotations, String> {
+        let mut annotations = Annotations::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut fields = line.splitn(3, '\t');
+            let location = fields
+                .next()
+                .and_then(|location| location.split_once(':'))
+                .and_then(|(bank, addr)| {
+                    Some((
+                        u16::from_str_radix(bank, 16).ok()?,
+                        u16::from_str_radix(addr, 16).ok()?,
+                    ))
+                })
+                .ok_or_else(|| format!("Line {}: expected 'bank:address'", line_number + 1))?;
+
+            let annotation = Annotation {
+                label: fields.next().unwrap_or("").to_string(),
+                comment: fields.next().unwrap_or("").to_string(),
+            };
+            if !annotation.is_empty() {
+                annotations.entries.insert(location, annotation);
+            }
+        }
+
+        Ok(annotations)
+    }
+
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(&(bank, addr), annotation)| {
+                format!(
+                    "{bank:02X}:{addr:04X}\t{}\t{}\n",
+                    annotation.label, annotation.comment
+                )
+            })
+            .collect()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Unable to create {}: {e}", dir.display()))?;
+        }
+        fs::write(path, self.to_text())
+            .map_err(|e| format!("Unable to write annotations {}: {e}", path.display()))
+    }
+
+    /// Every annotation as `((bank, address), annotation)`, in address order within each bank.
+    pub fn iter(&self) -> impl Iterator<Item = (&(u16, u16), &Annotation)> {
+        self.entries.iter()
+    }
+
+    pub fn get(&self, bank: u16, addr: u16) -> Option<&Annotation> {
+        self.entries.get(&(bank, addr))
+    }
+
+    /// The annotation on an address in whichever bank is mapped there right now.
+    pub fn at(&self, addr: u16, mmu: &Mmu) -> Option<&Annotation> {
+        self.get(mmu.bank_of(addr), addr)
+    }
+
+    /// Replace the annotation on an address, removing it if it is empty, then save.
+    ///
+    /// Labels can't contain whitespace so they can be exported as symbols, and comments are kept
+    /// to a single line.
+    pub fn set(&mut self, bank: u16, addr: u16, annotation: Annotation) -> Result<(), String> {
+        let label = annotation.label.trim();
+        if label.contains(char::is_whitespace) {
+            return Err(format!("Label '{label}' can't contain spaces"));
+        }
+        let annotation = Annotation {
+            label: label.to_string(),
+            comment: annotation
+                .comment
+                .split(['\t', '\r', '\n'])
+                .collect::<Vec<&str>>()
+                .join(" ")
+                .trim()
+                .to_string(),
+        };
+
+        if annotation.is_empty() {
+            self.entries.remove(&(bank, addr));
+        } else {
+            self.entries.insert((bank, addr), annotation);
+        }
+
+        self.save()
+    }
+
+    /// The label for an address in the mapped bank, preferring the user's own to the symbol
+    /// file's.
+    pub fn label<'a>(&'a self, addr: u16, mmu: &Mmu, symbols: &'a SymbolTable) -> Option<&'a str> {
+        self.label_in_bank(mmu.bank_of(addr), addr, symbols)
+    }
+
+    pub fn label_in_bank<'a>(
+        &'a self,
+        bank: u16,
+        addr: u16,
+        symbols: &'a SymbolTable,
+    ) -> Option<&'a str> {
+        self.get(bank, addr)
+            .map(|annotation| annotation.label.as_str())
+            .filter(|label| !label.is_empty())
+            .or_else(|| symbols.label_in_bank(bank, addr))
+    }
+
+    /// Find a label by name, ignoring case, checking the user's labels before the symbol file's,
+    /// returning its bank and address.
+    pub fn find(&self, name: &str, symbols: &SymbolTable) -> Option<(u16, u16)> {
+        let name = name.trim();
+        self.entries
+            .iter()
+            .find(|(_, annotation)| {
+                !annotation.label.is_empty() && annotation.label.eq_ignore_ascii_case(name)
+            })
+            .map(|(&location, _)| location)
+            .or_else(|| symbols.find(name))
+    }
+
+    /// Resolve a location typed by the user, either a label or a hex `bank:address` or address.
+    pub fn resolve(&self, location: &str, symbols: &SymbolTable) -> Result<Location, String> {
+        match self.find(location, symbols) {
+            Some((bank, addr)) => Ok(Location::new(bank, addr)),
+            None => symbols.resolve(location),
+        }
+    }
+
+    /// Write every label in `.sym` format, the symbol file's labels included, with the user's
+    /// taking the place of any at the same address.
+    pub fn write_sym<W: Write>(
+        &self,
+        writer: &mut W,
+        symbols: &SymbolTable,
+    ) -> std::io::Result<()> {
+        let mut labels: BTreeMap<(u16, u16), &str> = symbols
+            .iter()
+            .map(|(bank, addr, label)| ((bank, addr), label))
+            .collect();
+        for (&location, annotation) in &self.entries {
+            if !annotation.label.is_empty() {
+                labels.insert(location, &annotation.label);
+            }
+        }
+
+        writeln!(writer, "; File generated by lameboy")?;
+        for ((bank, addr), label) in labels {
+            writeln!(writer, "{bank:02X}:{addr:04X} {label}")?;
+        }
+        writer.flush()
+    }
+
+    pub fn export_sym(&self, path: &Path, symbols: &SymbolTable) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Unable to create symbol file {}: {e}", path.display()))?;
+        self.write_sym(&mut BufWriter::new(file), symbols)
+            .map_err(|e| format!("Unable to write symbol file {}: {e}", path.display()))?;
+        info!("Exported symbols to {}", path.display());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    fn annotation(label: &str, comment: &str) -> Annotation {
+        Annotation {
+            label: label.to_string(),
+            comment: comment.to_string(),
+        }
+    }
+
+    #[test]
+    fn edits_and_round_trips() {
+        let mut annotations = Annotations::new();
+        annotations
+            .set(0, 0x0150, annotation(" Main ", "Entry\tpoint"))
+            .unwrap();
+        annotations
+            .set(1, 0xD000, annotation("", "Scratch"))
+            .unwrap();
+        annotations.set(0, 0x0200, annotation("Gone", "")).unwrap();
+        annotations.set(0, 0x0200, Annotation::default()).unwrap();
+        assert!(annotations
+            .set(0, 0x0300, annotation("Two words", ""))
+            .is_err());
+
+        assert_eq!(
+            Some(&annotation("Main", "Entry point")),
+            annotations.get(0, 0x0150)
+        );
+        assert_eq!(None, annotations.get(0, 0x0200));
+
+        let text = annotations.to_text();
+        assert_eq!("00:0150\tMain\tEntry point\n01:D000\t\tScratch\n", text);
+        assert_eq!(text, Annotations::parse(&text).unwrap().to_text());
+        assert!(Annotations::parse("0150\tMain").is_err());
+    }
+
+    #[test]
+    fn labels_take_precedence_over_symbols() {
+        let symbols = SymbolTable::parse("00:0150 Start\n00:0160 Loop\n").unwrap();
+        let mut annotations = Annotations::new();
+        annotations.set(0, 0x0150, annotation("Main", "")).unwrap();
+        annotations
+            .set(0, 0x0160, annotation("", "Just a comment"))
+            .unwrap();
+
+        let mut lameboy = Lameboy::with_program(&[]);
+        let mmu = lameboy.get_mmu();
+        assert_eq!(Some("Main"), annotations.label(0x0150, mmu, &symbols));
+        assert_eq!(Some("Loop"), annotations.label(0x0160, mmu, &symbols));
+        assert_eq!(
+            Ok(Location::from(0x0150)),
+            annotations.resolve("main", &symbols)
+        );
+        assert_eq!(
+            Ok(Location::from(0x0160)),
+            annotations.resolve("Loop", &symbols)
+        );
+
+        let mut sym = Vec::new();
+        annotations.write_sym(&mut sym, &symbols).unwrap();
+        assert_eq!(
+            "; File generated by lameboy\n00:0150 Main\n00:0160 Loop\n",
+            String::from_utf8(sym).unwrap()
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::dis::memory_locations::get_memory_comment;
+use crate::dis::SymbolTable;
+use crate::lameboy::cpu::opcodes::{lookup, Op, Operand};
+use crate::lameboy::cpu::registers::Reg8;
+
+/// Size of a switchable ROM bank, and of the fixed bank 0.
+pub const ROM_BANK_SIZE: usize = 0x4000;
+
+/// Where execution can start without a jump, the entry point then the restart and interrupt
+/// vectors.
+const VECTORS: [u16; 14] = [
+    0x0100, 0x0000, 0x0008, 0x0010, 0x0018, 0x0020, 0x0028, 0x0030, 0x0038, 0x0040, 0x0048, 0x0050,
+    0x0058, 0x0060,
+];
+
+/// The cartridge header, which is always data however it's reached.
+const HEADER: std::ops::Range<usize> = 0x0104..0x0150;
+
+/// Most bytes written to a single `db` line.
+const DATA_PER_LINE: usize = 16;
+
+/// What kind of label to generate for a branch target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Jump,
+    Call,
+}
+
+/// A static disassembly of a whole ROM, found by following control flow.
+///
+/// Flow is traced from the entry point, the restart and interrupt vectors, and any extra seeds
+/// such as the opcodes in a code/data log. Every byte reached as an instruction is code and the
+/// rest is data, written out as `db` so the output reassembles with RGBDS to the same ROM
+/// whether or not everything was found.
+///
+/// Calls from bank 0 into the switchable bank follow the last `ld a, n` written to the MBC's
+/// bank register, or bank 1 on ROMs too small to switch, and are left unlabelled otherwise.
+pub struct Disassembly<'a> {
+    rom: &'a [u8],
+    /// Length of the instruction starting at each ROM offset, 0 for data or the rest of an
+    /// instruction.
+    lengths: Vec<u8>,
+    /// ROM offsets taken by an instruction, including its operands.
+    code: Vec<bool>,
+    /// ROM offset each branch instruction lands on, where its bank could be known.
+    branches: BTreeMap<usize, usize>,
+    labels: BTreeMap<usize, String>,
+}
+
+impl<'a> Disassembly<'a> {
+    /// Trace the code from the vectors and any other ROM offsets known to hold opcodes, such as
+    /// those from a code/data log.
+    pub fn new(rom: &'a [u8], seeds: &[usize], symbols: &SymbolTable) -> Disassembly<'a> {
+        let mut disassembly = Disassembly {
+            rom,
+            lengths: vec![0; rom.len()],
+            code: vec![false; rom.len()],
+            branches: BTreeMap::new(),
+            labels: BTreeMap::new(),
+        };
+
+        let default_bank = if disassembly.bank_count() <= 2 {
+            Some(1)
+        } else {
+            None
+        };
+        let mut pending: Vec<(usize, Option<u16>)> = Vec::new();
+        for &vector in VECTORS.iter().rev() {
+            // Unused vectors are usually padded with rst $38
+            if rom
+                .get(usize::from(vector))
+                .is_some_and(|&byte| byte != 0xFF)
+            {
+                pending.push((usize::from(vector), default_bank));
+            }
+        }
+        pending.extend(seeds.iter().rev().map(|&seed| (seed, default_bank)));
+
+        let mut targets = BTreeMap::new();
+        while let Some((offset, switched)) = pending.pop() {
+            disassembly.trace(offset, switched, &mut pending, &mut targets);
+        }
+
+        disassembly.name_labels(&targets, symbols);
+        disassembly
+    }
+
+    pub fn bank_count(&self) -> usize {
+        self.rom.len().div_ceil(ROM_BANK_SIZE)
+    }
+
+    /// Number of ROM bytes found to be code.
+    pub fn code_size(&self) -> usize {
+        self.code.iter().filter(|&&code| code).count()
+    }
+
+    pub fn is_instruction(&self, offset: usize) -> bool {
+        self.lengths.get(offset).is_some_and(|&length| length > 0)
+    }
+
+    pub fn label(&self, offset: usize) -> Option<&str> {
+        self.labels.get(&offset).map(String::as_str)
+    }
+
+    /// Follow execution from a ROM offset until it returns, jumps away or runs into something
+    /// that isn't an instruction, queueing up the targets of every branch on the way.
+    fn trace(
+        &mut self,
+        mut offset: usize,
+        mut switched: Option<u16>,
+        pending: &mut Vec<(usize, Option<u16>)>,
+        targets: &mut BTreeMap<usize, Target>,
+    ) {
+        let mut loaded_a: Option<u8> = None;
+
+        while offset < self.rom.len() && !self.code[offset] && !HEADER.contains(&offset) {
+            let opcode = lookup(self.rom[offset], self.byte(offset + 1));
+            let length = usize::from(opcode.length);
+            let fits_in_bank = offset % ROM_BANK_SIZE + length <= ROM_BANK_SIZE;
+            if matches!(opcode.operation, Op::Undefined)
+                || !fits_in_bank
+                || (offset..offset + length).any(|byte| self.code[byte] || HEADER.contains(&byte))
+            {
+                break;
+            }
+
+            self.lengths[offset] = opcode.length;
+            self.code[offset..offset + length].fill(true);
+
+            let addr = Disassembly::addr_of(offset);
+            let d8 = self.byte(offset + 1);
+            let a16 = self.word(offset + 1);
+            let branch = match opcode.operation {
+                Op::JumpD16 | Op::JumpConditionalD16 => Some((a16, Target::Jump)),
+                Op::CallD16 | Op::CallConditionalD16 => Some((a16, Target::Call)),
+                Op::JumpRelativeD8 | Op::JumpRelativeConditionalD8 => Some((
+                    addr.wrapping_add(2).wrapping_add(d8 as i8 as u16),
+                    Target::Jump,
+                )),
+                Op::Restart => Some((u16::from(self.rom[offset] & 0x38), Target::Call)),
+                _ => None,
+            };
+            if let Some((target, kind)) = branch {
+                if let Some((target_offset, target_switched)) =
+                    self.resolve(offset, target, switched)
+                {
+                    let existing = targets.entry(target_offset).or_insert(kind);
+                    if kind == Target::Call {
+                        *existing = Target::Call;
+                    }
+                    self.branches.insert(offset, target_offset);
+                    pending.push((target_offset, target_switched));
+                }
+            }
+
+            // Track bank switches so calls from bank 0 can be followed into the right bank
+            match opcode.operation {
+                Op::LoadR8D8(Reg8::A) => loaded_a = Some(d8),
+                Op::LoadA16RegA if (0x2000..0x4000).contains(&a16) => {
+                    if let Some(bank) = loaded_a {
+                        switched = Some(u16::from(bank).max(1));
+                    }
+                }
+                Op::LoadHighMemD8RegA | Op::LoadA16RegA => {}
+                _ => loaded_a = None,
+            }
+
+            let ends_flow = match opcode.operation {
+                Op::JumpD16 | Op::JumpRelativeD8 | Op::JumpR16(_) | Op::Ret | Op::RetInterrupt => {
+                    true
+                }
+                // Usually a crash handler, or padding that's been run into
+                Op::Restart => self.rom[offset] == 0xFF,
+                _ => false,
+            };
+            if ends_flow {
+                break;
+            }
+
+            offset += length;
+        }
+    }
+
+    /// The ROM offset a branch from an instruction lands on, if the bank it's in can be known,
+    /// along with the bank mapped once there.
+    fn resolve(
+        &self,
+        from: usize,
+        target: u16,
+        switched: Option<u16>,
+    ) -> Option<(usize, Option<u16>)> {
+        let bank = match target {
+            0x0000..=0x3FFF => return Some((usize::from(target), switched)),
+            0x4000..=0x7FFF if from >= ROM_BANK_SIZE => (from / ROM_BANK_SIZE) as u16,
+            0x4000..=0x7FFF => switched?,
+            _ => return None,
+        };
+
+        let offset = usize::from(bank) * ROM_BANK_SIZE + usize::from(target) - ROM_BANK_SIZE;
+        Some((offset, Some(bank))).filter(|&(offset, _)| offset < self.rom.len())
+    }
+
+    /// Name every branch target that starts an instruction, along with the vectors and header
+    /// fields, preferring any name the symbol file has that RGBDS can use.
+    fn name_labels(&mut self, targets: &BTreeMap<usize, Target>, symbols: &SymbolTable) {
+        let mut names: BTreeMap<usize, String> = BTreeMap::new();
+        for (&offset, &kind) in targets {
+            let (bank, addr) = Disassembly::location_of(offset);
+            let prefix = match kind {
+                Target::Jump => "Jump",
+                Target::Call => "Call",
+            };
+            names.insert(offset, format!("{prefix}_{bank:02X}_{addr:04X}"));
+        }
+        for addr in 0..HEADER.end as u16 {
+            if let Some(name) = get_memory_comment(addr) {
+                names.insert(usize::from(addr), name.to_string());
+            }
+        }
+
+        for (bank, addr, name) in symbols.iter() {
+            let offset = match addr {
+                0x0000..=0x3FFF => usize::from(addr),
+                0x4000..=0x7FFF => {
+                    usize::from(bank.max(1)) * ROM_BANK_SIZE + usize::from(addr) - ROM_BANK_SIZE
+                }
+                _ => continue,
+            };
+            if is_identifier(name) {
+                names.insert(offset, name.to_string());
+            }
+        }
+
+        let mut used = HashSet::new();
+        for (offset, name) in names {
+            let inside_instruction =
+                self.code.get(offset).is_some_and(|&code| code) && !self.is_instruction(offset);
+            if offset >= self.rom.len() || inside_instruction {
+                continue;
+            }
+
+            // Labels have to be unique, so qualify any name used twice with where it is
+            let name = if used.contains(&name) {
+                let (bank, addr) = Disassembly::location_of(offset);
+                format!("{name}_{bank:02X}_{addr:04X}")
+            } else {
+                name
+            };
+            used.insert(name.clone());
+            self.labels.insert(offset, name);
+        }
+    }
+
+    /// Write one bank as an RGBDS section.
+    pub fn write_bank<W: Write>(&self, bank: usize, writer: &mut W) -> std::io::Result<()> {
+        if bank == 0 {
+            writeln!(writer, "SECTION \"ROM Bank $00\", ROM0[$0000]")?;
+        } else {
+            writeln!(
+                writer,
+                "SECTION \"ROM Bank ${bank:02X}\", ROMX[$4000], BANK[${bank:02X}]"
+            )?;
+        }
+
+        let start = bank * ROM_BANK_SIZE;
+        let end = (start + ROM_BANK_SIZE).min(self.rom.len());
+        let mut offset = start;
+        while offset < end {
+            if let Some(label) = self.label(offset) {
+                writeln!(writer, "\n{label}:")?;
+            }
+
+            if self.is_instruction(offset) {
+                writeln!(writer, "    {}", self.instruction_text(offset))?;
+                offset += usize::from(self.lengths[offset]);
+            } else {
+                let mut data_end = offset + 1;
+                while data_end < end
+                    && data_end - offset < DATA_PER_LINE
+                    && !self.is_instruction(data_end)
+                    && self.label(data_end).is_none()
+                {
+                    data_end += 1;
+                }
+                let bytes: Vec<String> = self.rom[offset..data_end]
+                    .iter()
+                    .map(|byte| format!("${byte:02X}"))
+                    .collect();
+                writeln!(writer, "    db {}", bytes.join(", "))?;
+                offset = data_end;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Write every bank to its own `bank_XX.asm` in a directory, along with a `game.asm` that
+    /// includes them all.
+    pub fn export(&self, dir: &Path, rom_name: &str) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Unable to create {}: {e}", dir.display()))?;
+
+        let mut main = format!(
+            "; Disassembled by lameboy from {rom_name}\n\
+             ; Build from this directory with RGBDS:\n\
+             ;   rgbasm -o game.o game.asm && rgblink -o game.gb game.o\n\n"
+        );
+        for bank in 0..self.bank_count() {
+            let file_name = format!("bank_{bank:02X}.asm");
+            let path = dir.join(&file_name);
+            let file = File::create(&path)
+                .map_err(|e| format!("Unable to create {}: {e}", path.display()))?;
+            self.write_bank(bank, &mut BufWriter::new(file))
+                .map_err(|e| format!("Unable to write {}: {e}", path.display()))?;
+            main.push_str(&format!("INCLUDE \"{file_name}\"\n"));
+        }
+
+        let path = dir.join("game.asm");
+        fs::write(&path, main).map_err(|e| format!("Unable to write {}: {e}", path.display()))
+    }
+
+    /// An instruction in RGBDS syntax, with branch targets replaced by their labels.
+    fn instruction_text(&self, offset: usize) -> String {
+        let opcode = lookup(self.rom[offset], self.byte(offset + 1));
+        let d8 = self.byte(offset + 1);
+        let d16 = self.word(offset + 1);
+        let addr = Disassembly::addr_of(offset);
+
+        match opcode.operation {
+            // RGBDS always assembles stop with a zero second byte
+            Op::Stop if d8 != 0 => return format!("db $10, ${d8:02X}"),
+            Op::Stop => return String::from("stop"),
+            Op::LoadHighMemRegCRegA => return String::from("ldh [c], a"),
+            Op::LoadRegAHighMemRegC => return String::from("ldh a, [c]"),
+            _ => {}
+        }
+
+        let text = match opcode.operand {
+            None => String::from(opcode.mnemonic),
+            Some(Operand::Data8) => opcode.mnemonic.replace("d8", &format!("${d8:02X}")),
+            Some(Operand::Address8) => opcode
+                .mnemonic
+                .replace("a8", &format!("${:04X}", 0xFF00 | u16::from(d8))),
+            Some(Operand::Data16) => opcode.mnemonic.replace("d16", &format!("${d16:04X}")),
+            Some(Operand::Address16) => match opcode.operation {
+                Op::JumpD16 | Op::JumpConditionalD16 | Op::CallD16 | Op::CallConditionalD16 => {
+                    opcode
+                        .mnemonic
+                        .replace("a16", &self.target_text(offset, d16))
+                }
+                _ => opcode.mnemonic.replace("a16", &format!("${d16:04X}")),
+            },
+            Some(Operand::Signed8) => {
+                let offset_value = i16::from(d8 as i8);
+                match opcode.operation {
+                    Op::LoadRegHlRegSpD8 if offset_value < 0 => {
+                        format!("ld hl, sp - {}", -offset_value)
+                    }
+                    Op::LoadRegHlRegSpD8 => format!("ld hl, sp + {offset_value}"),
+                    Op::AddSpD8 => format!("add sp, {offset_value}"),
+                    _ => {
+                        let target = addr.wrapping_add(2).wrapping_add(offset_value as u16);
+                        opcode
+                            .mnemonic
+                            .replace("pc+r8", &self.target_text(offset, target))
+                    }
+                }
+            }
+        };
+
+        let io_addr = match opcode.operand {
+            Some(Operand::Address8) => Some(0xFF00 | u16::from(d8)),
+            Some(Operand::Address16) if d16 >= 0x8000 => Some(d16),
+            _ => None,
+        };
+        match io_addr.and_then(get_memory_comment) {
+            Some(comment) => format!("{text: <24}; {comment}"),
+            None => text,
+        }
+    }
+
+    /// A branch target as its label if it has one, otherwise as an address.
+    fn target_text(&self, from: usize, target: u16) -> String {
+        match self
+            .branches
+            .get(&from)
+            .and_then(|&offset| self.label(offset))
+        {
+            Some(label) => label.to_string(),
+            None => format!("${target:04X}"),
+        }
+    }
+
+    fn byte(&self, offset: usize) -> u8 {
+        self.rom.get(offset).copied().unwrap_or(0)
+    }
+
+    fn word(&self, offset: usize) -> u16 {
+        u16::from(self.byte(offset + 1)) << 8 | u16::from(self.byte(offset))
+    }
+
+    /// The address a ROM offset is seen at when its bank is mapped.
+    fn addr_of(offset: usize) -> u16 {
+        if offset < ROM_BANK_SIZE {
+            offset as u16
+        } else {
+            (ROM_BANK_SIZE + offset % ROM_BANK_SIZE) as u16
+        }
+    }
+
+    fn location_of(offset: usize) -> (usize, u16) {
+        (offset / ROM_BANK_SIZE, Disassembly::addr_of(offset))
+    }
+}
+
+/// Can a name be used as an RGBDS label as is.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_rom() -> Vec<u8> {
+        let mut rom = vec![0xFF; 4 * ROM_BANK_SIZE];
+        // VBlank: reti
+        rom[0x0040] = 0xD9;
+        // nop; jp $0150
+        rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+        // ld a, $03; ld [$2000], a; call $4000; jr @
+        rom[0x0150..0x015A]
+            .copy_from_slice(&[0x3E, 0x03, 0xEA, 0x00, 0x20, 0xCD, 0x00, 0x40, 0x18, 0xFE]);
+        // inc a; ret, only reached by the code/data log
+        rom[0x0200..0x0202].copy_from_slice(&[0x3C, 0xC9]);
+        // Bank 3: ldh a, [$FF44]; ret
+        rom[3 * ROM_BANK_SIZE..3 * ROM_BANK_SIZE + 3].copy_from_slice(&[0xF0, 0x44, 0xC9]);
+        rom
+    }
+
+    fn bank_text(disassembly: &Disassembly, bank: usize) -> String {
+        let mut text = Vec::new();
+        disassembly.write_bank(bank, &mut text).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn follows_flow_across_banks() {
+        let rom = test_rom();
+        let disassembly = Disassembly::new(&rom, &[], &SymbolTable::new());
+
+        for offset in [0x0040, 0x0100, 0x0101, 0x0150, 0x0158, 0xC000, 0xC002] {
+            assert!(disassembly.is_instruction(offset), "0x{offset:04X}");
+        }
+        assert!(!disassembly.is_instruction(0x0000));
+        assert!(!disassembly.is_instruction(0x0104));
+        assert!(!disassembly.is_instruction(0x0200));
+        assert_eq!(18, disassembly.code_size());
+
+        assert_eq!(Some("Boot"), disassembly.label(0x0100));
+        assert_eq!(Some("Jump_00_0150"), disassembly.label(0x0150));
+        assert_eq!(Some("Call_03_4000"), disassembly.label(0xC000));
+
+        let bank_0 = bank_text(&disassembly, 0);
+        assert!(bank_0.starts_with("SECTION \"ROM Bank $00\", ROM0[$0000]\n\nRST_00:\n"));
+        assert!(
+            bank_0.contains("\nBoot:\n    nop\n    jp Jump_00_0150\n\nHeaderLogo:\n    db $FF,")
+        );
+        assert!(bank_0.contains("    ld a, $03\n    ld [$2000], a\n    call Call_03_4000\n"));
+        assert!(bank_0.contains("\nJump_00_0158:\n    jr Jump_00_0158\n"));
+
+        let bank_3 = bank_text(&disassembly, 3);
+        assert!(bank_3.starts_with(
+            "SECTION \"ROM Bank $03\", ROMX[$4000], BANK[$03]\n\n\
+             Call_03_4000:\n    ldh a, [$FF44]          ; rLY\n    ret\n    db $FF,"
+        ));
+    }
+
+    #[test]
+    fn extra_seeds_and_symbols() {
+        let rom = test_rom();
+        let symbols = SymbolTable::parse("03:4000 ReadLY\n00:0150 Main.local\n").unwrap();
+
+        let disassembly = Disassembly::new(&rom, &[0x0200, 0x0201], &symbols);
+        assert!(disassembly.is_instruction(0x0200));
+        assert!(disassembly.is_instruction(0x0201));
+        assert_eq!(Some("ReadLY"), disassembly.label(0xC000));
+        assert_eq!(Some("Jump_00_0150"), disassembly.label(0x0150));
+        assert!(bank_text(&disassembly, 0).contains("    call ReadLY\n"));
+    }
+}
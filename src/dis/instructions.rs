@@ -1,555 +1,13 @@
-use crate::dis::Instruction;
-use crate::dis::InstructionArg::{Data16, Data8};
+use crate::lameboy::cpu::opcodes::{lookup, Opcode};
+use crate::lameboy::mmu::location::Location;
 use crate::lameboy::mmu::Mmu;
 
-pub fn decode_instruction(instruction_addr: u16, mmu: &Mmu) -> Instruction {
-    let opcode = mmu.read8_safe(instruction_addr);
+/// Decode the instruction at a location using the CPU's opcode table, following the CB prefix if
+/// there is one. A location pinned to a bank is read from that bank whether or not it is mapped.
+pub fn decode_instruction(location: impl Into<Location>, mmu: &Mmu) -> &'static Opcode {
+    let location = location.into();
+    let opcode = mmu.read_location(location);
+    let next_byte = mmu.read_location(location.with_addr(location.addr.wrapping_add(1)));
 
-    match opcode {
-        0x00 => Instruction::new("nop", None),
-        0x01 => Instruction::new("ld bc, d16", Some(Data16)),
-        0x02 => Instruction::new("ld [bc], a", None),
-        0x03 => Instruction::new("inc bc", None),
-        0x04 => Instruction::new("inc b", None),
-        0x05 => Instruction::new("dec b", None),
-        0x06 => Instruction::new("ld b, d8", Some(Data8)),
-        0x07 => Instruction::new("rlca", None),
-        0x08 => Instruction::new("ld [a16], sp", Some(Data16)),
-        0x09 => Instruction::new("add hl, bc", None),
-        0x0A => Instruction::new("ld a, [bc]", None),
-        0x0B => Instruction::new("dec bc", None),
-        0x0C => Instruction::new("inc c", None),
-        0x0D => Instruction::new("dec c", None),
-        0x0E => Instruction::new("ld c, d8", Some(Data8)),
-        0x0F => Instruction::new("rrca", None),
-
-        0x10 => Instruction::new("stop", Some(Data8)),
-        0x11 => Instruction::new("ld de, d16", Some(Data16)),
-        0x12 => Instruction::new("ld [de], a", None),
-        0x13 => Instruction::new("inc de", None),
-        0x14 => Instruction::new("inc d", None),
-        0x15 => Instruction::new("dec d", None),
-        0x16 => Instruction::new("ld d, d8", Some(Data8)),
-        0x17 => Instruction::new("rla", None),
-        0x18 => Instruction::new("jr pc+r8", Some(Data8)),
-        0x19 => Instruction::new("add hl, de", None),
-        0x1A => Instruction::new("ld a, [de]", None),
-        0x1B => Instruction::new("dec de", None),
-        0x1C => Instruction::new("inc e", None),
-        0x1D => Instruction::new("dec e", None),
-        0x1E => Instruction::new("ld e, d8", Some(Data8)),
-        0x1F => Instruction::new("rra", None),
-
-        0x20 => Instruction::new("jr nz, pc+r8", Some(Data8)),
-        0x21 => Instruction::new("ld hl, d16", Some(Data16)),
-        0x22 => Instruction::new("ld [hl+], a", None),
-        0x23 => Instruction::new("inc hl", None),
-        0x24 => Instruction::new("inc h", None),
-        0x25 => Instruction::new("dec h", None),
-        0x26 => Instruction::new("ld h, d8", Some(Data8)),
-        0x27 => Instruction::new("daa", None),
-        0x28 => Instruction::new("jr z, pc+r8", Some(Data8)),
-        0x29 => Instruction::new("add hl, hl", None),
-        0x2A => Instruction::new("ld a, [hl+]", None),
-        0x2B => Instruction::new("dec hl", None),
-        0x2C => Instruction::new("inc l", None),
-        0x2D => Instruction::new("dec l", None),
-        0x2E => Instruction::new("ld l, d8", Some(Data8)),
-        0x2F => Instruction::new("cpl", None),
-
-        0x30 => Instruction::new("jr nc, pc+r8", Some(Data8)),
-        0x31 => Instruction::new("ld sp, d16", None),
-        0x32 => Instruction::new("ld [hl-], a", None),
-        0x33 => Instruction::new("inc sp", None),
-        0x34 => Instruction::new("inc [hl]", None),
-        0x35 => Instruction::new("dec [hl]", None),
-        0x36 => Instruction::new("ld [hl], d8", Some(Data8)),
-        0x37 => Instruction::new("scf", None),
-        0x38 => Instruction::new("jr c, pc+r8", Some(Data8)),
-        0x39 => Instruction::new("add hl, sp", None),
-        0x3A => Instruction::new("ld a, [hl-]", None),
-        0x3B => Instruction::new("dec sp", None),
-        0x3C => Instruction::new("inc a", None),
-        0x3D => Instruction::new("dec a", None),
-        0x3E => Instruction::new("ld a, d8", Some(Data8)),
-        0x3F => Instruction::new("ccf", None),
-
-        // Loads
-        0x40 => Instruction::new("ld b, b", None),
-        0x41 => Instruction::new("ld b, c", None),
-        0x42 => Instruction::new("ld b, d", None),
-        0x43 => Instruction::new("ld b, e", None),
-        0x44 => Instruction::new("ld b, h", None),
-        0x45 => Instruction::new("ld b, l", None),
-        0x46 => Instruction::new("ld b, [hl]", None),
-        0x47 => Instruction::new("ld b, a", None),
-        0x48 => Instruction::new("ld c, b", None),
-        0x49 => Instruction::new("ld c, c", None),
-        0x4A => Instruction::new("ld c, d", None),
-        0x4B => Instruction::new("ld c, e", None),
-        0x4C => Instruction::new("ld c, h", None),
-        0x4D => Instruction::new("ld c, l", None),
-        0x4E => Instruction::new("ld c, [hl]", None),
-        0x4F => Instruction::new("ld c, a", None),
-
-        0x50 => Instruction::new("ld d, b", None),
-        0x51 => Instruction::new("ld d, c", None),
-        0x52 => Instruction::new("ld d, d", None),
-        0x53 => Instruction::new("ld d, e", None),
-        0x54 => Instruction::new("ld d, h", None),
-        0x55 => Instruction::new("ld d, l", None),
-        0x56 => Instruction::new("ld d, [hl]", None),
-        0x57 => Instruction::new("ld d, a", None),
-        0x58 => Instruction::new("ld e, b", None),
-        0x59 => Instruction::new("ld e, c", None),
-        0x5A => Instruction::new("ld e, d", None),
-        0x5B => Instruction::new("ld e, e", None),
-        0x5C => Instruction::new("ld e, h", None),
-        0x5D => Instruction::new("ld e, l", None),
-        0x5E => Instruction::new("ld e, [hl]", None),
-        0x5F => Instruction::new("ld e, a", None),
-
-        0x60 => Instruction::new("ld h, b", None),
-        0x61 => Instruction::new("ld h, c", None),
-        0x62 => Instruction::new("ld h, d", None),
-        0x63 => Instruction::new("ld h, e", None),
-        0x64 => Instruction::new("ld h, h", None),
-        0x65 => Instruction::new("ld h, l", None),
-        0x66 => Instruction::new("ld h, [hl]", None),
-        0x67 => Instruction::new("ld h, a", None),
-        0x68 => Instruction::new("ld l, b", None),
-        0x69 => Instruction::new("ld l, c", None),
-        0x6A => Instruction::new("ld l, d", None),
-        0x6B => Instruction::new("ld l, e", None),
-        0x6C => Instruction::new("ld l, h", None),
-        0x6D => Instruction::new("ld l, l", None),
-        0x6E => Instruction::new("ld l, [hl]", None),
-        0x6F => Instruction::new("ld l, a", None),
-
-        0x70 => Instruction::new("ld [hl], b", None),
-        0x71 => Instruction::new("ld [hl], c", None),
-        0x72 => Instruction::new("ld [hl], d", None),
-        0x73 => Instruction::new("ld [hl], e", None),
-        0x74 => Instruction::new("ld [hl], h", None),
-        0x75 => Instruction::new("ld [hl], l", None),
-        0x76 => Instruction::new("halt", None),
-        0x77 => Instruction::new("ld [hl], a", None),
-        0x78 => Instruction::new("ld a, b", None),
-        0x79 => Instruction::new("ld a, c", None),
-        0x7A => Instruction::new("ld a, d", None),
-        0x7B => Instruction::new("ld a, e", None),
-        0x7C => Instruction::new("ld a, h", None),
-        0x7D => Instruction::new("ld a, l", None),
-        0x7E => Instruction::new("ld a, [hl]", None),
-        0x7F => Instruction::new("ld a, a", None),
-
-        // ADDs
-        0x80 => Instruction::new("add b", None),
-        0x81 => Instruction::new("add c", None),
-        0x82 => Instruction::new("add d", None),
-        0x83 => Instruction::new("add e", None),
-        0x84 => Instruction::new("add h", None),
-        0x85 => Instruction::new("add l", None),
-        0x86 => Instruction::new("add [hl]", None),
-        0x87 => Instruction::new("add a", None),
-        0x88 => Instruction::new("adc b", None),
-        0x89 => Instruction::new("adc c", None),
-        0x8A => Instruction::new("adc d", None),
-        0x8B => Instruction::new("adc e", None),
-        0x8C => Instruction::new("adc h", None),
-        0x8D => Instruction::new("adc l", None),
-        0x8E => Instruction::new("adc [hl]", None),
-        0x8F => Instruction::new("adc a", None),
-
-        // SUBs
-        0x90 => Instruction::new("sub b", None),
-        0x91 => Instruction::new("sub c", None),
-        0x92 => Instruction::new("sub d", None),
-        0x93 => Instruction::new("sub e", None),
-        0x94 => Instruction::new("sub h", None),
-        0x95 => Instruction::new("sub l", None),
-        0x96 => Instruction::new("sub [hl]", None),
-        0x97 => Instruction::new("sub a", None),
-        0x98 => Instruction::new("sbc b", None),
-        0x99 => Instruction::new("sbc c", None),
-        0x9A => Instruction::new("sbc d", None),
-        0x9B => Instruction::new("sbc e", None),
-        0x9C => Instruction::new("sbc h", None),
-        0x9D => Instruction::new("sbc l", None),
-        0x9E => Instruction::new("sbc [hl]", None),
-        0x9F => Instruction::new("sbc a", None),
-
-        // ANDs & XORs
-        0xA0 => Instruction::new("and b", None),
-        0xA1 => Instruction::new("and c", None),
-        0xA2 => Instruction::new("and d", None),
-        0xA3 => Instruction::new("and e", None),
-        0xA4 => Instruction::new("and h", None),
-        0xA5 => Instruction::new("and l", None),
-        0xA6 => Instruction::new("and [hl]", None),
-        0xA7 => Instruction::new("and a", None),
-        0xA8 => Instruction::new("xor b", None),
-        0xA9 => Instruction::new("xor c", None),
-        0xAA => Instruction::new("xor d", None),
-        0xAB => Instruction::new("xor e", None),
-        0xAC => Instruction::new("xor h", None),
-        0xAD => Instruction::new("xor l", None),
-        0xAE => Instruction::new("xor [hl]", None),
-        0xAF => Instruction::new("xor a", None),
-
-        // ORs & CPs
-        0xB0 => Instruction::new("or b", None),
-        0xB1 => Instruction::new("or c", None),
-        0xB2 => Instruction::new("or d", None),
-        0xB3 => Instruction::new("or e", None),
-        0xB4 => Instruction::new("or h", None),
-        0xB5 => Instruction::new("or l", None),
-        0xB6 => Instruction::new("or [hl]", None),
-        0xB7 => Instruction::new("or a", None),
-        0xB8 => Instruction::new("cp b", None),
-        0xB9 => Instruction::new("cp c", None),
-        0xBA => Instruction::new("cp d", None),
-        0xBB => Instruction::new("cp e", None),
-        0xBC => Instruction::new("cp h", None),
-        0xBD => Instruction::new("cp l", None),
-        0xBE => Instruction::new("cp [hl]", None),
-        0xBF => Instruction::new("cp a", None),
-
-        0xC0 => Instruction::new("ret nz", None),
-        0xC1 => Instruction::new("pop bc", None),
-        0xC2 => Instruction::new("jp nz,a16", Some(Data16)),
-        0xC3 => Instruction::new("jp a16", Some(Data16)),
-        0xC4 => Instruction::new("call nz,a16", Some(Data16)),
-        0xC5 => Instruction::new("push bc", None),
-        0xC6 => Instruction::new("add d8", Some(Data8)),
-        0xC7 => Instruction::new("rst $00", None),
-        0xC8 => Instruction::new("ret z", None),
-        0xC9 => Instruction::new("ret", None),
-        0xCA => Instruction::new("jp z,a16", Some(Data16)),
-        0xCB => decode_cb_prefix_instruction(instruction_addr, mmu),
-        0xCC => Instruction::new("call z,a16", Some(Data16)),
-        0xCD => Instruction::new("call a16", Some(Data16)),
-        0xCE => Instruction::new("adc d8", Some(Data8)),
-        0xCF => Instruction::new("rst $08", None),
-
-        0xD0 => Instruction::new("ret nc", None),
-        0xD1 => Instruction::new("pop de", None),
-        0xD2 => Instruction::new("jp nc, a16", Some(Data16)),
-        0xD3 => Instruction::new("db $d3", None),
-        0xD4 => Instruction::new("call nc, a16", Some(Data16)),
-        0xD5 => Instruction::new("push de", None),
-        0xD6 => Instruction::new("sub d8", Some(Data8)),
-        0xD7 => Instruction::new("rst $10", None),
-        0xD8 => Instruction::new("ret c", None),
-        0xD9 => Instruction::new("reti", None),
-        0xDA => Instruction::new("jp c, a16", Some(Data16)),
-        0xDB => Instruction::new("db $db", None),
-        0xDC => Instruction::new("call c, a16", Some(Data16)),
-        0xDD => Instruction::new("db $dd", None),
-        0xDE => Instruction::new("sbc d8", Some(Data8)),
-        0xDF => Instruction::new("rst $18", None),
-
-        0xE0 => Instruction::new("ldh [a8], a", Some(Data8)),
-        0xE1 => Instruction::new("pop hl", None),
-        0xE2 => Instruction::new("ld [c], a", None),
-        0xE3 => Instruction::new("db $e3", None),
-        0xE4 => Instruction::new("db $e4", None),
-        0xE5 => Instruction::new("push hl", None),
-        0xE6 => Instruction::new("and d8", Some(Data8)),
-        0xE7 => Instruction::new("rst $20", None),
-        0xE8 => Instruction::new("add sp, r8", Some(Data8)),
-        0xE9 => Instruction::new("jp hl", None),
-        0xEA => Instruction::new("ld [a16], a", Some(Data16)),
-        0xEB => Instruction::new("db $eb", None),
-        0xEC => Instruction::new("db $ec", None),
-        0xED => Instruction::new("db $ed", None),
-        0xEE => Instruction::new("xor d8", Some(Data8)),
-        0xEF => Instruction::new("rst $28", None),
-
-        0xF0 => Instruction::new("ldh a, [a8]", Some(Data8)),
-        0xF1 => Instruction::new("pop af", None),
-        0xF2 => Instruction::new("ld a, [c]", None),
-        0xF3 => Instruction::new("di", None),
-        0xF4 => Instruction::new("db $f4", None),
-        0xF5 => Instruction::new("push af", None),
-        0xF6 => Instruction::new("or d8", Some(Data8)),
-        0xF7 => Instruction::new("rst $30", None),
-        0xF8 => Instruction::new("ld hl, sp+r8", Some(Data8)),
-        0xF9 => Instruction::new("ld sp, hl", None),
-        0xFA => Instruction::new("ld a, [a16]", Some(Data16)),
-        0xFB => Instruction::new("ei", None),
-        0xFC => Instruction::new("db $fc", None),
-        0xFD => Instruction::new("db $fd", None),
-        0xFE => Instruction::new("cp d8", Some(Data8)),
-        0xFF => Instruction::new("rst $38", None),
-    }
-}
-
-fn decode_cb_prefix_instruction(instruction_addr: u16, mmu: &Mmu) -> Instruction {
-    let opcode = mmu.read8_safe(instruction_addr.wrapping_add(1));
-
-    match opcode {
-        0x00 => Instruction::new("rlc b", Some(Data8)),
-        0x01 => Instruction::new("rlc c", Some(Data8)),
-        0x02 => Instruction::new("rlc d", Some(Data8)),
-        0x03 => Instruction::new("rlc e", Some(Data8)),
-        0x04 => Instruction::new("rlc h", Some(Data8)),
-        0x05 => Instruction::new("rlc l", Some(Data8)),
-        0x06 => Instruction::new("rlc [hl]", Some(Data8)),
-        0x07 => Instruction::new("rlc a", Some(Data8)),
-        0x08 => Instruction::new("rrc b", Some(Data8)),
-        0x09 => Instruction::new("rrc c", Some(Data8)),
-        0x0A => Instruction::new("rrc d", Some(Data8)),
-        0x0B => Instruction::new("rrc e", Some(Data8)),
-        0x0C => Instruction::new("rrc h", Some(Data8)),
-        0x0D => Instruction::new("rrc l", Some(Data8)),
-        0x0E => Instruction::new("rrc [hl]", Some(Data8)),
-        0x0F => Instruction::new("rrc a", Some(Data8)),
-
-        0x10 => Instruction::new("rl b", Some(Data8)),
-        0x11 => Instruction::new("rl c", Some(Data8)),
-        0x12 => Instruction::new("rl d", Some(Data8)),
-        0x13 => Instruction::new("rl e", Some(Data8)),
-        0x14 => Instruction::new("rl h", Some(Data8)),
-        0x15 => Instruction::new("rl l", Some(Data8)),
-        0x16 => Instruction::new("rl [hl]", Some(Data8)),
-        0x17 => Instruction::new("rl a", Some(Data8)),
-        0x18 => Instruction::new("rr b", Some(Data8)),
-        0x19 => Instruction::new("rr c", Some(Data8)),
-        0x1A => Instruction::new("rr d", Some(Data8)),
-        0x1B => Instruction::new("rr e", Some(Data8)),
-        0x1C => Instruction::new("rr h", Some(Data8)),
-        0x1D => Instruction::new("rr l", Some(Data8)),
-        0x1E => Instruction::new("rr [hl]", Some(Data8)),
-        0x1F => Instruction::new("rr a", Some(Data8)),
-
-        0x20 => Instruction::new("sla b", Some(Data8)),
-        0x21 => Instruction::new("sla c", Some(Data8)),
-        0x22 => Instruction::new("sla d", Some(Data8)),
-        0x23 => Instruction::new("sla e", Some(Data8)),
-        0x24 => Instruction::new("sla h", Some(Data8)),
-        0x25 => Instruction::new("sla l", Some(Data8)),
-        0x26 => Instruction::new("sla [hl]", Some(Data8)),
-        0x27 => Instruction::new("sla a", Some(Data8)),
-        0x28 => Instruction::new("sra b", Some(Data8)),
-        0x29 => Instruction::new("sra c", Some(Data8)),
-        0x2A => Instruction::new("sra d", Some(Data8)),
-        0x2B => Instruction::new("sra e", Some(Data8)),
-        0x2C => Instruction::new("sra h", Some(Data8)),
-        0x2D => Instruction::new("sra l", Some(Data8)),
-        0x2E => Instruction::new("sra [hl]", Some(Data8)),
-        0x2F => Instruction::new("sra a", Some(Data8)),
-
-        0x30 => Instruction::new("swap b", Some(Data8)),
-        0x31 => Instruction::new("swap c", Some(Data8)),
-        0x32 => Instruction::new("swap d", Some(Data8)),
-        0x33 => Instruction::new("swap e", Some(Data8)),
-        0x34 => Instruction::new("swap h", Some(Data8)),
-        0x35 => Instruction::new("swap l", Some(Data8)),
-        0x36 => Instruction::new("swap [hl]", Some(Data8)),
-        0x37 => Instruction::new("swap a", Some(Data8)),
-        0x38 => Instruction::new("srl b", Some(Data8)),
-        0x39 => Instruction::new("srl c", Some(Data8)),
-        0x3A => Instruction::new("srl d", Some(Data8)),
-        0x3B => Instruction::new("srl e", Some(Data8)),
-        0x3C => Instruction::new("srl h", Some(Data8)),
-        0x3D => Instruction::new("srl l", Some(Data8)),
-        0x3E => Instruction::new("srl [hl]", Some(Data8)),
-        0x3F => Instruction::new("srl a", Some(Data8)),
-
-        0x40 => Instruction::new("bit 0, b", Some(Data8)),
-        0x41 => Instruction::new("bit 0, c", Some(Data8)),
-        0x42 => Instruction::new("bit 0, d", Some(Data8)),
-        0x43 => Instruction::new("bit 0, e", Some(Data8)),
-        0x44 => Instruction::new("bit 0, h", Some(Data8)),
-        0x45 => Instruction::new("bit 0, l", Some(Data8)),
-        0x46 => Instruction::new("bit 0, [hl]", Some(Data8)),
-        0x47 => Instruction::new("bit 0, a", Some(Data8)),
-        0x48 => Instruction::new("bit 1, b", Some(Data8)),
-        0x49 => Instruction::new("bit 1, c", Some(Data8)),
-        0x4A => Instruction::new("bit 1, d", Some(Data8)),
-        0x4B => Instruction::new("bit 1, e", Some(Data8)),
-        0x4C => Instruction::new("bit 1, h", Some(Data8)),
-        0x4D => Instruction::new("bit 1, l", Some(Data8)),
-        0x4E => Instruction::new("bit 1, [hl]", Some(Data8)),
-        0x4F => Instruction::new("bit 1, a", Some(Data8)),
-        0x50 => Instruction::new("bit 2, b", Some(Data8)),
-        0x51 => Instruction::new("bit 2, c", Some(Data8)),
-        0x52 => Instruction::new("bit 2, d", Some(Data8)),
-        0x53 => Instruction::new("bit 2, e", Some(Data8)),
-        0x54 => Instruction::new("bit 2, h", Some(Data8)),
-        0x55 => Instruction::new("bit 2, l", Some(Data8)),
-        0x56 => Instruction::new("bit 2, [hl]", Some(Data8)),
-        0x57 => Instruction::new("bit 2, a", Some(Data8)),
-        0x58 => Instruction::new("bit 3, b", Some(Data8)),
-        0x59 => Instruction::new("bit 3, c", Some(Data8)),
-        0x5A => Instruction::new("bit 3, d", Some(Data8)),
-        0x5B => Instruction::new("bit 3, e", Some(Data8)),
-        0x5C => Instruction::new("bit 3, h", Some(Data8)),
-        0x5D => Instruction::new("bit 3, l", Some(Data8)),
-        0x5E => Instruction::new("bit 3, [hl]", Some(Data8)),
-        0x5F => Instruction::new("bit 3, a", Some(Data8)),
-        0x60 => Instruction::new("bit 4, b", Some(Data8)),
-        0x61 => Instruction::new("bit 4, c", Some(Data8)),
-        0x62 => Instruction::new("bit 4, d", Some(Data8)),
-        0x63 => Instruction::new("bit 4, e", Some(Data8)),
-        0x64 => Instruction::new("bit 4, h", Some(Data8)),
-        0x65 => Instruction::new("bit 4, l", Some(Data8)),
-        0x66 => Instruction::new("bit 4, [hl]", Some(Data8)),
-        0x67 => Instruction::new("bit 4, a", Some(Data8)),
-        0x68 => Instruction::new("bit 5, b", Some(Data8)),
-        0x69 => Instruction::new("bit 5, c", Some(Data8)),
-        0x6A => Instruction::new("bit 5, d", Some(Data8)),
-        0x6B => Instruction::new("bit 5, e", Some(Data8)),
-        0x6C => Instruction::new("bit 5, h", Some(Data8)),
-        0x6D => Instruction::new("bit 5, l", Some(Data8)),
-        0x6E => Instruction::new("bit 5, [hl]", Some(Data8)),
-        0x6F => Instruction::new("bit 5, a", Some(Data8)),
-        0x70 => Instruction::new("bit 6, b", Some(Data8)),
-        0x71 => Instruction::new("bit 6, c", Some(Data8)),
-        0x72 => Instruction::new("bit 6, d", Some(Data8)),
-        0x73 => Instruction::new("bit 6, e", Some(Data8)),
-        0x74 => Instruction::new("bit 6, h", Some(Data8)),
-        0x75 => Instruction::new("bit 6, l", Some(Data8)),
-        0x76 => Instruction::new("bit 6, [hl]", Some(Data8)),
-        0x77 => Instruction::new("bit 6, a", Some(Data8)),
-        0x78 => Instruction::new("bit 7, b", Some(Data8)),
-        0x79 => Instruction::new("bit 7, c", Some(Data8)),
-        0x7A => Instruction::new("bit 7, d", Some(Data8)),
-        0x7B => Instruction::new("bit 7, e", Some(Data8)),
-        0x7C => Instruction::new("bit 7, h", Some(Data8)),
-        0x7D => Instruction::new("bit 7, l", Some(Data8)),
-        0x7E => Instruction::new("bit 7, [hl]", Some(Data8)),
-        0x7F => Instruction::new("bit 7, a'", Some(Data8)),
-
-        0x80 => Instruction::new("res 0, b", Some(Data8)),
-        0x81 => Instruction::new("res 0, c", Some(Data8)),
-        0x82 => Instruction::new("res 0, d", Some(Data8)),
-        0x83 => Instruction::new("res 0, e", Some(Data8)),
-        0x84 => Instruction::new("res 0, h", Some(Data8)),
-        0x85 => Instruction::new("res 0, l", Some(Data8)),
-        0x86 => Instruction::new("res 0, [hl]", Some(Data8)),
-        0x87 => Instruction::new("res 0, a", Some(Data8)),
-        0x88 => Instruction::new("res 1, b", Some(Data8)),
-        0x89 => Instruction::new("res 1, c", Some(Data8)),
-        0x8A => Instruction::new("res 1, d", Some(Data8)),
-        0x8B => Instruction::new("res 1, e", Some(Data8)),
-        0x8C => Instruction::new("res 1, h", Some(Data8)),
-        0x8D => Instruction::new("res 1, l", Some(Data8)),
-        0x8E => Instruction::new("res 1, [hl]", Some(Data8)),
-        0x8F => Instruction::new("res 1, a", Some(Data8)),
-        0x90 => Instruction::new("res 2, b", Some(Data8)),
-        0x91 => Instruction::new("res 2, c", Some(Data8)),
-        0x92 => Instruction::new("res 2, d", Some(Data8)),
-        0x93 => Instruction::new("res 2, e", Some(Data8)),
-        0x94 => Instruction::new("res 2, h", Some(Data8)),
-        0x95 => Instruction::new("res 2, l", Some(Data8)),
-        0x96 => Instruction::new("res 2, [hl]", Some(Data8)),
-        0x97 => Instruction::new("res 2, a", Some(Data8)),
-        0x98 => Instruction::new("res 3, b", Some(Data8)),
-        0x99 => Instruction::new("res 3, c", Some(Data8)),
-        0x9A => Instruction::new("res 3, d", Some(Data8)),
-        0x9B => Instruction::new("res 3, e", Some(Data8)),
-        0x9C => Instruction::new("res 3, h", Some(Data8)),
-        0x9D => Instruction::new("res 3, l", Some(Data8)),
-        0x9E => Instruction::new("res 3, [hl]", Some(Data8)),
-        0x9F => Instruction::new("res 3, a", Some(Data8)),
-        0xA0 => Instruction::new("res 4, b", Some(Data8)),
-        0xA1 => Instruction::new("res 4, c", Some(Data8)),
-        0xA2 => Instruction::new("res 4, d", Some(Data8)),
-        0xA3 => Instruction::new("res 4, e", Some(Data8)),
-        0xA4 => Instruction::new("res 4, h", Some(Data8)),
-        0xA5 => Instruction::new("res 4, l", Some(Data8)),
-        0xA6 => Instruction::new("res 4, [hl]", Some(Data8)),
-        0xA7 => Instruction::new("res 4, a", Some(Data8)),
-        0xA8 => Instruction::new("res 5, b", Some(Data8)),
-        0xA9 => Instruction::new("res 5, c", Some(Data8)),
-        0xAA => Instruction::new("res 5, d", Some(Data8)),
-        0xAB => Instruction::new("res 5, e", Some(Data8)),
-        0xAC => Instruction::new("res 5, h", Some(Data8)),
-        0xAD => Instruction::new("res 5, l", Some(Data8)),
-        0xAE => Instruction::new("res 5, [hl]", Some(Data8)),
-        0xAF => Instruction::new("res 5, a", Some(Data8)),
-        0xB0 => Instruction::new("res 6, b", Some(Data8)),
-        0xB1 => Instruction::new("res 6, c", Some(Data8)),
-        0xB2 => Instruction::new("res 6, d", Some(Data8)),
-        0xB3 => Instruction::new("res 6, e", Some(Data8)),
-        0xB4 => Instruction::new("res 6, h", Some(Data8)),
-        0xB5 => Instruction::new("res 6, l", Some(Data8)),
-        0xB6 => Instruction::new("res 6, [hl]", Some(Data8)),
-        0xB7 => Instruction::new("res 6, a", Some(Data8)),
-        0xB8 => Instruction::new("res 7, b", Some(Data8)),
-        0xB9 => Instruction::new("res 7, c", Some(Data8)),
-        0xBA => Instruction::new("res 7, d", Some(Data8)),
-        0xBB => Instruction::new("res 7, e", Some(Data8)),
-        0xBC => Instruction::new("res 7, h", Some(Data8)),
-        0xBD => Instruction::new("res 7, l", Some(Data8)),
-        0xBE => Instruction::new("res 7, [hl]", Some(Data8)),
-        0xBF => Instruction::new("res 7, a", Some(Data8)),
-
-        0xC0 => Instruction::new("set 0, b", Some(Data8)),
-        0xC1 => Instruction::new("set 0, c", Some(Data8)),
-        0xC2 => Instruction::new("set 0, d", Some(Data8)),
-        0xC3 => Instruction::new("set 0, e", Some(Data8)),
-        0xC4 => Instruction::new("set 0, h", Some(Data8)),
-        0xC5 => Instruction::new("set 0, l", Some(Data8)),
-        0xC6 => Instruction::new("set 0, [hl]", Some(Data8)),
-        0xC7 => Instruction::new("set 0, a", Some(Data8)),
-        0xC8 => Instruction::new("set 1, b", Some(Data8)),
-        0xC9 => Instruction::new("set 1, c", Some(Data8)),
-        0xCA => Instruction::new("set 1, d", Some(Data8)),
-        0xCB => Instruction::new("set 1, e", Some(Data8)),
-        0xCC => Instruction::new("set 1, h", Some(Data8)),
-        0xCD => Instruction::new("set 1, l", Some(Data8)),
-        0xCE => Instruction::new("set 1, [hl]", Some(Data8)),
-        0xCF => Instruction::new("set 1, a", Some(Data8)),
-        0xD0 => Instruction::new("set 2, b", Some(Data8)),
-        0xD1 => Instruction::new("set 2, c", Some(Data8)),
-        0xD2 => Instruction::new("set 2, d", Some(Data8)),
-        0xD3 => Instruction::new("set 2, e", Some(Data8)),
-        0xD4 => Instruction::new("set 2, h", Some(Data8)),
-        0xD5 => Instruction::new("set 2, l", Some(Data8)),
-        0xD6 => Instruction::new("set 2, [hl]", Some(Data8)),
-        0xD7 => Instruction::new("set 2, a", Some(Data8)),
-        0xD8 => Instruction::new("set 3, b", Some(Data8)),
-        0xD9 => Instruction::new("set 3, c", Some(Data8)),
-        0xDA => Instruction::new("set 3, d", Some(Data8)),
-        0xDB => Instruction::new("set 3, e", Some(Data8)),
-        0xDC => Instruction::new("set 3, h", Some(Data8)),
-        0xDD => Instruction::new("set 3, l", Some(Data8)),
-        0xDE => Instruction::new("set 3, [hl]", Some(Data8)),
-        0xDF => Instruction::new("set 3, a", Some(Data8)),
-        0xE0 => Instruction::new("set 4, b", Some(Data8)),
-        0xE1 => Instruction::new("set 4, c", Some(Data8)),
-        0xE2 => Instruction::new("set 4, d", Some(Data8)),
-        0xE3 => Instruction::new("set 4, e", Some(Data8)),
-        0xE4 => Instruction::new("set 4, h", Some(Data8)),
-        0xE5 => Instruction::new("set 4, l", Some(Data8)),
-        0xE6 => Instruction::new("set 4, [hl]", Some(Data8)),
-        0xE7 => Instruction::new("set 4, a", Some(Data8)),
-        0xE8 => Instruction::new("set 5, b", Some(Data8)),
-        0xE9 => Instruction::new("set 5, c", Some(Data8)),
-        0xEA => Instruction::new("set 5, d", Some(Data8)),
-        0xEB => Instruction::new("set 5, e", Some(Data8)),
-        0xEC => Instruction::new("set 5, h", Some(Data8)),
-        0xED => Instruction::new("set 5, l", Some(Data8)),
-        0xEE => Instruction::new("set 5, [hl]", Some(Data8)),
-        0xEF => Instruction::new("set 5, a", Some(Data8)),
-        0xF0 => Instruction::new("set 6, b", Some(Data8)),
-        0xF1 => Instruction::new("set 6, c", Some(Data8)),
-        0xF2 => Instruction::new("set 6, d", Some(Data8)),
-        0xF3 => Instruction::new("set 6, e", Some(Data8)),
-        0xF4 => Instruction::new("set 6, h", Some(Data8)),
-        0xF5 => Instruction::new("set 6, l", Some(Data8)),
-        0xF6 => Instruction::new("set 6, [hl]", Some(Data8)),
-        0xF7 => Instruction::new("set 6, a", Some(Data8)),
-        0xF8 => Instruction::new("set 7, b", Some(Data8)),
-        0xF9 => Instruction::new("set 7, c", Some(Data8)),
-        0xFA => Instruction::new("set 7, d", Some(Data8)),
-        0xFB => Instruction::new("set 7, e", Some(Data8)),
-        0xFC => Instruction::new("set 7, h", Some(Data8)),
-        0xFD => Instruction::new("set 7, l", Some(Data8)),
-        0xFE => Instruction::new("set 7, [hl]", Some(Data8)),
-        0xFF => Instruction::new("set 7, a", Some(Data8)),
-    }
+    lookup(opcode, next_byte)
 }
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::lameboy::mmu::location::Location;
+use crate::lameboy::mmu::Mmu;
+
+/// Labels loaded from a symbol file, keyed by bank and address.
+///
+/// Reads the `bank:address label` format written by RGBDS's `rgblink -n` and no$gmb, where both
+/// numbers are hex. Everything after a `;` is a comment, and no$gmb's `[section]` headers are
+/// skipped.
+///
+/// ```text
+/// ; File generated by rgblink
+/// 00:0150 Start
+/// 03:4000 LoadLevel
+/// 00:c0a0 wPlayerX
+/// ```
+pub struct SymbolTable {
+    labels: BTreeMap<(u16, u16), String>,
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable {
+            labels: BTreeMap::new(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<SymbolTable, String> {
+        let mut symbols = SymbolTable::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+
+            let parsed = line
+                .split_once(char::is_whitespace)
+                .and_then(|(location, name)| {
+                    let (bank, addr) = location.split_once(':')?;
+                    Some((
+                        u16::from_str_radix(bank, 16).ok()?,
+                        u16::from_str_radix(addr, 16).ok()?,
+                        name.trim(),
+                    ))
+                });
+            match parsed {
+                Some((bank, addr, name)) if !name.is_empty() => symbols.insert(bank, addr, name),
+                _ => {
+                    return Err(format!(
+                        "Line {}: expected 'bank:address label'",
+                        line_number + 1
+                    ))
+                }
+            }
+        }
+
+        Ok(symbols)
+    }
+
+    pub fn load(path: &Path) -> Result<SymbolTable, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read symbol file {}: {e}", path.display()))?;
+        let symbols = SymbolTable::parse(&text)
+            .map_err(|e| format!("Symbol file {}: {e}", path.display()))?;
+        info!("Loaded {} symbols from {}", symbols.len(), path.display());
+
+        Ok(symbols)
+    }
+
+    /// The `.sym` file next to a ROM, if there is one.
+    pub fn find_for_rom(rom_path: &Path) -> Option<PathBuf> {
+        Some(rom_path.with_extension("sym")).filter(|path| path.is_file())
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Add a label, the first one loaded for an address being the one shown.
+    pub fn insert(&mut self, bank: u16, addr: u16, name: &str) {
+        self.labels
+            .entry((bank, addr))
+            .or_insert_with(|| name.to_string());
+    }
+
+    /// Every label as `(bank, address, name)`, in address order within each bank.
+    pub fn iter(&self) -> impl Iterator<Item = (u16, u16, &str)> {
+        self.labels
+            .iter()
+            .map(|(&(bank, addr), name)| (bank, addr, name.as_str()))
+    }
+
+    pub fn label_in_bank(&self, bank: u16, addr: u16) -> Option<&str> {
+        self.labels.get(&(bank, addr)).map(String::as_str)
+    }
+
+    /// The label at an address in whichever bank is mapped there right now.
+    pub fn label(&self, addr: u16, mmu: &Mmu) -> Option<&str> {
+        self.label_in_bank(mmu.bank_of(addr), addr)
+    }
+
+    /// Find a label by name, ignoring case, returning its bank and address.
+    pub fn find(&self, name: &str) -> Option<(u16, u16)> {
+        self.labels
+            .iter()
+            .find(|(_, label)| label.eq_ignore_ascii_case(name))
+            .map(|(&location, _)| location)
+    }
+
+    /// Resolve a location typed by the user, either a label, which keeps its bank, or a hex
+    /// `bank:address` or address with an optional `0x` or `$` prefix.
+    pub fn resolve(&self, location: &str) -> Result<Location, String> {
+        let location = location.trim();
+        if let Some((bank, addr)) = self.find(location) {
+            return Ok(Location::new(bank, addr));
+        }
+
+        Location::parse(location).map_err(|_| format!("Unknown label or address '{location}'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    const SYMBOLS: &str = "; File generated by rgblink\n\
+                           00:0150 Start\n\
+                           01:4000 BankOneData ; comment\n\
+                           03:4000 LoadLevel\n\
+                           [labels]\n\
+                           00:C0A0 wPlayerX\n\
+                           00:0150 Start.duplicate\n";
+
+    #[test]
+    fn parses_symbol_files() {
+        let symbols = SymbolTable::parse(SYMBOLS).unwrap();
+        assert_eq!(4, symbols.len());
+        assert_eq!(Some("Start"), symbols.label_in_bank(0, 0x0150));
+        assert_eq!(Some("LoadLevel"), symbols.label_in_bank(3, 0x4000));
+        assert_eq!(Some((0, 0xC0A0)), symbols.find("wplayerx"));
+
+        assert!(SymbolTable::parse("0150 Start").is_err());
+        assert!(SymbolTable::parse("00:0150").is_err());
+    }
+
+    #[test]
+    fn resolves_with_current_bank() {
+        let symbols = SymbolTable::parse(SYMBOLS).unwrap();
+        let mut lameboy = Lameboy::with_program(&[]);
+
+        let mmu = lameboy.get_mmu();
+        assert_eq!(Some("Start"), symbols.label(0x0150, mmu));
+        assert_eq!(Some("BankOneData"), symbols.label(0x4000, mmu));
+        assert_eq!(Some("wPlayerX"), symbols.label(0xC0A0, mmu));
+
+        assert_eq!(Ok(Location::new(3, 0x4000)), symbols.resolve("LoadLevel"));
+        assert_eq!(Ok(Location::from(0x1234)), symbols.resolve("$1234"));
+        assert_eq!(Ok(Location::from(0xC000)), symbols.resolve("0xc000"));
+        assert_eq!(Ok(Location::new(2, 0x4000)), symbols.resolve("02:4000"));
+        assert!(symbols.resolve("Missing").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+
+use crate::dis::{Disassembly, SymbolTable};
+use crate::lameboy::cdl::CodeDataLog;
+
+#[derive(clap::Args, Debug)]
+pub struct DisasmArgs {
+    /// ROM file to disassemble
+    pub file: String,
+    /// Directory to write the `.asm` files to
+    #[arg(long, default_value = "disasm")]
+    pub out: PathBuf,
+    /// Code/data log from running the ROM, whose executed opcodes are traced from as well
+    #[arg(long)]
+    pub cdl: Option<PathBuf>,
+    /// Symbol file to name labels from, defaults to the `.sym` file next to the ROM
+    #[arg(long)]
+    pub sym: Option<PathBuf>,
+}
+
+/// Handle the `disasm` command, writing an RGBDS project for the ROM.
+pub fn run_command(args: &DisasmArgs, rom_data: &[u8]) -> Result<(), String> {
+    let rom_path = Path::new(&args.file);
+
+    let seeds = match &args.cdl {
+        Some(path) => CodeDataLog::load(path, rom_data.len())?.opcode_offsets(),
+        None => Vec::new(),
+    };
+    let symbols = match args
+        .sym
+        .clone()
+        .or_else(|| SymbolTable::find_for_rom(rom_path))
+    {
+        Some(path) => SymbolTable::load(&path)?,
+        None => SymbolTable::new(),
+    };
+
+    let disassembly = Disassembly::new(rom_data, &seeds, &symbols);
+    let rom_name = rom_path
+        .file_name()
+        .map_or(args.file.clone(), |name| name.to_string_lossy().to_string());
+    disassembly.export(&args.out, &rom_name)?;
+
+    println!(
+        "Disassembled {} banks to {}, {} of {} bytes found to be code",
+        disassembly.bank_count(),
+        args.out.display(),
+        disassembly.code_size(),
+        rom_data.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,653 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::lameboy::breakpoint::Breakpoint;
+use crate::lameboy::mmu::watchpoint::{WatchKind, Watchpoint};
+use crate::lameboy::stepping::StopReason;
+use crate::lameboy::Lameboy;
+
+/// Largest packet we accept, advertised to the debugger in hex.
+const PACKET_SIZE: usize = 0x1000;
+
+/// Registers in the order GDB's z80 architecture expects them, each 16 bits little-endian.
+/// The Game Boy has none of the z80's index or shadow registers so the list stops at PC.
+pub const REGISTER_NAMES: [&str; 6] = ["af", "bc", "de", "hl", "sp", "pc"];
+
+#[derive(clap::Args, Debug)]
+pub struct GdbArgs {
+    /// ROM file to load
+    pub file: String,
+    /// Local TCP port to listen on
+    #[arg(long, default_value_t = 2345)]
+    pub port: u16,
+}
+
+/// What the debugger sent us, once framing and acknowledgements are dealt with.
+#[derive(Debug, PartialEq)]
+enum Received {
+    Packet(String),
+    /// Ctrl-C, sent outside of a packet to stop the target.
+    Interrupt,
+}
+
+/// How to answer a packet.
+#[derive(Debug, PartialEq)]
+enum Response {
+    Reply(String),
+    /// The emulator is running again, reply once it stops.
+    Resumed,
+    /// Reply OK and drop the connection.
+    Detach,
+    /// Drop the connection without replying.
+    Kill,
+}
+
+/// Frame a packet as `$data#checksum`.
+fn encode_packet(data: &str) -> Vec<u8> {
+    let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+    format!("${data}#{checksum:02x}").into_bytes()
+}
+
+/// Pull the next complete message out of the bytes received so far, acknowledging packets as they
+/// are read. Returns None until a whole packet has arrived.
+fn next_received(buffer: &mut Vec<u8>, acks: &mut Vec<u8>) -> Option<Received> {
+    loop {
+        match buffer.first()? {
+            b'$' => {
+                let end = buffer.iter().position(|&byte| byte == b'#')?;
+                if buffer.len() < end + 3 {
+                    return None;
+                }
+
+                let data: Vec<u8> = buffer[1..end].to_vec();
+                let checksum = std::str::from_utf8(&buffer[end + 1..end + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                buffer.drain(..end + 3);
+
+                let sum = data.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+                if checksum != Some(sum) {
+                    warn!("GDB packet checksum mismatch, asking for a resend");
+                    acks.push(b'-');
+                    continue;
+                }
+
+                acks.push(b'+');
+                return Some(Received::Packet(
+                    String::from_utf8_lossy(&data).into_owned(),
+                ));
+            }
+            0x03 => {
+                buffer.remove(0);
+                return Some(Received::Interrupt);
+            }
+            // Acknowledgements of our own packets, and anything else between packets
+            _ => {
+                buffer.remove(0);
+            }
+        }
+    }
+}
+
+fn parse_hex(hex: &str) -> Result<u16, String> {
+    u16::from_str_radix(hex, 16).map_err(|_| format!("Invalid hex number '{hex}'"))
+}
+
+fn parse_hex_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("Odd length hex data '{hex}'"));
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("Invalid hex data '{}'", String::from_utf8_lossy(pair)))
+        })
+        .collect()
+}
+
+/// Split `addr,length` into its two numbers.
+fn parse_range(range: &str) -> Result<(u16, usize), String> {
+    let (addr, length) = range
+        .split_once(',')
+        .ok_or_else(|| format!("Invalid range '{range}'"))?;
+
+    Ok((parse_hex(addr)?, usize::from(parse_hex(length)?)))
+}
+
+fn read_register(lameboy: &mut Lameboy, index: usize) -> Option<u16> {
+    let registers = &lameboy.get_cpu().registers;
+    let value = match index {
+        0 => u16::from(registers.a) << 8 | u16::from(registers.f.bits()),
+        1 => u16::from(registers.b) << 8 | u16::from(registers.c),
+        2 => u16::from(registers.d) << 8 | u16::from(registers.e),
+        3 => u16::from(registers.h) << 8 | u16::from(registers.l),
+        4 => registers.sp,
+        5 => registers.pc,
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+fn write_register(lameboy: &mut Lameboy, index: usize, value: u16) -> bool {
+    use crate::lameboy::cpu::registers::Reg16;
+
+    let registers = &mut lameboy.get_cpu().registers;
+    match index {
+        0 => registers.write16(&Reg16::AF, value),
+        1 => registers.write16(&Reg16::BC, value),
+        2 => registers.write16(&Reg16::DE, value),
+        3 => registers.write16(&Reg16::HL, value),
+        4 => registers.sp = value,
+        5 => registers.pc = value,
+        _ => return false,
+    }
+    lameboy.debug.program_counter = lameboy.get_cpu().registers.pc;
+
+    true
+}
+
+/// The watchpoint kind for a `Z2`/`Z3`/`Z4` packet type.
+fn watch_kind(kind: &str) -> Option<WatchKind> {
+    match kind {
+        "2" => Some(WatchKind::Write),
+        "3" => Some(WatchKind::Read),
+        "4" => Some(WatchKind::ReadWrite),
+        _ => None,
+    }
+}
+
+/// The stop reply describing why the emulator stopped, naming the address for watchpoints.
+fn stop_reply(lameboy: &Lameboy) -> String {
+    match lameboy.get_stop_reason() {
+        Some(StopReason::Watchpoint(hit)) => {
+            let kind = if hit.write { "watch" } else { "rwatch" };
+            format!("T05{kind}:{:04x};", hit.addr)
+        }
+        _ => String::from("S05"),
+    }
+}
+
+/// Handle a single packet from the debugger.
+fn handle_packet(lameboy: &mut Lameboy, packet: &str) -> Response {
+    match execute(lameboy, packet) {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("GDB packet '{}': {}", packet, e);
+            Response::Reply(String::from("E01"))
+        }
+    }
+}
+
+fn execute(lameboy: &mut Lameboy, packet: &str) -> Result<Response, String> {
+    // The protocol is ASCII, and anything else would split a character below
+    if !packet.is_ascii() {
+        return Err(String::from("Non-ASCII packet"));
+    }
+    let (command, args) = packet.split_at(packet.len().min(1));
+    let reply = match command {
+        "?" => stop_reply(lameboy),
+        "g" => (0..REGISTER_NAMES.len())
+            .filter_map(|index| read_register(lameboy, index))
+            .map(|value| format!("{:04x}", value.swap_bytes()))
+            .collect(),
+        "G" => {
+            let bytes = parse_hex_bytes(args)?;
+            for (index, pair) in bytes.chunks_exact(2).enumerate() {
+                write_register(lameboy, index, u16::from_le_bytes([pair[0], pair[1]]));
+            }
+            String::from("OK")
+        }
+        "p" => {
+            let index = usize::from(parse_hex(args)?);
+            match read_register(lameboy, index) {
+                Some(value) => format!("{:04x}", value.swap_bytes()),
+                None => String::from("E01"),
+            }
+        }
+        "P" => {
+            let (index, value) = args
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid register write '{args}'"))?;
+            let bytes = parse_hex_bytes(value)?;
+            let value = u16::from_le_bytes([
+                bytes.first().copied().unwrap_or(0),
+                bytes.get(1).copied().unwrap_or(0),
+            ]);
+            if write_register(lameboy, usize::from(parse_hex(index)?), value) {
+                String::from("OK")
+            } else {
+                String::from("E01")
+            }
+        }
+        "m" => {
+            let (addr, length) = parse_range(args)?;
+            let mmu = lameboy.get_mmu();
+            (0..length.min(PACKET_SIZE / 2))
+                .map(|offset| format!("{:02x}", mmu.read8_safe(addr.wrapping_add(offset as u16))))
+                .collect()
+        }
+        "M" => {
+            let (range, data) = args
+                .split_once(':')
+                .ok_or_else(|| format!("Invalid memory write '{args}'"))?;
+            let (addr, length) = parse_range(range)?;
+            let bytes = parse_hex_bytes(data)?;
+            if bytes.len() != length {
+                return Err(format!("Expected {length} bytes, got {}", bytes.len()));
+            }
+            for (offset, &byte) in bytes.iter().enumerate() {
+                lameboy
+                    .get_mmu()
+                    .write8(addr.wrapping_add(offset as u16), byte);
+            }
+            String::from("OK")
+        }
+        "c" | "s" => {
+            if !args.is_empty() {
+                let addr = parse_hex(args)?;
+                write_register(lameboy, 5, addr);
+            }
+            if command == "s" {
+                lameboy.stop();
+                lameboy.step();
+                String::from("S05")
+            } else {
+                lameboy.resume();
+                return Ok(Response::Resumed);
+            }
+        }
+        "Z" | "z" => {
+            let mut fields = args.split(',');
+            let kind = fields.next().unwrap_or("");
+            let addr = parse_hex(fields.next().unwrap_or(""))?;
+            let length = parse_hex(fields.next().unwrap_or("1"))?.max(1);
+            let insert = command == "Z";
+
+            match (kind, watch_kind(kind)) {
+                // Software and hardware breakpoints are the same thing to the emulator
+                ("0" | "1", _) => {
+                    let breakpoints = &mut lameboy.debug.breakpoints;
+                    if insert {
+                        breakpoints.push(Breakpoint::new(addr));
+                    } else if let Some(index) = breakpoints
+                        .iter()
+                        .rposition(|b| b.addr == addr && b.condition.is_none())
+                    {
+                        breakpoints.remove(index);
+                    }
+                    String::from("OK")
+                }
+                (_, Some(kind)) => {
+                    let watchpoint = Watchpoint::new(addr, addr.wrapping_add(length - 1), kind);
+                    let watchpoints = &mut lameboy.get_mmu().watchpoints;
+                    if insert {
+                        watchpoints.add(watchpoint);
+                    } else if let Some(index) = watchpoints
+                        .list()
+                        .iter()
+                        .rposition(|w| w.start == watchpoint.start && w.end == watchpoint.end)
+                    {
+                        watchpoints.remove(index);
+                    }
+                    String::from("OK")
+                }
+                _ => String::new(),
+            }
+        }
+        "D" => return Ok(Response::Detach),
+        "k" => return Ok(Response::Kill),
+        "H" => String::from("OK"),
+        _ if packet.starts_with("qSupported") => format!("PacketSize={PACKET_SIZE:x}"),
+        _ if packet == "qAttached" => String::from("1"),
+        // Anything else is unsupported, which an empty reply tells the debugger
+        _ => String::new(),
+    };
+
+    Ok(Response::Reply(reply))
+}
+
+/// What became of a client after handling what it sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientStatus {
+    Connected,
+    /// The connection was closed.
+    Disconnected,
+    /// The debugger killed or detached from the target.
+    Finished,
+}
+
+/// A connected debugger.
+struct Client {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    /// The emulator was resumed by the debugger, which is waiting to hear that it stopped.
+    waiting_for_stop: bool,
+}
+
+impl Client {
+    fn send(&mut self, data: &str) -> std::io::Result<()> {
+        debug!("GDB <- {}", data);
+        self.stream.write_all(&encode_packet(data))
+    }
+
+    /// Read and handle everything the debugger has sent.
+    fn poll(&mut self, lameboy: &mut Lameboy) -> std::io::Result<ClientStatus> {
+        let mut chunk = [0; 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Ok(ClientStatus::Disconnected),
+                Ok(length) => self.buffer.extend_from_slice(&chunk[..length]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut acks = Vec::new();
+        while let Some(received) = next_received(&mut self.buffer, &mut acks) {
+            self.stream.write_all(&acks)?;
+            acks.clear();
+
+            match received {
+                Received::Interrupt => {
+                    if self.waiting_for_stop {
+                        lameboy.stop();
+                        self.waiting_for_stop = false;
+                        self.send("S02")?;
+                    }
+                }
+                Received::Packet(packet) => {
+                    debug!("GDB -> {}", packet);
+                    match handle_packet(lameboy, &packet) {
+                        Response::Reply(reply) => self.send(&reply)?,
+                        Response::Resumed => self.waiting_for_stop = true,
+                        Response::Detach => {
+                            self.send("OK")?;
+                            return Ok(ClientStatus::Finished);
+                        }
+                        Response::Kill => return Ok(ClientStatus::Finished),
+                    }
+                }
+            }
+        }
+        self.stream.write_all(&acks)?;
+
+        if self.waiting_for_stop && !lameboy.is_running() {
+            self.waiting_for_stop = false;
+            self.send(&stop_reply(lameboy))?;
+        }
+
+        Ok(ClientStatus::Connected)
+    }
+}
+
+/// A GDB remote serial protocol server on a local TCP port, polled by the emulator's main loop so
+/// an external debugger can control it alongside the debug windows.
+///
+/// Registers are exposed in GDB's z80 layout, so `set architecture z80` before connecting.
+/// Breakpoints and watchpoints are added to the emulator's own lists, so they show up in the
+/// debug windows too.
+pub struct GdbServer {
+    listener: TcpListener,
+    client: Option<Client>,
+    finished: bool,
+}
+
+impl GdbServer {
+    pub fn bind(port: u16) -> Result<GdbServer, String> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|e| format!("Unable to listen for GDB on port {port}: {e}"))?;
+        info!("Waiting for GDB on {}", listener.local_addr().unwrap());
+
+        Ok(GdbServer {
+            listener,
+            client: None,
+            finished: false,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.listener.local_addr().map_or(0, |addr| addr.port())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /// A debugger has killed or detached from the emulator.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Accept a debugger if none is connected, then handle anything it has sent. The emulator is
+    /// stopped when a debugger attaches, as it expects to find the target halted.
+    pub fn poll(&mut self, lameboy: &mut Lameboy) {
+        if self.client.is_none() {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    if let Err(e) = stream.set_nonblocking(true) {
+                        error!("Unable to set up GDB connection: {}", e);
+                        return;
+                    }
+                    let _ = stream.set_nodelay(true);
+                    info!("GDB connected from {}", addr);
+                    lameboy.stop();
+                    self.client = Some(Client {
+                        stream,
+                        buffer: Vec::new(),
+                        waiting_for_stop: false,
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                Err(e) => {
+                    error!("Unable to accept GDB connection: {}", e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(client) = &mut self.client {
+            match client.poll(lameboy) {
+                Ok(ClientStatus::Connected) => {}
+                Ok(ClientStatus::Disconnected) => {
+                    info!("GDB disconnected");
+                    self.client = None;
+                }
+                Ok(ClientStatus::Finished) => {
+                    info!("GDB killed or detached from the emulator");
+                    self.client = None;
+                    self.finished = true;
+                }
+                Err(e) => {
+                    error!("GDB connection lost: {}", e);
+                    self.client = None;
+                }
+            }
+        }
+    }
+}
+
+/// Handle the `gdb` command, running a ROM without a window under the control of a debugger.
+pub fn run_command(args: &GdbArgs, rom_data: Vec<u8>) -> Result<(), String> {
+    let mut server = GdbServer::bind(args.port)?;
+    println!("Listening for GDB on 127.0.0.1:{}", server.port());
+
+    let mut lameboy = Lameboy::new(rom_data);
+    lameboy.reset();
+
+    loop {
+        server.poll(&mut lameboy);
+        if server.is_finished() {
+            return Ok(());
+        }
+
+        if lameboy.is_running() {
+            lameboy.run_frame();
+        } else {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `ld a, 0x05; ld [0xC000], a; ld a, [0xC001]; jr -2`
+    const PROGRAM: &[u8] = &[0x3E, 0x05, 0xEA, 0x00, 0xC0, 0xFA, 0x01, 0xC0, 0x18, 0xFE];
+
+    fn reply(lameboy: &mut Lameboy, packet: &str) -> String {
+        match handle_packet(lameboy, packet) {
+            Response::Reply(reply) => reply,
+            response => panic!("Unexpected response {response:?}"),
+        }
+    }
+
+    fn run_until_stopped(lameboy: &mut Lameboy) {
+        for _ in 0..10 {
+            if !lameboy.is_running() {
+                return;
+            }
+            lameboy.run_frame();
+        }
+        panic!("Emulator didn't stop");
+    }
+
+    #[test]
+    fn packet_framing() {
+        assert_eq!(b"$OK#9a".to_vec(), encode_packet("OK"));
+
+        let mut buffer = b"+$g#67$m0,1#00\x03$?#3".to_vec();
+        let mut acks = Vec::new();
+        assert_eq!(
+            Some(Received::Packet(String::from("g"))),
+            next_received(&mut buffer, &mut acks)
+        );
+        assert_eq!(
+            Some(Received::Interrupt),
+            next_received(&mut buffer, &mut acks)
+        );
+        assert_eq!(b"+-".to_vec(), acks);
+
+        // Incomplete packet waits for the rest of its checksum
+        assert_eq!(None, next_received(&mut buffer, &mut acks));
+        buffer.push(b'f');
+        assert_eq!(
+            Some(Received::Packet(String::from("?"))),
+            next_received(&mut buffer, &mut acks)
+        );
+    }
+
+    #[test]
+    fn registers_and_memory() {
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+
+        assert_eq!("b0011300d8004d01feff0001", reply(&mut lameboy, "g"));
+        assert_eq!("0001", reply(&mut lameboy, "p5"));
+        assert_eq!("E01", reply(&mut lameboy, "p6"));
+
+        assert_eq!("OK", reply(&mut lameboy, "P1=3412"));
+        assert_eq!(0x12, lameboy.get_cpu().registers.b);
+        assert_eq!(0x34, lameboy.get_cpu().registers.c);
+        assert_eq!("OK", reply(&mut lameboy, "G0012000000000000f0ff5001"));
+        assert_eq!(0x12, lameboy.get_cpu().registers.a);
+        assert_eq!(0xFFF0, lameboy.get_cpu().registers.sp);
+        assert_eq!(0x0150, lameboy.get_cpu().registers.pc);
+
+        assert_eq!("3e05ea", reply(&mut lameboy, "m100,3"));
+        assert_eq!("OK", reply(&mut lameboy, "MC000,2:abcd"));
+        assert_eq!(0xCD, lameboy.get_mmu().read8_safe(0xC001));
+        assert_eq!("E01", reply(&mut lameboy, "MC000,2:ab"));
+        assert_eq!("", reply(&mut lameboy, "vMustReplyEmpty"));
+    }
+
+    #[test]
+    fn rejects_non_ascii() {
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+
+        assert_eq!("E01", reply(&mut lameboy, "\u{FFFD}"));
+        assert_eq!("E01", reply(&mut lameboy, "Ga\u{e9}0"));
+        assert!(parse_hex_bytes("a\u{e9}0").is_err());
+    }
+
+    #[test]
+    fn breakpoints_and_stepping() {
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+
+        assert_eq!("S05", reply(&mut lameboy, "s"));
+        assert_eq!(0x0102, lameboy.get_cpu().registers.pc);
+
+        assert_eq!("OK", reply(&mut lameboy, "Z0,108,1"));
+        assert_eq!(Response::Resumed, handle_packet(&mut lameboy, "c"));
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0x0108, lameboy.get_cpu().registers.pc);
+        assert_eq!("S05", reply(&mut lameboy, "?"));
+
+        assert_eq!("OK", reply(&mut lameboy, "z0,108,1"));
+        assert!(lameboy.debug.breakpoints.is_empty());
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+
+        assert_eq!("OK", reply(&mut lameboy, "Z2,c000,1"));
+        assert_eq!(Response::Resumed, handle_packet(&mut lameboy, "c"));
+        run_until_stopped(&mut lameboy);
+        assert_eq!("T05watch:c000;", reply(&mut lameboy, "?"));
+        assert_eq!("OK", reply(&mut lameboy, "z2,c000,1"));
+
+        assert_eq!("OK", reply(&mut lameboy, "Z3,c001,1"));
+        assert_eq!(Response::Resumed, handle_packet(&mut lameboy, "c"));
+        run_until_stopped(&mut lameboy);
+        assert_eq!("T05rwatch:c001;", reply(&mut lameboy, "?"));
+        assert_eq!(0x0108, lameboy.get_cpu().registers.pc);
+    }
+
+    #[test]
+    fn serves_over_tcp() {
+        let mut server = GdbServer::bind(0).unwrap();
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+        lameboy.resume();
+
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port())).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        stream.write_all(b"$p5#a5").unwrap();
+
+        let expected = [b"+".to_vec(), encode_packet("0201")].concat();
+        let mut received = Vec::new();
+        let mut chunk = [0; 64];
+        for _ in 0..1000 {
+            server.poll(&mut lameboy);
+            if let Ok(length) = stream.read(&mut chunk) {
+                received.extend_from_slice(&chunk[..length]);
+            }
+            if received.len() >= expected.len() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert!(server.is_connected());
+        assert!(!lameboy.is_running());
+        assert_eq!(expected, received);
+
+        // Killing the target ends the session for good, rather than waiting for another debugger
+        stream.write_all(b"$k#6b").unwrap();
+        for _ in 0..1000 {
+            server.poll(&mut lameboy);
+            if server.is_finished() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(server.is_finished());
+        assert!(!server.is_connected());
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use imgui::{Image as ImguiImage, TextureId, Ui};
+
+use crate::lameboy::ppu::image::Image;
+
+/// Images drawn by the debug windows, uploaded to textures by the GUI after the windows are built
+/// and before imgui renders them.
+pub struct DebugTextures {
+    ids: HashMap<&'static str, TextureId>,
+    pending: Vec<(&'static str, Image)>,
+}
+
+impl DebugTextures {
+    pub fn new() -> DebugTextures {
+        DebugTextures {
+            ids: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Draw an image scaled to a size. The first time a name is drawn there's no texture for it
+    /// yet, so it only takes up the space until the next frame.
+    pub fn image(&mut self, ui: &Ui, name: &'static str, image: Image, size: [f32; 2]) {
+        match self.ids.get(name) {
+            Some(&id) => ImguiImage::new(id, size).build(ui),
+            None => ui.dummy(size),
+        }
+        self.upload(name, image);
+    }
+
+    /// Draw part of an uploaded image, given as the fractions of its width and height at the
+    /// region's top left and bottom right corners.
+    pub fn image_region(
+        &self,
+        ui: &Ui,
+        name: &str,
+        size: [f32; 2],
+        top_left: [f32; 2],
+        bottom_right: [f32; 2],
+    ) {
+        match self.ids.get(name) {
+            Some(&id) => ImguiImage::new(id, size)
+                .uv0(top_left)
+                .uv1(bottom_right)
+                .build(ui),
+            None => ui.dummy(size),
+        }
+    }
+
+    /// Queue an image to be uploaded this frame, to draw with `image_region`.
+    pub fn upload(&mut self, name: &'static str, image: Image) {
+        self.pending.push((name, image));
+    }
+
+    /// The images drawn since the last call, to upload.
+    pub fn take_pending(&mut self) -> Vec<(&'static str, Image)> {
+        std::mem::take(&mut self.pending)
+    }
+
+    pub fn id(&self, name: &str) -> Option<TextureId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn set_id(&mut self, name: &'static str, id: TextureId) {
+        self.ids.insert(name, id);
+    }
+}
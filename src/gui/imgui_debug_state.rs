@@ -1,3 +1,9 @@
+use crate::dis::{Annotations, SymbolTable};
+use crate::gui::debug_textures::DebugTextures;
+use crate::lameboy::breakpoint::Breakpoint;
+use crate::lameboy::history::DEFAULT_HISTORY_SIZE;
+use crate::lameboy::watch::Watch;
+
 pub struct ImguiDebugState {
     pub program_counter: u16,
     pub show_imgui_metrics: bool,
@@ -8,19 +14,78 @@ pub struct ImguiDebugState {
     pub show_cpu: bool,
     pub show_ppu: bool,
     pub show_joypad: bool,
+    pub show_watches: bool,
+    pub show_history: bool,
+    pub show_profiler: bool,
+    pub show_timeline: bool,
+    pub show_tiles: bool,
+    pub show_tile_maps: bool,
+    pub show_oam: bool,
+    pub show_palettes: bool,
+    pub show_io_registers: bool,
     pub ppu_mod: i32,
-    pub ppu_sprite_index: i32,
     pub show_about: bool,
-    pub input_breakpoint_addr: i32,
+    pub input_breakpoint_location: String,
+    pub input_breakpoint_error: String,
     pub input_memory_addr: i32,
     pub input_memory_value: i32,
     pub dump_memory_addr: i32,
     pub dump_memory_pc_lock: bool,
+    pub dump_bank_pinned: bool,
+    pub dump_bank: i32,
     pub disassemble_memory_addr: i32,
     pub disassemble_memory_pc_lock: bool,
     pub disassemble_read_args: bool,
-    pub breakpoints: Vec<u16>,
-    pub memory_breakpoints: Vec<u16>,
+    pub disassemble_bank_pinned: bool,
+    pub disassemble_bank: i32,
+    pub disassemble_goto: String,
+    pub disassemble_coverage: bool,
+    pub cdl_path: String,
+    pub run_to_cursor: Option<u16>,
+    pub breakpoints: Vec<Breakpoint>,
+    pub symbols: SymbolTable,
+    pub annotations: Annotations,
+    pub annotate_location: Option<(u16, u16)>,
+    pub annotation_label: String,
+    pub annotation_comment: String,
+    pub annotation_error: String,
+    pub annotation_export_path: String,
+    pub selected_breakpoint: Option<usize>,
+    pub input_breakpoint_condition: String,
+    pub breakpoint_condition_error: String,
+    pub input_watchpoint_start: i32,
+    pub input_watchpoint_end: i32,
+    pub input_watchpoint_kind: usize,
+    pub input_watchpoint_log_only: bool,
+    pub watches: Vec<Watch>,
+    pub input_watch: String,
+    pub input_watch_format: usize,
+    pub input_watch_error: String,
+    pub history_size: i32,
+    pub rewind_to_cycle: Option<u64>,
+    pub history_search: String,
+    pub history_export_path: String,
+    pub profiler_by_pc: bool,
+    pub profiler_sort: (usize, bool),
+    pub profiler_export_path: String,
+    pub timeline_last_frame: bool,
+    pub timeline_zoom: f32,
+    pub timeline_shown: [bool; 7],
+    pub tiles_palette: usize,
+    pub tiles_zoom: f32,
+    pub tiles_export_path: String,
+    pub tile_map_index: usize,
+    pub tile_map_overlay: bool,
+    pub tile_map_zoom: f32,
+    pub tile_map_export_path: String,
+    pub oam_overlay: bool,
+    pub oam_selected: Option<u8>,
+    pub textures: DebugTextures,
+    pub trace_path: String,
+    pub trace_start: u64,
+    pub trace_end: u64,
+    pub trace_reference_path: String,
+    pub trace_diff: String,
 }
 
 impl ImguiDebugState {
@@ -35,19 +100,78 @@ impl ImguiDebugState {
             show_cpu: false,
             show_ppu: false,
             show_joypad: false,
+            show_watches: false,
+            show_history: false,
+            show_profiler: false,
+            show_timeline: false,
+            show_tiles: false,
+            show_tile_maps: false,
+            show_oam: false,
+            show_palettes: false,
+            show_io_registers: false,
             ppu_mod: 4,
-            ppu_sprite_index: 0,
             show_about: false,
-            input_breakpoint_addr: 0,
+            input_breakpoint_location: String::new(),
+            input_breakpoint_error: String::new(),
             input_memory_addr: 0,
             input_memory_value: 0,
             dump_memory_addr: 0,
             dump_memory_pc_lock: true,
+            dump_bank_pinned: false,
+            dump_bank: 1,
             disassemble_memory_addr: 0,
             disassemble_memory_pc_lock: true,
             disassemble_read_args: true,
+            disassemble_bank_pinned: false,
+            disassemble_bank: 1,
+            disassemble_goto: String::new(),
+            disassemble_coverage: true,
+            cdl_path: String::from("coverage.cdl"),
+            run_to_cursor: None,
             breakpoints: Vec::new(),
-            memory_breakpoints: Vec::new(),
+            symbols: SymbolTable::new(),
+            annotations: Annotations::new(),
+            annotate_location: None,
+            annotation_label: String::new(),
+            annotation_comment: String::new(),
+            annotation_error: String::new(),
+            annotation_export_path: String::from("labels.sym"),
+            selected_breakpoint: None,
+            input_breakpoint_condition: String::new(),
+            breakpoint_condition_error: String::new(),
+            input_watchpoint_start: 0,
+            input_watchpoint_end: 0,
+            input_watchpoint_kind: 0,
+            input_watchpoint_log_only: false,
+            watches: Vec::new(),
+            input_watch: String::new(),
+            input_watch_format: 0,
+            input_watch_error: String::new(),
+            history_size: DEFAULT_HISTORY_SIZE as i32,
+            rewind_to_cycle: None,
+            history_search: String::new(),
+            history_export_path: String::from("history.log"),
+            profiler_by_pc: false,
+            profiler_sort: (2, true),
+            profiler_export_path: String::from("profile.folded"),
+            timeline_last_frame: true,
+            timeline_zoom: 1.0,
+            timeline_shown: [true; 7],
+            tiles_palette: 0,
+            tiles_zoom: 2.0,
+            tiles_export_path: String::from("tiles.png"),
+            tile_map_index: 0,
+            tile_map_overlay: true,
+            tile_map_zoom: 2.0,
+            tile_map_export_path: String::from("tile_map.png"),
+            oam_overlay: false,
+            oam_selected: None,
+            textures: DebugTextures::new(),
+            trace_path: String::from("trace.log"),
+            trace_start: 0,
+            trace_end: 0,
+            trace_reference_path: String::new(),
+            trace_diff: String::new(),
         }
     }
 }
@@ -1,16 +1,23 @@
+use std::rc::Rc;
 use std::time::Instant;
 
 use glium::glutin;
 use glium::glutin::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
 use glium::glutin::event_loop::{ControlFlow, EventLoop};
 use glium::glutin::window::WindowBuilder;
-use glium::{Display, Surface};
+use glium::texture::RawImage2d;
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter, SamplerBehavior};
+use glium::{Display, Surface, Texture2d};
 use imgui::{Context, FontConfig, FontSource, Style};
-use imgui_glium_renderer::Renderer;
+use imgui_glium_renderer::{Renderer, Texture};
 use imgui_winit_support::{HiDpiMode, WinitPlatform};
 
+use crate::gdb::GdbServer;
+use crate::gui::debug_textures::DebugTextures;
+use crate::lameboy::ppu::gpu::Gpu;
 use crate::lameboy::Lameboy;
 
+pub mod debug_textures;
 pub mod imgui_debug_state;
 pub mod imgui_debuggable;
 
@@ -20,6 +27,7 @@ pub struct Gui {
     imgui: Context,
     platform: WinitPlatform,
     renderer: Renderer,
+    gpu: Gpu,
     last_frame: Instant,
     background_colour: (f32, f32, f32, f32),
 }
@@ -62,24 +70,28 @@ impl Gui {
 
         let renderer = Renderer::init(&mut imgui, &display).expect("Failed to initialize renderer");
 
+        let gpu = Gpu::new(&display);
+
         Gui {
             event_loop: events_loop,
             display,
             imgui,
             platform,
             renderer,
+            gpu,
             last_frame: Instant::now(),
             background_colour,
         }
     }
 
-    pub fn main_loop(self, mut lameboy: Lameboy) {
+    pub fn main_loop(self, mut lameboy: Lameboy, mut gdb: Option<GdbServer>) {
         let Gui {
             event_loop,
             display,
             mut imgui,
             mut platform,
             mut renderer,
+            mut gpu,
             background_colour,
             ..
         } = self;
@@ -111,12 +123,20 @@ impl Gui {
                 );
                 platform.prepare_render(ui, gl_window.window());
 
-                if lameboy.is_running() {
+                if let Some(gdb) = &mut gdb {
+                    gdb.poll(&mut lameboy);
+                }
+
+                if lameboy.rewinding {
+                    lameboy.rewind();
+                } else if lameboy.is_running() {
                     lameboy.run_frame();
                 }
 
-                lameboy.get_ppu().draw(&mut target);
+                gpu.load_texture(lameboy.get_ppu().get_screen_buffer());
+                gpu.draw(&mut target);
                 lameboy.imgui_display(ui);
+                Gui::upload_debug_textures(&display, &mut renderer, &mut lameboy.debug.textures);
 
                 if !lameboy.active {
                     *control_flow = ControlFlow::Exit;
@@ -137,13 +157,53 @@ impl Gui {
                 platform.handle_event(imgui.io_mut(), gl_window.window(), &event);
 
                 if let Event::WindowEvent { event: i, .. } = event {
-                    Gui::update_events(&mut lameboy, &i, imgui.style_mut());
+                    let want_keyboard = imgui.io().want_capture_keyboard;
+                    Gui::update_events(&mut lameboy, &i, want_keyboard, imgui.style_mut());
                 }
             }
         })
     }
 
-    fn update_events(lameboy: &mut Lameboy, event: &WindowEvent, style: &mut Style) {
+    /// Copy the images the debug windows drew this frame into textures imgui can render.
+    fn upload_debug_textures(
+        display: &Display,
+        renderer: &mut Renderer,
+        textures: &mut DebugTextures,
+    ) {
+        for (name, image) in textures.take_pending() {
+            let raw =
+                RawImage2d::from_raw_rgba(image.pixels, (image.width as u32, image.height as u32));
+            let texture = match Texture2d::new(display, raw) {
+                Ok(texture) => texture,
+                Err(e) => {
+                    error!("Unable to create texture for {}: {}", name, e);
+                    continue;
+                }
+            };
+            let texture = Texture {
+                texture: Rc::new(texture),
+                sampler: SamplerBehavior {
+                    magnify_filter: MagnifySamplerFilter::Nearest,
+                    minify_filter: MinifySamplerFilter::Nearest,
+                    ..SamplerBehavior::default()
+                },
+            };
+
+            match textures.id(name) {
+                Some(id) => {
+                    renderer.textures().replace(id, texture);
+                }
+                None => textures.set_id(name, renderer.textures().insert(texture)),
+            }
+        }
+    }
+
+    fn update_events(
+        lameboy: &mut Lameboy,
+        event: &WindowEvent,
+        want_keyboard: bool,
+        style: &mut Style,
+    ) {
         match event {
             WindowEvent::CloseRequested => lameboy.active = false,
             WindowEvent::KeyboardInput { input, .. } => {
@@ -160,6 +220,11 @@ impl Gui {
                     Some(VirtualKeyCode::LShift) | Some(VirtualKeyCode::RShift) => {
                         lameboy.get_joypad().select = pressed
                     }
+
+                    // Leave backspace alone while it's editing a text box
+                    Some(VirtualKeyCode::Back) if !want_keyboard || !pressed => {
+                        lameboy.rewinding = pressed
+                    }
                     _ => {}
                 }
             }
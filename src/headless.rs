@@ -0,0 +1,331 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::dis::SymbolTable;
+use crate::lameboy::cdl::CodeDataLog;
+use crate::lameboy::joypad::Joypad;
+use crate::lameboy::ppu::palette::DMG_SHADES;
+use crate::lameboy::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::lameboy::profiler::Function;
+use crate::lameboy::trace::TraceWriter;
+use crate::lameboy::{Lameboy, CYCLES_PER_FRAME};
+
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    /// ROM file to load
+    pub file: String,
+    /// Number of frames to run for
+    #[arg(long, default_value_t = 60)]
+    pub frames: u64,
+    /// Number of cycles to run for, instead of a number of frames
+    #[arg(long, conflicts_with = "frames")]
+    pub cycles: Option<u64>,
+    /// Joypad input script, each line is a frame number followed by the buttons held from then on
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+    /// Write the final frame to this PNG file
+    #[arg(long)]
+    pub screenshot: Option<PathBuf>,
+    /// Write a Gameboy Doctor trace of the run to this file
+    #[arg(long)]
+    pub trace: Option<PathBuf>,
+    /// Only trace instructions from this many cycles into the run
+    #[arg(long, default_value_t = 0, requires = "trace")]
+    pub trace_start: u64,
+    /// Stop tracing this many cycles into the run
+    #[arg(long, requires = "trace")]
+    pub trace_end: Option<u64>,
+    /// Log how every ROM byte is used to this code/data log file, adding to it if it exists
+    #[arg(long)]
+    pub cdl: Option<PathBuf>,
+    /// Profile where CPU cycles go and write the call stacks to this folded-stack file
+    #[arg(long)]
+    pub profile: Option<PathBuf>,
+    /// Write the final background tile map to this PNG file, outlining the part on screen
+    #[arg(long)]
+    pub tile_map: Option<PathBuf>,
+}
+
+bitflags! {
+    pub struct Buttons: u8 {
+        const A      = 0b0000_0001;
+        const B      = 0b0000_0010;
+        const SELECT = 0b0000_0100;
+        const START  = 0b0000_1000;
+        const RIGHT  = 0b0001_0000;
+        const LEFT   = 0b0010_0000;
+        const UP     = 0b0100_0000;
+        const DOWN   = 0b1000_0000;
+    }
+}
+
+impl Buttons {
+    fn from_name(name: &str) -> Option<Buttons> {
+        match name.to_ascii_lowercase().as_str() {
+            "a" => Some(Buttons::A),
+            "b" => Some(Buttons::B),
+            "select" => Some(Buttons::SELECT),
+            "start" => Some(Buttons::START),
+            "right" => Some(Buttons::RIGHT),
+            "left" => Some(Buttons::LEFT),
+            "up" => Some(Buttons::UP),
+            "down" => Some(Buttons::DOWN),
+            _ => None,
+        }
+    }
+
+    fn apply(self, joypad: &mut Joypad) {
+        joypad.a = self.contains(Buttons::A);
+        joypad.b = self.contains(Buttons::B);
+        joypad.select = self.contains(Buttons::SELECT);
+        joypad.start = self.contains(Buttons::START);
+        joypad.right = self.contains(Buttons::RIGHT);
+        joypad.left = self.contains(Buttons::LEFT);
+        joypad.up = self.contains(Buttons::UP);
+        joypad.down = self.contains(Buttons::DOWN);
+    }
+}
+
+/// Scripted joypad input, as the set of buttons held from a given frame onwards.
+///
+/// ```text
+/// # frame  buttons
+/// 60       start
+/// 62
+/// 120      a right
+/// ```
+///
+/// A line with no buttons releases everything. Lines must be in frame order.
+#[derive(Debug, PartialEq)]
+pub struct InputScript {
+    changes: Vec<(u64, Buttons)>,
+}
+
+impl InputScript {
+    pub fn parse(script: &str) -> Result<InputScript, String> {
+        let mut changes: Vec<(u64, Buttons)> = Vec::new();
+
+        for (line_number, line) in script.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut words = line.split(|c: char| c.is_whitespace() || c == ',');
+            let frame = words.next().unwrap_or("");
+            let frame: u64 = frame
+                .parse()
+                .map_err(|_| format!("Line {}: invalid frame '{frame}'", line_number + 1))?;
+            if changes.last().is_some_and(|&(last, _)| frame < last) {
+                return Err(format!("Line {}: frames out of order", line_number + 1));
+            }
+
+            let mut buttons = Buttons::empty();
+            for name in words.filter(|word| !word.is_empty()) {
+                buttons |= Buttons::from_name(name)
+                    .ok_or_else(|| format!("Line {}: unknown button '{name}'", line_number + 1))?;
+            }
+
+            changes.push((frame, buttons));
+        }
+
+        Ok(InputScript { changes })
+    }
+
+    /// The buttons held during a frame.
+    pub fn buttons_at(&self, frame: u64) -> Buttons {
+        self.changes
+            .iter()
+            .take_while(|&&(from, _)| from <= frame)
+            .last()
+            .map_or(Buttons::empty(), |&(_, buttons)| buttons)
+    }
+}
+
+/// 64-bit FNV-1a, used for state hashes as it is stable across builds and platforms.
+fn fnv1a(chunks: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for &byte in chunks.iter().flat_map(|chunk| chunk.iter()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    hash
+}
+
+/// Hashes of the emulator state at the end of a headless run.
+#[derive(Debug, PartialEq)]
+pub struct StateHashes {
+    pub frame: u64,
+    pub wram: u64,
+    pub vram: u64,
+}
+
+impl StateHashes {
+    pub fn capture(lameboy: &mut Lameboy) -> StateHashes {
+        StateHashes {
+            frame: fnv1a(&[lameboy.get_ppu().get_screen_buffer()]),
+            wram: fnv1a(&lameboy.get_mmu().get_wram()),
+            vram: fnv1a(&[lameboy.get_ppu().get_vram()]),
+        }
+    }
+}
+
+/// Run a ROM for a fixed number of cycles without a window, applying scripted input at the start
+/// of each frame.
+pub fn run(lameboy: &mut Lameboy, cycles: u64, input: Option<&InputScript>) {
+    let frame_cycles = u64::from(CYCLES_PER_FRAME);
+    let mut current_frame = None;
+    let mut elapsed: u64 = 0;
+
+    while elapsed < cycles {
+        let frame = elapsed / frame_cycles;
+        if current_frame != Some(frame) {
+            current_frame = Some(frame);
+            if let Some(input) = input {
+                input.buttons_at(frame).apply(lameboy.get_joypad());
+            }
+        }
+
+        elapsed += u64::from(lameboy.step());
+    }
+}
+
+/// Write a screen buffer of shade indices out as an RGB PNG.
+pub fn write_screenshot(path: &Path, screen_buffer: &[u8]) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Unable to create screenshot {}: {e}", path.display()))?;
+
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        SCREEN_WIDTH as u32,
+        SCREEN_HEIGHT as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let pixels: Vec<u8> = screen_buffer
+        .iter()
+        .flat_map(|&shade| DMG_SHADES[(shade & 0x03) as usize])
+        .collect();
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| format!("Unable to write screenshot {}: {e}", path.display()))
+}
+
+/// Handle the `run` command, printing the state hashes once finished.
+pub fn run_command(args: &RunArgs, rom_data: Vec<u8>) -> Result<(), String> {
+    let input = match &args.input {
+        Some(path) => {
+            let script = fs::read_to_string(path)
+                .map_err(|e| format!("Unable to read input script {}: {e}", path.display()))?;
+            Some(InputScript::parse(&script)?)
+        }
+        None => None,
+    };
+
+    let mut lameboy = Lameboy::new(rom_data);
+    lameboy.reset();
+
+    if let Some(path) = &args.trace {
+        lameboy.start_trace(TraceWriter::create(path)?, args.trace_start, args.trace_end);
+    }
+
+    if let Some(path) = &args.cdl {
+        let rom_size = lameboy.get_cart().rom_length();
+        lameboy.get_mmu().code_data_log = Some(CodeDataLog::load_or_new(path, rom_size)?);
+    }
+
+    lameboy.get_profiler().enabled = args.profile.is_some();
+
+    let cycles = args
+        .cycles
+        .unwrap_or(args.frames * u64::from(CYCLES_PER_FRAME));
+    run(&mut lameboy, cycles, input.as_ref());
+    lameboy.stop_trace();
+
+    if let Some(path) = &args.screenshot {
+        write_screenshot(path, lameboy.get_ppu().get_screen_buffer())?;
+    }
+
+    if let Some(path) = &args.tile_map {
+        let ppu = lameboy.get_ppu();
+        ppu.tile_map(ppu.bg_map(), true).save_png(path)?;
+    }
+
+    if let Some(path) = &args.cdl {
+        if let Some(log) = &lameboy.get_mmu().code_data_log {
+            log.save(path)?;
+            println!("cdl:    {}", log.coverage());
+        }
+    }
+
+    if let Some(path) = &args.profile {
+        let symbols = match SymbolTable::find_for_rom(Path::new(&args.file)) {
+            Some(sym_path) => SymbolTable::load(&sym_path)?,
+            None => SymbolTable::new(),
+        };
+        lameboy
+            .get_profiler()
+            .export_folded(path, |function| match function {
+                Function::Routine { bank, addr } => {
+                    function.name(symbols.label_in_bank(*bank, *addr))
+                }
+                _ => function.name(None),
+            })?;
+    }
+
+    let hashes = StateHashes::capture(&mut lameboy);
+    println!("cycles: {}", lameboy.get_cycle_count());
+    println!("frame:  {:016x}", hashes.frame);
+    println!("wram:   {:016x}", hashes.wram);
+    println!("vram:   {:016x}", hashes.vram);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::COUNTER_PROGRAM;
+
+    #[test]
+    fn parse_input_script() {
+        let script = InputScript::parse("# intro\n60 start\n62\n\n120 a, right # jump\n").unwrap();
+
+        assert_eq!(Buttons::empty(), script.buttons_at(0));
+        assert_eq!(Buttons::START, script.buttons_at(60));
+        assert_eq!(Buttons::START, script.buttons_at(61));
+        assert_eq!(Buttons::empty(), script.buttons_at(62));
+        assert_eq!(Buttons::A | Buttons::RIGHT, script.buttons_at(500));
+    }
+
+    #[test]
+    fn reject_bad_input_script() {
+        assert!(InputScript::parse("ten start").is_err());
+        assert!(InputScript::parse("10 jump").is_err());
+        assert!(InputScript::parse("10 a\n5 b").is_err());
+    }
+
+    #[test]
+    fn hashes_are_deterministic() {
+        let run_rom = || {
+            let mut lameboy = Lameboy::with_program(COUNTER_PROGRAM);
+            run(&mut lameboy, 10_000, None);
+            StateHashes::capture(&mut lameboy)
+        };
+
+        let hashes = run_rom();
+        assert_eq!(hashes, run_rom());
+        assert_ne!(fnv1a(&[&[0; 0x1000], &[0; 0x1000]]), hashes.wram);
+    }
+
+    #[test]
+    fn fnv1a_reference_values() {
+        assert_eq!(0xCBF2_9CE4_8422_2325, fnv1a(&[]));
+        assert_eq!(0xAF63_DC4C_8601_EC8C, fnv1a(&[b"a"]));
+        assert_eq!(fnv1a(&[b"foobar"]), fnv1a(&[b"foo", b"bar"]));
+    }
+}
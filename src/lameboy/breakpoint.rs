@@ -0,0 +1,163 @@
+use crate::lameboy::expression::{Expression, ExpressionContext};
+use crate::lameboy::mmu::location::Location;
+
+/// An execution breakpoint with an optional condition.
+pub struct Breakpoint {
+    pub addr: u16,
+    /// Only break with this bank mapped at the address, or in any bank if None.
+    pub bank: Option<u16>,
+    pub enabled: bool,
+    pub condition: Option<Expression>,
+    /// How many times execution has reached this breakpoint with its condition true.
+    pub hit_count: u32,
+    /// How many hits to let pass before stopping.
+    pub ignore_count: u32,
+}
+
+impl Breakpoint {
+    pub fn new(addr: u16) -> Breakpoint {
+        Breakpoint::at(Location::from(addr))
+    }
+
+    pub fn at(location: Location) -> Breakpoint {
+        Breakpoint {
+            addr: location.addr,
+            bank: location.bank,
+            enabled: true,
+            condition: None,
+            hit_count: 0,
+            ignore_count: 0,
+        }
+    }
+
+    pub fn location(&self) -> Location {
+        Location {
+            bank: self.bank,
+            addr: self.addr,
+        }
+    }
+
+    /// Whether the breakpoint is on an address in a bank, a breakpoint without a bank being on
+    /// every bank.
+    pub fn is_at(&self, bank: u16, addr: u16) -> bool {
+        self.addr == addr && self.bank.is_none_or(|b| b == bank)
+    }
+
+    /// Parse and set the condition, an empty string removes it.
+    pub fn set_condition(&mut self, source: &str) -> Result<(), String> {
+        self.condition = if source.trim().is_empty() {
+            None
+        } else {
+            Some(Expression::parse(source)?)
+        };
+
+        Ok(())
+    }
+
+    pub fn condition_source(&self) -> &str {
+        self.condition.as_ref().map_or("", |c| c.source())
+    }
+
+    /// Check whether execution should stop at `pc`, counting a hit if the address and condition
+    /// match. A condition that fails to evaluate stops execution so the error is not missed.
+    pub fn should_break(&mut self, pc: u16, context: &dyn ExpressionContext) -> bool {
+        if !self.enabled || !self.is_at(context.bank_of(pc), pc) {
+            return false;
+        }
+
+        if let Some(condition) = &self.condition {
+            match condition.is_true(context) {
+                Ok(true) => {}
+                Ok(false) => return false,
+                Err(e) => {
+                    warn!(
+                        "Breakpoint 0x{:04X} condition '{}': {}",
+                        self.addr, condition, e
+                    );
+                    return true;
+                }
+            }
+        }
+
+        self.hit_count += 1;
+        self.hit_count > self.ignore_count
+    }
+}
+
+/// Check every breakpoint against the PC, so all matching breakpoints count their hits.
+pub fn check_breakpoints(
+    breakpoints: &mut [Breakpoint],
+    pc: u16,
+    context: &dyn ExpressionContext,
+) -> bool {
+    let mut hit = false;
+    for breakpoint in breakpoints.iter_mut() {
+        hit |= breakpoint.should_break(pc, context);
+    }
+
+    hit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn unconditional() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let mut breakpoint = Breakpoint::new(0x0100);
+
+        assert!(!breakpoint.should_break(0x0101, lameboy.get_cpu()));
+        assert!(breakpoint.should_break(0x0100, lameboy.get_cpu()));
+        assert_eq!(1, breakpoint.hit_count);
+
+        breakpoint.enabled = false;
+        assert!(!breakpoint.should_break(0x0100, lameboy.get_cpu()));
+    }
+
+    #[test]
+    fn conditional() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let mut breakpoint = Breakpoint::new(0x0100);
+        breakpoint
+            .set_condition("a == 2 && [0xC000] == 0x05")
+            .unwrap();
+
+        assert!(!breakpoint.should_break(0x0100, lameboy.get_cpu()));
+
+        lameboy.get_cpu().registers.a = 2;
+        lameboy.get_mmu().write8(0xC000, 0x05);
+        assert!(breakpoint.should_break(0x0100, lameboy.get_cpu()));
+        assert_eq!(1, breakpoint.hit_count);
+
+        assert!(breakpoint.set_condition("a ==").is_err());
+        breakpoint.set_condition(" ").unwrap();
+        assert!(breakpoint.condition.is_none());
+    }
+
+    #[test]
+    fn ignore_count() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let mut breakpoint = Breakpoint::new(0x0100);
+        breakpoint.ignore_count = 2;
+
+        assert!(!breakpoint.should_break(0x0100, lameboy.get_cpu()));
+        assert!(!breakpoint.should_break(0x0100, lameboy.get_cpu()));
+        assert!(breakpoint.should_break(0x0100, lameboy.get_cpu()));
+        assert_eq!(3, breakpoint.hit_count);
+    }
+
+    #[test]
+    fn banked() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let mut other_bank = Breakpoint::at(Location::new(3, 0x4000));
+        let mut mapped_bank = Breakpoint::at(Location::new(1, 0x4000));
+        let mut any_bank = Breakpoint::new(0x4000);
+
+        assert!(!other_bank.should_break(0x4000, lameboy.get_cpu()));
+        assert!(mapped_bank.should_break(0x4000, lameboy.get_cpu()));
+        assert!(any_bank.should_break(0x4000, lameboy.get_cpu()));
+        assert_eq!("03:4000", other_bank.location().to_string());
+    }
+}
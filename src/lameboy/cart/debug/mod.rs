@@ -21,6 +21,7 @@ impl ImguiDebuggable for Cart {
                         "INVALID"
                     }
                 ));
+                ui.text(format!("Global checksum: 0x{:04X}", self.global_checksum));
             });
     }
 }
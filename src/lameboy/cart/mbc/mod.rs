@@ -3,6 +3,16 @@ mod nombc;
 pub trait Mbc {
     fn read(&self, addr: u16) -> u8;
     fn write(&self, addr: u16, data: u8);
+    /// ROM bank mapped into 0x4000 - 0x7FFF.
+    fn rom_bank(&self) -> u16;
+    /// RAM bank mapped into 0xA000 - 0xBFFF.
+    fn ram_bank(&self) -> u16;
+    /// How many 16KB ROM banks the cart has.
+    fn rom_banks(&self) -> u16;
+    /// Read from a ROM bank whether or not it is mapped, the address being in either ROM region.
+    fn read_rom_bank(&self, bank: u16, addr: u16) -> u8;
+    /// Read from a RAM bank whether or not it is mapped.
+    fn read_ram_bank(&self, bank: u16, addr: u16) -> u8;
 }
 
 pub trait DebuggableMBC: Mbc + core::fmt::Debug {}
@@ -36,6 +36,27 @@ impl Mbc for NoMBC {
     fn write(&self, addr: u16, data: u8) {
         debug!("Attempted to access cart [WRITE] to no-MBC cart [0x{addr:04X}] = 0x{data:02X}");
     }
+
+    fn rom_bank(&self) -> u16 {
+        1
+    }
+
+    fn ram_bank(&self) -> u16 {
+        0
+    }
+
+    fn rom_banks(&self) -> u16 {
+        (self.rom_data.len() / 0x4000) as u16
+    }
+
+    fn read_rom_bank(&self, bank: u16, addr: u16) -> u8 {
+        let offset = usize::from(bank) * 0x4000 + usize::from(addr & 0x3FFF);
+        self.rom_data.get(offset).copied().unwrap_or(0xFF)
+    }
+
+    fn read_ram_bank(&self, _bank: u16, _addr: u16) -> u8 {
+        0xFF
+    }
 }
 
 impl fmt::Debug for NoMBC {
@@ -37,6 +37,8 @@ pub struct Cart {
     pub rom_size: u8,
     pub ram_size: u8,
     pub valid_checksum: bool,
+    /// Checksum of the whole ROM from the header, used to tell ROMs apart.
+    pub global_checksum: u16,
     mbc: Box<dyn DebuggableMBC>,
 }
 
@@ -48,6 +50,7 @@ impl Cart {
         let rom_size = rom_data[ROM_SIZE_OFFSET];
         let ram_size = rom_data[RAM_SIZE_OFFSET];
         let valid_checksum = Cart::validate_checksum(&rom_data);
+        let global_checksum = Cart::parse_global_checksum(&rom_data);
 
         let mbc = get_mbc(rom_data, cart_type, rom_size, ram_size).unwrap();
 
@@ -58,15 +61,47 @@ impl Cart {
             ram_size,
             mbc,
             valid_checksum,
+            global_checksum,
         }
     }
 
+    pub fn rom_bank(&self) -> u16 {
+        self.mbc.rom_bank()
+    }
+
+    pub fn ram_bank(&self) -> u16 {
+        self.mbc.ram_bank()
+    }
+
+    pub fn rom_banks(&self) -> u16 {
+        self.mbc.rom_banks()
+    }
+
+    /// Size of the ROM in bytes.
+    pub fn rom_length(&self) -> usize {
+        usize::from(self.rom_banks()) * 0x4000
+    }
+
+    pub fn read_rom_bank(&self, bank: u16, addr: u16) -> u8 {
+        self.mbc.read_rom_bank(bank, addr)
+    }
+
+    pub fn read_ram_bank(&self, bank: u16, addr: u16) -> u8 {
+        self.mbc.read_ram_bank(bank, addr)
+    }
+
     fn parse_title(rom_data: &[u8]) -> String {
         String::from_utf8_lossy(&rom_data[TITLE_OFFSET..TITLE_OFFSET + TITLE_LENGTH_DMG])
             .trim_matches(char::from(0))
             .to_string()
     }
 
+    fn parse_global_checksum(rom_data: &[u8]) -> u16 {
+        let bytes =
+            &rom_data[GLOBAL_CHECKSUM_OFFSET..GLOBAL_CHECKSUM_OFFSET + GLOBAL_CHECKSUM_LENGTH];
+        u16::from_be_bytes([bytes[0], bytes[1]])
+    }
+
     fn validate_checksum(rom_data: &[u8]) -> bool {
         let mut chksum: u8 = 0;
         for byte in rom_data
@@ -142,6 +177,7 @@ pub fn parse_ram_size(ram_size: u8) -> Result<usize, String> {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::unnecessary_cast)]
 mod tests {
     use super::*;
 
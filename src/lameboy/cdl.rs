@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::Path;
+
+bitflags! {
+    /// How a ROM byte has been accessed, one byte of these per ROM byte in a CDL file.
+    pub struct CdlFlags: u8 {
+        /// Executed as the first byte of an instruction.
+        const OPCODE  = 0b0000_0001;
+        /// Fetched as part of an instruction, after its first byte.
+        const OPERAND = 0b0000_0010;
+        /// Read as data by an instruction.
+        const DATA    = 0b0000_0100;
+        /// Copied to OAM by DMA.
+        const DMA     = 0b0000_1000;
+    }
+}
+
+/// A code/data log, recording how each byte of the ROM has been used, by physical ROM offset so
+/// the same address in different banks is told apart.
+pub struct CodeDataLog {
+    flags: Vec<u8>,
+}
+
+impl CodeDataLog {
+    pub fn new(rom_size: usize) -> CodeDataLog {
+        CodeDataLog {
+            flags: vec![0; rom_size],
+        }
+    }
+
+    /// Load a log saved by an earlier run to add to, or start a new one if there isn't a file.
+    pub fn load_or_new(path: &Path, rom_size: usize) -> Result<CodeDataLog, String> {
+        if !path.is_file() {
+            return Ok(CodeDataLog::new(rom_size));
+        }
+
+        CodeDataLog::load(path, rom_size)
+    }
+
+    /// Load a log saved for a ROM of a given size.
+    pub fn load(path: &Path, rom_size: usize) -> Result<CodeDataLog, String> {
+        let flags = fs::read(path)
+            .map_err(|e| format!("Unable to read code/data log {}: {e}", path.display()))?;
+        if flags.len() != rom_size {
+            return Err(format!(
+                "Code/data log {} is {} bytes but the ROM is {rom_size}",
+                path.display(),
+                flags.len()
+            ));
+        }
+        info!("Loaded code/data log from {}", path.display());
+
+        Ok(CodeDataLog { flags })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, &self.flags)
+            .map_err(|e| format!("Unable to write code/data log {}: {e}", path.display()))?;
+        info!("Saved code/data log to {}", path.display());
+
+        Ok(())
+    }
+
+    pub fn mark(&mut self, offset: usize, flags: CdlFlags) {
+        if let Some(logged) = self.flags.get_mut(offset) {
+            *logged |= flags.bits();
+        }
+    }
+
+    pub fn get(&self, offset: usize) -> CdlFlags {
+        self.flags.get(offset).map_or(CdlFlags::empty(), |&flags| {
+            CdlFlags::from_bits_truncate(flags)
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.flags
+    }
+
+    pub fn clear(&mut self) {
+        self.flags.fill(0);
+    }
+
+    /// How many ROM bytes have been accessed in any of the given ways.
+    pub fn count(&self, flags: CdlFlags) -> usize {
+        self.flags
+            .iter()
+            .filter(|&&logged| logged & flags.bits() != 0)
+            .count()
+    }
+
+    /// The ROM offsets of every byte executed as an opcode.
+    pub fn opcode_offsets(&self) -> Vec<usize> {
+        self.flags
+            .iter()
+            .enumerate()
+            .filter(|&(_, &logged)| logged & CdlFlags::OPCODE.bits() != 0)
+            .map(|(offset, _)| offset)
+            .collect()
+    }
+
+    /// A summary of how much of the ROM was executed or read.
+    pub fn coverage(&self) -> String {
+        let percent = |count: usize| 100.0 * count as f64 / self.flags.len().max(1) as f64;
+        let code = self.count(CdlFlags::OPCODE | CdlFlags::OPERAND);
+        let data = self.count(CdlFlags::DATA | CdlFlags::DMA);
+        format!(
+            "{code} bytes of code ({:.1}%), {data} bytes of data ({:.1}%)",
+            percent(code),
+            percent(data)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn opcode_offsets() {
+        let mut flags = vec![0x00; 0x10];
+        flags[0x02] = CdlFlags::OPCODE.bits();
+        flags[0x03] = (CdlFlags::OPCODE | CdlFlags::OPERAND).bits();
+        flags[0x04] = (CdlFlags::OPERAND | CdlFlags::DATA).bits();
+        let log = CodeDataLog { flags };
+
+        assert_eq!(vec![0x02, 0x03], log.opcode_offsets());
+    }
+
+    #[test]
+    fn logs_rom_accesses() {
+        // ld a, [$4000]; ld a, $40; ldh [$FF46], a
+        let mut lameboy = Lameboy::with_program(&[0xFA, 0x00, 0x40, 0x3E, 0x40, 0xE0, 0x46]);
+        lameboy.get_mmu().code_data_log = Some(CodeDataLog::new(0x8000));
+        for _ in 0..3 {
+            lameboy.step();
+        }
+
+        let log = lameboy.get_mmu().code_data_log.take().unwrap();
+        assert_eq!(CdlFlags::OPCODE, log.get(0x0100));
+        assert_eq!(CdlFlags::OPERAND, log.get(0x0101));
+        assert_eq!(CdlFlags::OPCODE, log.get(0x0103));
+        assert_eq!(CdlFlags::DATA | CdlFlags::DMA, log.get(0x4000));
+        assert_eq!(CdlFlags::DMA, log.get(0x409F));
+        assert_eq!(CdlFlags::empty(), log.get(0x40A0));
+        assert_eq!(7, log.count(CdlFlags::OPCODE | CdlFlags::OPERAND));
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Deepest the shadow stack is allowed to grow, as a game that never returns from its calls would
+/// otherwise grow it forever.
+const MAX_DEPTH: usize = 256;
+
+/// How many recent mismatches are kept for display.
+const MISMATCH_LOG_SIZE: usize = 64;
+
+/// A call that has not returned yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallFrame {
+    /// Address of the CALL or RST, or the instruction interrupted.
+    pub call_site: u16,
+    pub target: u16,
+    /// Address pushed to the stack to return to.
+    pub return_addr: u16,
+    /// Stack pointer after the return address was pushed.
+    pub sp: u16,
+    pub interrupt: bool,
+}
+
+/// A return or call that didn't line up with the shadow stack, usually from a game manipulating
+/// the stack by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackMismatch {
+    /// A return from a frame to an address other than the one its call pushed.
+    ReturnAddress { pc: u16, expected: u16, actual: u16 },
+    /// A return from an outer frame, skipping the frames above it.
+    Unwound { pc: u16, frames: usize },
+    /// A return that doesn't match any call, such as pushing an address and returning to it.
+    NoCall { pc: u16, addr: u16 },
+    /// A call made with the stack pointer above frames still on the stack, which were dropped.
+    Dropped { pc: u16, frames: usize },
+}
+
+impl fmt::Display for StackMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackMismatch::ReturnAddress {
+                pc,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "0x{pc:04X} returned to 0x{actual:04X} instead of 0x{expected:04X}"
+            ),
+            StackMismatch::Unwound { pc, frames } => {
+                write!(f, "0x{pc:04X} returned past {frames} frame(s)")
+            }
+            StackMismatch::NoCall { pc, addr } => {
+                write!(f, "0x{pc:04X} returned to 0x{addr:04X} without a call")
+            }
+            StackMismatch::Dropped { pc, frames } => {
+                write!(
+                    f,
+                    "0x{pc:04X} called over {frames} frame(s) still on the stack"
+                )
+            }
+        }
+    }
+}
+
+/// A shadow call stack built by watching calls, interrupts and returns as the CPU executes them.
+pub struct CallStack {
+    frames: Vec<CallFrame>,
+    pub mismatches: VecDeque<StackMismatch>,
+}
+
+impl CallStack {
+    pub fn new() -> CallStack {
+        CallStack {
+            frames: Vec::new(),
+            mismatches: VecDeque::with_capacity(MISMATCH_LOG_SIZE),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.frames.clear();
+        self.mismatches.clear();
+    }
+
+    /// The frames on the stack, outermost first.
+    pub fn frames(&self) -> &[CallFrame] {
+        &self.frames
+    }
+
+    pub fn on_call(&mut self, frame: CallFrame) {
+        // Anything at or below the new frame's stack pointer can no longer be returned to
+        let live = self.frames.partition_point(|f| f.sp > frame.sp);
+        if live < self.frames.len() {
+            self.record(StackMismatch::Dropped {
+                pc: frame.call_site,
+                frames: self.frames.len() - live,
+            });
+            self.frames.truncate(live);
+        }
+
+        if self.frames.len() == MAX_DEPTH {
+            self.frames.remove(0);
+        }
+        self.frames.push(frame);
+    }
+
+    /// Handle a return from the instruction at `pc`, popping `addr` off the stack at `sp`.
+    pub fn on_return(&mut self, pc: u16, sp: u16, addr: u16) {
+        let Some(index) = self.frames.iter().rposition(|f| f.sp == sp) else {
+            self.record(StackMismatch::NoCall { pc, addr });
+            return;
+        };
+
+        let unwound = self.frames.len() - 1 - index;
+        if unwound > 0 {
+            self.record(StackMismatch::Unwound {
+                pc,
+                frames: unwound,
+            });
+        }
+
+        let expected = self.frames[index].return_addr;
+        if expected != addr {
+            self.record(StackMismatch::ReturnAddress {
+                pc,
+                expected,
+                actual: addr,
+            });
+        }
+
+        self.frames.truncate(index);
+    }
+
+    fn record(&mut self, mismatch: StackMismatch) {
+        debug!("Call stack mismatch: {}", mismatch);
+        if self.mismatches.len() == MISMATCH_LOG_SIZE {
+            self.mismatches.pop_front();
+        }
+        self.mismatches.push_back(mismatch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    fn frame(call_site: u16, target: u16, sp: u16) -> CallFrame {
+        CallFrame {
+            call_site,
+            target,
+            return_addr: call_site + 3,
+            sp,
+            interrupt: false,
+        }
+    }
+
+    #[test]
+    fn tracks_calls_and_interrupts() {
+        let mut lameboy = Lameboy::with_code(&[
+            // call 0x0200
+            (0x0100, &[0xCD, 0x00, 0x02]),
+            // rst 0x08; ret
+            (0x0200, &[0xCF, 0xC9]),
+            // reti
+            (0x0040, &[0xD9]),
+        ]);
+        lameboy.get_mmu().write8(0xFFFF, 0x00);
+
+        lameboy.step();
+        lameboy.step();
+        assert_eq!(
+            &[
+                frame(0x0100, 0x0200, 0xFFFC),
+                CallFrame {
+                    call_site: 0x0200,
+                    target: 0x0008,
+                    return_addr: 0x0201,
+                    sp: 0xFFFA,
+                    interrupt: false,
+                }
+            ],
+            lameboy.get_cpu().call_stack.frames()
+        );
+
+        // An interrupt entering on top of the RST's handler
+        lameboy.get_mmu().write8(0xFFFF, 0x01);
+        lameboy.get_mmu().write8(0xFF0F, 0x01);
+        lameboy.step();
+        assert_eq!(
+            Some(&CallFrame {
+                call_site: 0x0009,
+                target: 0x0040,
+                return_addr: 0x0009,
+                sp: 0xFFF8,
+                interrupt: true,
+            }),
+            lameboy.get_cpu().call_stack.frames().last()
+        );
+
+        // reti back to the RST handler
+        lameboy.step();
+        assert_eq!(2, lameboy.get_cpu().call_stack.frames().len());
+        assert!(lameboy.get_cpu().call_stack.mismatches.is_empty());
+    }
+
+    #[test]
+    fn flags_manual_stack_changes() {
+        let mut call_stack = CallStack::new();
+        call_stack.on_call(frame(0x0100, 0x0200, 0xFFFC));
+        call_stack.on_call(frame(0x0210, 0x0300, 0xFFFA));
+
+        // Return from the outer frame, the inner one's return address having been popped
+        call_stack.on_return(0x0305, 0xFFFC, 0x0103);
+        assert_eq!(
+            Some(&StackMismatch::Unwound {
+                pc: 0x0305,
+                frames: 1
+            }),
+            call_stack.mismatches.back()
+        );
+        assert!(call_stack.frames().is_empty());
+
+        // Jump table trick of pushing an address and returning to it
+        call_stack.on_return(0x0400, 0xFFFC, 0x1234);
+        assert_eq!(
+            Some(&StackMismatch::NoCall {
+                pc: 0x0400,
+                addr: 0x1234
+            }),
+            call_stack.mismatches.back()
+        );
+
+        // Return address overwritten
+        call_stack.on_call(frame(0x0100, 0x0200, 0xFFFC));
+        call_stack.on_return(0x0205, 0xFFFC, 0x4000);
+        assert_eq!(
+            Some(&StackMismatch::ReturnAddress {
+                pc: 0x0205,
+                expected: 0x0103,
+                actual: 0x4000
+            }),
+            call_stack.mismatches.back()
+        );
+
+        // Stack pointer reset under existing frames
+        call_stack.on_call(frame(0x0100, 0x0200, 0xFFFC));
+        call_stack.on_call(frame(0x0500, 0x0600, 0xFFFC));
+        assert_eq!(
+            Some(&StackMismatch::Dropped {
+                pc: 0x0500,
+                frames: 1
+            }),
+            call_stack.mismatches.back()
+        );
+        assert_eq!(1, call_stack.frames().len());
+    }
+}
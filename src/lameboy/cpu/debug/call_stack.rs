@@ -0,0 +1,52 @@
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::cpu::Cpu;
+use imgui::{Condition, Ui};
+
+pub fn call_stack_window(cpu: &mut Cpu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("CPU - Call Stack")
+        .size([300.0, 200.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Click a frame to show its call site in the disassembly",
+            );
+            ui.separator();
+
+            let frames = cpu.call_stack.frames();
+            if frames.is_empty() {
+                ui.text("No calls");
+            }
+
+            // Innermost frame first, like a debugger's backtrace
+            for (depth, frame) in frames.iter().rev().enumerate() {
+                let label = format!(
+                    "#{depth:<3} 0x{:04X} -> 0x{:04X}  SP 0x{:04X}{}",
+                    frame.call_site,
+                    frame.target,
+                    frame.sp,
+                    if frame.interrupt { "  [int]" } else { "" },
+                );
+                if ui.selectable(label) {
+                    imgui_debug.disassemble_memory_pc_lock = false;
+                    imgui_debug.disassemble_memory_addr = i32::from(frame.call_site);
+                }
+            }
+
+            let mismatches = &mut cpu.call_stack.mismatches;
+            if !mismatches.is_empty() {
+                ui.separator();
+                ui.text_colored(
+                    [1.0, 0.4, 0.4, 1.0],
+                    format!("{} stack mismatch(es)", mismatches.len()),
+                );
+                ui.same_line();
+                if ui.small_button("Clear") {
+                    mismatches.clear();
+                }
+                for mismatch in mismatches.iter().rev() {
+                    ui.text_colored([1.0, 0.6, 0.4, 1.0], mismatch.to_string());
+                }
+            }
+        });
+}
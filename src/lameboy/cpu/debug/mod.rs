@@ -1,3 +1,4 @@
+use crate::lameboy::cpu::debug::call_stack::call_stack_window;
 use crate::lameboy::cpu::debug::registers::registers_window;
 use crate::lameboy::cpu::debug::stack::stack_window;
 use crate::lameboy::cpu::Cpu;
@@ -6,12 +7,14 @@ use crate::gui::imgui_debug_state::ImguiDebugState;
 use crate::gui::imgui_debuggable::ImguiDebuggable;
 use imgui::Ui;
 
+mod call_stack;
 mod registers;
 mod stack;
 
 impl ImguiDebuggable for Cpu {
-    fn imgui_display(&mut self, ui: &Ui, _: &mut ImguiDebugState) {
+    fn imgui_display(&mut self, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
         registers_window(self, ui);
         stack_window(self, ui);
+        call_stack_window(self, ui, imgui_debug);
     }
 }
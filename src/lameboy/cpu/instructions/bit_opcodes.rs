@@ -1,17 +1,18 @@
 use crate::lameboy::cpu::instructions::{bit_index_from_opcode, register_from_opcode};
 use crate::lameboy::cpu::registers::{Flags, Reg16, Reg8, Register};
 use crate::lameboy::cpu::Cpu;
+use crate::lameboy::mmu::bus::Bus;
 
 /// Put the complement of an 8-bit values single bit into the RegisterFlags::ZERO flag.
 ///
-/// Takes 8 cycles unless operating on an indirectly addressed value, then 16 cycles.
+/// Takes 8 cycles unless operating on an indirectly addressed value, then 12 cycles.
 ///
 /// # Examples
 ///
 /// ```asm
 /// BIT 4, B  ; Flag::RegisterFlags::ZERO = (B & 0x01 << 4)
 /// ```
-pub fn bit_test(cpu: &mut Cpu, opcode: u8) -> u8 {
+pub fn bit_test<B: Bus>(cpu: &mut Cpu<B>, opcode: u8) -> u8 {
     let register = opcode & 0b0000_0111;
     let bit_index = (opcode & 0b0011_1000) >> 3;
 
@@ -23,7 +24,7 @@ pub fn bit_test(cpu: &mut Cpu, opcode: u8) -> u8 {
         0b011 => (cpu.registers.read8(&Reg8::E), 8),
         0b100 => (cpu.registers.read8(&Reg8::H), 8),
         0b101 => (cpu.registers.read8(&Reg8::L), 8),
-        0b110 => (cpu.mmu.read8(cpu.registers.read16(&Reg16::HL)), 16),
+        0b110 => (cpu.mmu.read8(cpu.registers.read16(&Reg16::HL)), 12),
         _ => panic!("Unhandled register bit pattern: 0b{register:08b}"),
     };
 
@@ -47,7 +48,7 @@ pub fn bit_test(cpu: &mut Cpu, opcode: u8) -> u8 {
 /// SET 4, B  ; B = (B | 0x01 << 4)
 /// RES 4, B  ; B = (B & 0x01 << 4)
 /// ```
-pub fn bit_assign(cpu: &mut Cpu, opcode: u8, set_bit: bool) -> u8 {
+pub fn bit_assign<B: Bus>(cpu: &mut Cpu<B>, opcode: u8, set_bit: bool) -> u8 {
     let bit_index = bit_index_from_opcode(opcode);
 
     let (mut value, duration) = match register_from_opcode(opcode) {
@@ -1,6 +1,8 @@
+use crate::lameboy::cpu::call_stack::CallFrame;
 use crate::lameboy::cpu::instructions::opcode_flag_test;
 use crate::lameboy::cpu::instructions::stack::push_stack_d16;
 use crate::lameboy::cpu::Cpu;
+use crate::lameboy::mmu::bus::Bus;
 
 /// Jump to a different address using 16-bit data as an address after first pushing the current PC
 /// to the stack.
@@ -12,13 +14,20 @@ use crate::lameboy::cpu::Cpu;
 /// ```asm
 /// CALL $0150 ; STACK <<- PC; PC <- 0x0150
 /// ```
-pub fn call_d16(cpu: &mut Cpu) -> u8 {
+pub fn call_d16<B: Bus>(cpu: &mut Cpu<B>) -> u8 {
     // Read 16-bit jump target address
     let jump_target = cpu.fetch16();
 
     // Push current PC to the stack
     let current_pc = cpu.registers.pc;
     push_stack_d16(cpu, current_pc);
+    cpu.call_stack.on_call(CallFrame {
+        call_site: current_pc.wrapping_sub(3),
+        target: jump_target,
+        return_addr: current_pc,
+        sp: cpu.registers.sp,
+        interrupt: false,
+    });
 
     // Jump PC to the target address
     cpu.registers.pc = jump_target;
@@ -36,7 +45,7 @@ pub fn call_d16(cpu: &mut Cpu) -> u8 {
 /// ```asm
 /// CALL NZ $0150 ; IF !Flags::RegisterFlags::ZERO { STACK <<- PC; PC <- 0x0150 }
 /// ```
-pub fn call_conditional_d16(cpu: &mut Cpu, opcode: u8) -> u8 {
+pub fn call_conditional_d16<B: Bus>(cpu: &mut Cpu<B>, opcode: u8) -> u8 {
     // Read 16-bit jump target address
     let jump_target = cpu.fetch16();
 
@@ -44,6 +53,13 @@ pub fn call_conditional_d16(cpu: &mut Cpu, opcode: u8) -> u8 {
         // Push current PC to the stack
         let current_pc = cpu.registers.pc;
         push_stack_d16(cpu, current_pc);
+        cpu.call_stack.on_call(CallFrame {
+            call_site: current_pc.wrapping_sub(3),
+            target: jump_target,
+            return_addr: current_pc,
+            sp: cpu.registers.sp,
+            interrupt: false,
+        });
 
         // Jump PC to the target address
         cpu.registers.pc = jump_target;
@@ -58,13 +74,20 @@ pub fn call_conditional_d16(cpu: &mut Cpu, opcode: u8) -> u8 {
 /// address after first pushing the current PC.
 ///
 /// Takes 12 cycles.
-pub fn call_interrupt(cpu: &mut Cpu, addr: u16) -> u8 {
+pub fn call_interrupt<B: Bus>(cpu: &mut Cpu<B>, addr: u16) -> u8 {
     // Disable further interrupts
     cpu.ime = false;
 
     // Save current PC on the stack
     let current_pc = cpu.registers.pc;
     push_stack_d16(cpu, current_pc);
+    cpu.call_stack.on_call(CallFrame {
+        call_site: current_pc,
+        target: addr,
+        return_addr: current_pc,
+        sp: cpu.registers.sp,
+        interrupt: true,
+    });
 
     // Jump to handler
     cpu.registers.pc = addr;
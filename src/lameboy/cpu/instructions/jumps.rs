@@ -1,6 +1,7 @@
 use crate::lameboy::cpu::instructions::opcode_flag_test;
 use crate::lameboy::cpu::registers::Reg16;
 use crate::lameboy::cpu::Cpu;
+use crate::lameboy::mmu::bus::Bus;
 
 /// Jump to a different address using 16-bit data as an address.
 ///
@@ -11,7 +12,7 @@ use crate::lameboy::cpu::Cpu;
 /// ```asm
 /// JP $0150 ; PC <- 0x0150
 /// ```
-pub fn jump_d16(cpu: &mut Cpu) -> u8 {
+pub fn jump_d16<B: Bus>(cpu: &mut Cpu<B>) -> u8 {
     // Read 16-bit jump target address
     let jump_target = cpu.fetch16();
 
@@ -31,7 +32,7 @@ pub fn jump_d16(cpu: &mut Cpu) -> u8 {
 /// ```asm
 /// JP NZ $0150 ; IF !Flags::RegisterFlags::ZERO { PC <- 0x0150 }
 /// ```
-pub fn jump_conditional_d16(cpu: &mut Cpu, opcode: u8) -> u8 {
+pub fn jump_conditional_d16<B: Bus>(cpu: &mut Cpu<B>, opcode: u8) -> u8 {
     // Read 16-bit jump target address
     let jump_target = cpu.fetch16();
 
@@ -55,7 +56,7 @@ pub fn jump_conditional_d16(cpu: &mut Cpu, opcode: u8) -> u8 {
 /// ```asm
 /// JP (HL) ; PC <- HL
 /// ```
-pub fn jump_r16(cpu: &mut Cpu, r16: &Reg16) -> u8 {
+pub fn jump_r16<B: Bus>(cpu: &mut Cpu<B>, r16: &Reg16) -> u8 {
     // Set PC to whatever the 16-bit register is
     cpu.registers.pc = cpu.registers.read16(r16);
 
@@ -71,14 +72,14 @@ pub fn jump_r16(cpu: &mut Cpu, r16: &Reg16) -> u8 {
 /// ```asm
 /// JR $DA ; PC <- PC + 0xDA
 /// ```
-pub fn jump_relative_d8(cpu: &mut Cpu) -> u8 {
+pub fn jump_relative_d8<B: Bus>(cpu: &mut Cpu<B>) -> u8 {
     // Read signed 8-bit jump offset
     let jump_offset: i8 = cpu.fetch8() as i8;
 
     // Jump PC to that target address
     cpu.registers.pc = cpu.registers.pc.wrapping_add(jump_offset as u16);
 
-    16
+    12
 }
 
 /// Jump to a different relative address by adding the 8-bit operand to the current PC register if
@@ -91,7 +92,7 @@ pub fn jump_relative_d8(cpu: &mut Cpu) -> u8 {
 /// ```asm
 /// JR NZ $DA ; IF !Flags::RegisterFlags::ZERO { PC <- PC + $DA }
 /// ```
-pub fn jump_relative_conditional_d8(cpu: &mut Cpu, opcode: u8) -> u8 {
+pub fn jump_relative_conditional_d8<B: Bus>(cpu: &mut Cpu<B>, opcode: u8) -> u8 {
     // Read signed 8-bit jump offset
     let jump_offset: i8 = cpu.fetch8() as i8;
 
@@ -42,6 +42,7 @@ fn opcode_flag_test(opcode: u8, flags: Flags) -> bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::opcode_flag_test;
     use crate::lameboy::cpu::registers::Flags;
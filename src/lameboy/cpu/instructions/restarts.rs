@@ -1,5 +1,7 @@
+use crate::lameboy::cpu::call_stack::CallFrame;
 use crate::lameboy::cpu::instructions::stack::push_stack_d16;
 use crate::lameboy::cpu::Cpu;
+use crate::lameboy::mmu::bus::Bus;
 
 /// Push the current PC to the stack and then jump to one of 8 positions in the zero page.
 ///
@@ -10,16 +12,23 @@ use crate::lameboy::cpu::Cpu;
 /// ```asm
 /// RST 1 ; STACK <<- PC; PC <- 0x0008
 /// ```
-pub fn restart(cpu: &mut Cpu, opcode: u8) -> u8 {
+pub fn restart<B: Bus>(cpu: &mut Cpu<B>, opcode: u8) -> u8 {
     // Push current PC to the stack
     let current_pc = cpu.registers.pc;
     push_stack_d16(cpu, current_pc);
 
     // Derive target address from opcode bits
-    let jump_target = opcode & 0b0011_1000;
+    let jump_target = u16::from(opcode & 0b0011_1000);
+    cpu.call_stack.on_call(CallFrame {
+        call_site: current_pc.wrapping_sub(1),
+        target: jump_target,
+        return_addr: current_pc,
+        sp: cpu.registers.sp,
+        interrupt: false,
+    });
 
     // Jump PC to the target address
-    cpu.registers.pc = u16::from(jump_target);
+    cpu.registers.pc = jump_target;
 
     16
 }
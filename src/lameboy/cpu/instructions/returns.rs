@@ -1,6 +1,7 @@
 use crate::lameboy::cpu::instructions::opcode_flag_test;
 use crate::lameboy::cpu::instructions::stack::pop_stack_d16;
 use crate::lameboy::cpu::Cpu;
+use crate::lameboy::mmu::bus::Bus;
 
 /// Return to an address that was pushed to the stack.
 ///
@@ -11,9 +12,14 @@ use crate::lameboy::cpu::Cpu;
 /// ```asm
 /// RET ; PC <<- STACK;
 /// ```
-pub fn ret(cpu: &mut Cpu) -> u8 {
+pub fn ret<B: Bus>(cpu: &mut Cpu<B>) -> u8 {
+    let sp = cpu.registers.sp;
+    cpu.last_return = Some(sp);
+
     // Read 16-bit jump target address
     let jump_target: u16 = pop_stack_d16(cpu);
+    cpu.call_stack
+        .on_return(cpu.registers.pc.wrapping_sub(1), sp, jump_target);
 
     // Jump PC to the target address
     cpu.registers.pc = jump_target;
@@ -30,7 +36,7 @@ pub fn ret(cpu: &mut Cpu) -> u8 {
 /// ```asm
 /// RET ; PC <<- STACK;
 /// ```
-pub fn ret_conditional(cpu: &mut Cpu, opcode: u8) -> u8 {
+pub fn ret_conditional<B: Bus>(cpu: &mut Cpu<B>, opcode: u8) -> u8 {
     if opcode_flag_test(opcode, cpu.registers.f) {
         ret(cpu);
 
@@ -50,7 +56,7 @@ pub fn ret_conditional(cpu: &mut Cpu, opcode: u8) -> u8 {
 /// ```asm
 /// RETI ; PC <<- STACK; ime == true
 /// ```
-pub fn ret_interrupt(cpu: &mut Cpu) -> u8 {
+pub fn ret_interrupt<B: Bus>(cpu: &mut Cpu<B>) -> u8 {
     cpu.ime = true;
 
     ret(cpu)
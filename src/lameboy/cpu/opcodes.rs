@@ -0,0 +1,933 @@
+use crate::lameboy::cpu::registers::Reg16::*;
+use crate::lameboy::cpu::registers::Reg8::*;
+use crate::lameboy::cpu::registers::{Reg16, Reg8};
+
+use self::Op::*;
+use self::Operand::*;
+
+/// Kinds of immediate operand which can follow an opcode in memory.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Operand {
+    /// 8-bit immediate data (d8)
+    Data8,
+    /// 16-bit immediate data (d16)
+    Data16,
+    /// 8-bit offset into high memory from 0xFF00 (a8)
+    Address8,
+    /// 16-bit absolute address (a16)
+    Address16,
+    /// 8-bit signed offset (r8)
+    Signed8,
+}
+
+impl Operand {
+    /// Number of bytes the operand takes up after the opcode.
+    pub const fn size(&self) -> u8 {
+        match self {
+            Data8 | Address8 | Signed8 => 1,
+            Data16 | Address16 => 2,
+        }
+    }
+}
+
+/// The operation the CPU performs for an opcode, each variant maps onto a single instruction
+/// handler along with any arguments that handler needs beyond the opcode itself.
+#[derive(Debug)]
+pub enum Op {
+    // Misc
+    Nop,
+    Stop,
+    Halt,
+    Undefined,
+    PrefixCb,
+    Interrupts(bool),
+    DecimalAdjust,
+    Complement,
+    ComplementCarryFlag,
+    SetCarryFlag,
+    SwapR8(Reg8),
+    SwapIndirectHl,
+
+    // 8-bit loads
+    LoadR8D8(Reg8),
+    LoadR8R8(Reg8, Reg8),
+    LoadIndirectR16D8(Reg16),
+    LoadR8IndirectR16(Reg8, Reg16),
+    LoadIndirectR16R8(Reg16, Reg8),
+    LoadRegAA16,
+    LoadA16RegA,
+    LoadRegAHighMemRegC,
+    LoadHighMemRegCRegA,
+    LoadR8IndirectR16Decrement(Reg8, Reg16),
+    LoadIndirectR16DecrementR8(Reg16, Reg8),
+    LoadR8IndirectR16Increment(Reg8, Reg16),
+    LoadIndirectR16IncrementR8(Reg16, Reg8),
+    LoadRegAHighMemD8,
+    LoadHighMemD8RegA,
+
+    // 16-bit loads
+    LoadR16D16(Reg16),
+    LoadR16R16(Reg16, Reg16),
+    LoadRegHlRegSpD8,
+    LoadIndirectA16R16(Reg16),
+    PushR16(Reg16),
+    PopR16(Reg16),
+
+    // 8-bit ALU
+    AddR8(Reg8),
+    AddIndirectR16(Reg16),
+    AddD8,
+    AdcR8(Reg8),
+    AdcIndirectR16(Reg16),
+    AdcD8,
+    SubR8(Reg8),
+    SubIndirectR16(Reg16),
+    SubD8,
+    SbcR8(Reg8),
+    SbcIndirectR16(Reg16),
+    SbcD8,
+    AndR8(Reg8),
+    AndIndirectR16(Reg16),
+    AndD8,
+    XorR8(Reg8),
+    XorIndirectR16(Reg16),
+    XorD8,
+    OrR8(Reg8),
+    OrIndirectR16(Reg16),
+    OrD8,
+    CpR8(Reg8),
+    CpIndirectR16(Reg16),
+    CpD8,
+    IncR8(Reg8),
+    IncIndirectR16(Reg16),
+    DecR8(Reg8),
+    DecIndirectR16(Reg16),
+
+    // 16-bit ALU
+    AddHlR16(Reg16),
+    AddSpD8,
+    IncR16(Reg16),
+    DecR16(Reg16),
+
+    // Jumps, calls, restarts & returns
+    JumpD16,
+    JumpConditionalD16,
+    JumpR16(Reg16),
+    JumpRelativeD8,
+    JumpRelativeConditionalD8,
+    CallD16,
+    CallConditionalD16,
+    Restart,
+    Ret,
+    RetConditional,
+    RetInterrupt,
+
+    // Rotates, shifts & bit opcodes
+    RotateLeftR8(Reg8, bool, bool),
+    RotateLeftIndirectHl(bool, bool),
+    RotateRightR8(Reg8, bool, bool),
+    RotateRightIndirectHl(bool, bool),
+    ShiftLeftR8(Reg8),
+    ShiftLeftIndirectHl,
+    ShiftRightR8(Reg8, bool),
+    ShiftRightIndirectHl(bool),
+    BitTest,
+    BitAssign(bool),
+}
+
+/// A single entry in the opcode table, describing everything needed to decode, execute, and
+/// disassemble an opcode.
+///
+/// Cycle counts are in clock cycles (T-states). For conditional jumps, calls, and returns
+/// `cycles` is the duration when the branch is taken and `cycles_not_taken` when it isn't, for
+/// every other opcode they're the same value. CB prefixed opcodes include the 4 cycles spent on
+/// the prefix byte.
+#[derive(Debug)]
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    pub operand: Option<Operand>,
+    pub length: u8,
+    pub cycles: u8,
+    pub cycles_not_taken: u8,
+    pub operation: Op,
+}
+
+impl Opcode {
+    const fn new(mnemonic: &'static str, operand: Option<Operand>, cycles: u8, op: Op) -> Opcode {
+        Opcode::conditional(mnemonic, operand, cycles, cycles, op)
+    }
+
+    const fn conditional(
+        mnemonic: &'static str,
+        operand: Option<Operand>,
+        cycles: u8,
+        cycles_not_taken: u8,
+        op: Op,
+    ) -> Opcode {
+        let length = match &operand {
+            None => 1,
+            Some(operand) => 1 + operand.size(),
+        };
+
+        Opcode {
+            mnemonic,
+            operand,
+            length,
+            cycles,
+            cycles_not_taken,
+            operation: op,
+        }
+    }
+
+    const fn prefixed(mnemonic: &'static str, cycles: u8, op: Op) -> Opcode {
+        Opcode {
+            mnemonic,
+            operand: None,
+            length: 2,
+            cycles,
+            cycles_not_taken: cycles,
+            operation: op,
+        }
+    }
+
+    /// Is this a conditional opcode whose duration depends on whether it branches.
+    pub fn is_conditional(&self) -> bool {
+        self.cycles != self.cycles_not_taken
+    }
+}
+
+/// Look up the table entry for an opcode, following the CB prefix into the second table.
+pub fn lookup(opcode: u8, next_byte: u8) -> &'static Opcode {
+    if opcode == 0xCB {
+        &CB_OPCODES[next_byte as usize]
+    } else {
+        &OPCODES[opcode as usize]
+    }
+}
+
+pub static OPCODES: [Opcode; 256] = [
+    // 0x00
+    Opcode::new("nop", None, 4, Nop),
+    Opcode::new("ld bc, d16", Some(Data16), 12, LoadR16D16(BC)),
+    Opcode::new("ld [bc], a", None, 8, LoadIndirectR16R8(BC, A)),
+    Opcode::new("inc bc", None, 8, IncR16(BC)),
+    Opcode::new("inc b", None, 4, IncR8(B)),
+    Opcode::new("dec b", None, 4, DecR8(B)),
+    Opcode::new("ld b, d8", Some(Data8), 8, LoadR8D8(B)),
+    Opcode::new("rlca", None, 4, RotateLeftR8(A, false, true)),
+    Opcode::new("ld [a16], sp", Some(Address16), 20, LoadIndirectA16R16(SP)),
+    Opcode::new("add hl, bc", None, 8, AddHlR16(BC)),
+    Opcode::new("ld a, [bc]", None, 8, LoadR8IndirectR16(A, BC)),
+    Opcode::new("dec bc", None, 8, DecR16(BC)),
+    Opcode::new("inc c", None, 4, IncR8(C)),
+    Opcode::new("dec c", None, 4, DecR8(C)),
+    Opcode::new("ld c, d8", Some(Data8), 8, LoadR8D8(C)),
+    Opcode::new("rrca", None, 4, RotateRightR8(A, false, true)),
+    // 0x10
+    Opcode::new("stop", Some(Data8), 4, Stop),
+    Opcode::new("ld de, d16", Some(Data16), 12, LoadR16D16(DE)),
+    Opcode::new("ld [de], a", None, 8, LoadIndirectR16R8(DE, A)),
+    Opcode::new("inc de", None, 8, IncR16(DE)),
+    Opcode::new("inc d", None, 4, IncR8(D)),
+    Opcode::new("dec d", None, 4, DecR8(D)),
+    Opcode::new("ld d, d8", Some(Data8), 8, LoadR8D8(D)),
+    Opcode::new("rla", None, 4, RotateLeftR8(A, true, true)),
+    Opcode::new("jr pc+r8", Some(Signed8), 12, JumpRelativeD8),
+    Opcode::new("add hl, de", None, 8, AddHlR16(DE)),
+    Opcode::new("ld a, [de]", None, 8, LoadR8IndirectR16(A, DE)),
+    Opcode::new("dec de", None, 8, DecR16(DE)),
+    Opcode::new("inc e", None, 4, IncR8(E)),
+    Opcode::new("dec e", None, 4, DecR8(E)),
+    Opcode::new("ld e, d8", Some(Data8), 8, LoadR8D8(E)),
+    Opcode::new("rra", None, 4, RotateRightR8(A, true, true)),
+    // 0x20
+    Opcode::conditional(
+        "jr nz, pc+r8",
+        Some(Signed8),
+        12,
+        8,
+        JumpRelativeConditionalD8,
+    ),
+    Opcode::new("ld hl, d16", Some(Data16), 12, LoadR16D16(HL)),
+    Opcode::new("ld [hl+], a", None, 8, LoadIndirectR16IncrementR8(HL, A)),
+    Opcode::new("inc hl", None, 8, IncR16(HL)),
+    Opcode::new("inc h", None, 4, IncR8(H)),
+    Opcode::new("dec h", None, 4, DecR8(H)),
+    Opcode::new("ld h, d8", Some(Data8), 8, LoadR8D8(H)),
+    Opcode::new("daa", None, 4, DecimalAdjust),
+    Opcode::conditional(
+        "jr z, pc+r8",
+        Some(Signed8),
+        12,
+        8,
+        JumpRelativeConditionalD8,
+    ),
+    Opcode::new("add hl, hl", None, 8, AddHlR16(HL)),
+    Opcode::new("ld a, [hl+]", None, 8, LoadR8IndirectR16Increment(A, HL)),
+    Opcode::new("dec hl", None, 8, DecR16(HL)),
+    Opcode::new("inc l", None, 4, IncR8(L)),
+    Opcode::new("dec l", None, 4, DecR8(L)),
+    Opcode::new("ld l, d8", Some(Data8), 8, LoadR8D8(L)),
+    Opcode::new("cpl", None, 4, Complement),
+    // 0x30
+    Opcode::conditional(
+        "jr nc, pc+r8",
+        Some(Signed8),
+        12,
+        8,
+        JumpRelativeConditionalD8,
+    ),
+    Opcode::new("ld sp, d16", Some(Data16), 12, LoadR16D16(SP)),
+    Opcode::new("ld [hl-], a", None, 8, LoadIndirectR16DecrementR8(HL, A)),
+    Opcode::new("inc sp", None, 8, IncR16(SP)),
+    Opcode::new("inc [hl]", None, 12, IncIndirectR16(HL)),
+    Opcode::new("dec [hl]", None, 12, DecIndirectR16(HL)),
+    Opcode::new("ld [hl], d8", Some(Data8), 12, LoadIndirectR16D8(HL)),
+    Opcode::new("scf", None, 4, SetCarryFlag),
+    Opcode::conditional(
+        "jr c, pc+r8",
+        Some(Signed8),
+        12,
+        8,
+        JumpRelativeConditionalD8,
+    ),
+    Opcode::new("add hl, sp", None, 8, AddHlR16(SP)),
+    Opcode::new("ld a, [hl-]", None, 8, LoadR8IndirectR16Decrement(A, HL)),
+    Opcode::new("dec sp", None, 8, DecR16(SP)),
+    Opcode::new("inc a", None, 4, IncR8(A)),
+    Opcode::new("dec a", None, 4, DecR8(A)),
+    Opcode::new("ld a, d8", Some(Data8), 8, LoadR8D8(A)),
+    Opcode::new("ccf", None, 4, ComplementCarryFlag),
+    // 0x40
+    Opcode::new("ld b, b", None, 4, LoadR8R8(B, B)),
+    Opcode::new("ld b, c", None, 4, LoadR8R8(B, C)),
+    Opcode::new("ld b, d", None, 4, LoadR8R8(B, D)),
+    Opcode::new("ld b, e", None, 4, LoadR8R8(B, E)),
+    Opcode::new("ld b, h", None, 4, LoadR8R8(B, H)),
+    Opcode::new("ld b, l", None, 4, LoadR8R8(B, L)),
+    Opcode::new("ld b, [hl]", None, 8, LoadR8IndirectR16(B, HL)),
+    Opcode::new("ld b, a", None, 4, LoadR8R8(B, A)),
+    Opcode::new("ld c, b", None, 4, LoadR8R8(C, B)),
+    Opcode::new("ld c, c", None, 4, LoadR8R8(C, C)),
+    Opcode::new("ld c, d", None, 4, LoadR8R8(C, D)),
+    Opcode::new("ld c, e", None, 4, LoadR8R8(C, E)),
+    Opcode::new("ld c, h", None, 4, LoadR8R8(C, H)),
+    Opcode::new("ld c, l", None, 4, LoadR8R8(C, L)),
+    Opcode::new("ld c, [hl]", None, 8, LoadR8IndirectR16(C, HL)),
+    Opcode::new("ld c, a", None, 4, LoadR8R8(C, A)),
+    // 0x50
+    Opcode::new("ld d, b", None, 4, LoadR8R8(D, B)),
+    Opcode::new("ld d, c", None, 4, LoadR8R8(D, C)),
+    Opcode::new("ld d, d", None, 4, LoadR8R8(D, D)),
+    Opcode::new("ld d, e", None, 4, LoadR8R8(D, E)),
+    Opcode::new("ld d, h", None, 4, LoadR8R8(D, H)),
+    Opcode::new("ld d, l", None, 4, LoadR8R8(D, L)),
+    Opcode::new("ld d, [hl]", None, 8, LoadR8IndirectR16(D, HL)),
+    Opcode::new("ld d, a", None, 4, LoadR8R8(D, A)),
+    Opcode::new("ld e, b", None, 4, LoadR8R8(E, B)),
+    Opcode::new("ld e, c", None, 4, LoadR8R8(E, C)),
+    Opcode::new("ld e, d", None, 4, LoadR8R8(E, D)),
+    Opcode::new("ld e, e", None, 4, LoadR8R8(E, E)),
+    Opcode::new("ld e, h", None, 4, LoadR8R8(E, H)),
+    Opcode::new("ld e, l", None, 4, LoadR8R8(E, L)),
+    Opcode::new("ld e, [hl]", None, 8, LoadR8IndirectR16(E, HL)),
+    Opcode::new("ld e, a", None, 4, LoadR8R8(E, A)),
+    // 0x60
+    Opcode::new("ld h, b", None, 4, LoadR8R8(H, B)),
+    Opcode::new("ld h, c", None, 4, LoadR8R8(H, C)),
+    Opcode::new("ld h, d", None, 4, LoadR8R8(H, D)),
+    Opcode::new("ld h, e", None, 4, LoadR8R8(H, E)),
+    Opcode::new("ld h, h", None, 4, LoadR8R8(H, H)),
+    Opcode::new("ld h, l", None, 4, LoadR8R8(H, L)),
+    Opcode::new("ld h, [hl]", None, 8, LoadR8IndirectR16(H, HL)),
+    Opcode::new("ld h, a", None, 4, LoadR8R8(H, A)),
+    Opcode::new("ld l, b", None, 4, LoadR8R8(L, B)),
+    Opcode::new("ld l, c", None, 4, LoadR8R8(L, C)),
+    Opcode::new("ld l, d", None, 4, LoadR8R8(L, D)),
+    Opcode::new("ld l, e", None, 4, LoadR8R8(L, E)),
+    Opcode::new("ld l, h", None, 4, LoadR8R8(L, H)),
+    Opcode::new("ld l, l", None, 4, LoadR8R8(L, L)),
+    Opcode::new("ld l, [hl]", None, 8, LoadR8IndirectR16(L, HL)),
+    Opcode::new("ld l, a", None, 4, LoadR8R8(L, A)),
+    // 0x70
+    Opcode::new("ld [hl], b", None, 8, LoadIndirectR16R8(HL, B)),
+    Opcode::new("ld [hl], c", None, 8, LoadIndirectR16R8(HL, C)),
+    Opcode::new("ld [hl], d", None, 8, LoadIndirectR16R8(HL, D)),
+    Opcode::new("ld [hl], e", None, 8, LoadIndirectR16R8(HL, E)),
+    Opcode::new("ld [hl], h", None, 8, LoadIndirectR16R8(HL, H)),
+    Opcode::new("ld [hl], l", None, 8, LoadIndirectR16R8(HL, L)),
+    Opcode::new("halt", None, 4, Halt),
+    Opcode::new("ld [hl], a", None, 8, LoadIndirectR16R8(HL, A)),
+    Opcode::new("ld a, b", None, 4, LoadR8R8(A, B)),
+    Opcode::new("ld a, c", None, 4, LoadR8R8(A, C)),
+    Opcode::new("ld a, d", None, 4, LoadR8R8(A, D)),
+    Opcode::new("ld a, e", None, 4, LoadR8R8(A, E)),
+    Opcode::new("ld a, h", None, 4, LoadR8R8(A, H)),
+    Opcode::new("ld a, l", None, 4, LoadR8R8(A, L)),
+    Opcode::new("ld a, [hl]", None, 8, LoadR8IndirectR16(A, HL)),
+    Opcode::new("ld a, a", None, 4, LoadR8R8(A, A)),
+    // 0x80
+    Opcode::new("add b", None, 4, AddR8(B)),
+    Opcode::new("add c", None, 4, AddR8(C)),
+    Opcode::new("add d", None, 4, AddR8(D)),
+    Opcode::new("add e", None, 4, AddR8(E)),
+    Opcode::new("add h", None, 4, AddR8(H)),
+    Opcode::new("add l", None, 4, AddR8(L)),
+    Opcode::new("add [hl]", None, 8, AddIndirectR16(HL)),
+    Opcode::new("add a", None, 4, AddR8(A)),
+    Opcode::new("adc b", None, 4, AdcR8(B)),
+    Opcode::new("adc c", None, 4, AdcR8(C)),
+    Opcode::new("adc d", None, 4, AdcR8(D)),
+    Opcode::new("adc e", None, 4, AdcR8(E)),
+    Opcode::new("adc h", None, 4, AdcR8(H)),
+    Opcode::new("adc l", None, 4, AdcR8(L)),
+    Opcode::new("adc [hl]", None, 8, AdcIndirectR16(HL)),
+    Opcode::new("adc a", None, 4, AdcR8(A)),
+    // 0x90
+    Opcode::new("sub b", None, 4, SubR8(B)),
+    Opcode::new("sub c", None, 4, SubR8(C)),
+    Opcode::new("sub d", None, 4, SubR8(D)),
+    Opcode::new("sub e", None, 4, SubR8(E)),
+    Opcode::new("sub h", None, 4, SubR8(H)),
+    Opcode::new("sub l", None, 4, SubR8(L)),
+    Opcode::new("sub [hl]", None, 8, SubIndirectR16(HL)),
+    Opcode::new("sub a", None, 4, SubR8(A)),
+    Opcode::new("sbc b", None, 4, SbcR8(B)),
+    Opcode::new("sbc c", None, 4, SbcR8(C)),
+    Opcode::new("sbc d", None, 4, SbcR8(D)),
+    Opcode::new("sbc e", None, 4, SbcR8(E)),
+    Opcode::new("sbc h", None, 4, SbcR8(H)),
+    Opcode::new("sbc l", None, 4, SbcR8(L)),
+    Opcode::new("sbc [hl]", None, 8, SbcIndirectR16(HL)),
+    Opcode::new("sbc a", None, 4, SbcR8(A)),
+    // 0xA0
+    Opcode::new("and b", None, 4, AndR8(B)),
+    Opcode::new("and c", None, 4, AndR8(C)),
+    Opcode::new("and d", None, 4, AndR8(D)),
+    Opcode::new("and e", None, 4, AndR8(E)),
+    Opcode::new("and h", None, 4, AndR8(H)),
+    Opcode::new("and l", None, 4, AndR8(L)),
+    Opcode::new("and [hl]", None, 8, AndIndirectR16(HL)),
+    Opcode::new("and a", None, 4, AndR8(A)),
+    Opcode::new("xor b", None, 4, XorR8(B)),
+    Opcode::new("xor c", None, 4, XorR8(C)),
+    Opcode::new("xor d", None, 4, XorR8(D)),
+    Opcode::new("xor e", None, 4, XorR8(E)),
+    Opcode::new("xor h", None, 4, XorR8(H)),
+    Opcode::new("xor l", None, 4, XorR8(L)),
+    Opcode::new("xor [hl]", None, 8, XorIndirectR16(HL)),
+    Opcode::new("xor a", None, 4, XorR8(A)),
+    // 0xB0
+    Opcode::new("or b", None, 4, OrR8(B)),
+    Opcode::new("or c", None, 4, OrR8(C)),
+    Opcode::new("or d", None, 4, OrR8(D)),
+    Opcode::new("or e", None, 4, OrR8(E)),
+    Opcode::new("or h", None, 4, OrR8(H)),
+    Opcode::new("or l", None, 4, OrR8(L)),
+    Opcode::new("or [hl]", None, 8, OrIndirectR16(HL)),
+    Opcode::new("or a", None, 4, OrR8(A)),
+    Opcode::new("cp b", None, 4, CpR8(B)),
+    Opcode::new("cp c", None, 4, CpR8(C)),
+    Opcode::new("cp d", None, 4, CpR8(D)),
+    Opcode::new("cp e", None, 4, CpR8(E)),
+    Opcode::new("cp h", None, 4, CpR8(H)),
+    Opcode::new("cp l", None, 4, CpR8(L)),
+    Opcode::new("cp [hl]", None, 8, CpIndirectR16(HL)),
+    Opcode::new("cp a", None, 4, CpR8(A)),
+    // 0xC0
+    Opcode::conditional("ret nz", None, 20, 8, RetConditional),
+    Opcode::new("pop bc", None, 12, PopR16(BC)),
+    Opcode::conditional("jp nz, a16", Some(Address16), 16, 12, JumpConditionalD16),
+    Opcode::new("jp a16", Some(Address16), 16, JumpD16),
+    Opcode::conditional("call nz, a16", Some(Address16), 24, 12, CallConditionalD16),
+    Opcode::new("push bc", None, 16, PushR16(BC)),
+    Opcode::new("add d8", Some(Data8), 8, AddD8),
+    Opcode::new("rst $00", None, 16, Restart),
+    Opcode::conditional("ret z", None, 20, 8, RetConditional),
+    Opcode::new("ret", None, 16, Ret),
+    Opcode::conditional("jp z, a16", Some(Address16), 16, 12, JumpConditionalD16),
+    Opcode::new("prefix cb", None, 4, PrefixCb),
+    Opcode::conditional("call z, a16", Some(Address16), 24, 12, CallConditionalD16),
+    Opcode::new("call a16", Some(Address16), 24, CallD16),
+    Opcode::new("adc d8", Some(Data8), 8, AdcD8),
+    Opcode::new("rst $08", None, 16, Restart),
+    // 0xD0
+    Opcode::conditional("ret nc", None, 20, 8, RetConditional),
+    Opcode::new("pop de", None, 12, PopR16(DE)),
+    Opcode::conditional("jp nc, a16", Some(Address16), 16, 12, JumpConditionalD16),
+    Opcode::new("db $d3", None, 0, Undefined),
+    Opcode::conditional("call nc, a16", Some(Address16), 24, 12, CallConditionalD16),
+    Opcode::new("push de", None, 16, PushR16(DE)),
+    Opcode::new("sub d8", Some(Data8), 8, SubD8),
+    Opcode::new("rst $10", None, 16, Restart),
+    Opcode::conditional("ret c", None, 20, 8, RetConditional),
+    Opcode::new("reti", None, 16, RetInterrupt),
+    Opcode::conditional("jp c, a16", Some(Address16), 16, 12, JumpConditionalD16),
+    Opcode::new("db $db", None, 0, Undefined),
+    Opcode::conditional("call c, a16", Some(Address16), 24, 12, CallConditionalD16),
+    Opcode::new("db $dd", None, 0, Undefined),
+    Opcode::new("sbc d8", Some(Data8), 8, SbcD8),
+    Opcode::new("rst $18", None, 16, Restart),
+    // 0xE0
+    Opcode::new("ldh [a8], a", Some(Address8), 12, LoadHighMemD8RegA),
+    Opcode::new("pop hl", None, 12, PopR16(HL)),
+    Opcode::new("ld [c], a", None, 8, LoadHighMemRegCRegA),
+    Opcode::new("db $e3", None, 0, Undefined),
+    Opcode::new("db $e4", None, 0, Undefined),
+    Opcode::new("push hl", None, 16, PushR16(HL)),
+    Opcode::new("and d8", Some(Data8), 8, AndD8),
+    Opcode::new("rst $20", None, 16, Restart),
+    Opcode::new("add sp, r8", Some(Signed8), 16, AddSpD8),
+    Opcode::new("jp hl", None, 4, JumpR16(HL)),
+    Opcode::new("ld [a16], a", Some(Address16), 16, LoadA16RegA),
+    Opcode::new("db $eb", None, 0, Undefined),
+    Opcode::new("db $ec", None, 0, Undefined),
+    Opcode::new("db $ed", None, 0, Undefined),
+    Opcode::new("xor d8", Some(Data8), 8, XorD8),
+    Opcode::new("rst $28", None, 16, Restart),
+    // 0xF0
+    Opcode::new("ldh a, [a8]", Some(Address8), 12, LoadRegAHighMemD8),
+    Opcode::new("pop af", None, 12, PopR16(AF)),
+    Opcode::new("ld a, [c]", None, 8, LoadRegAHighMemRegC),
+    Opcode::new("di", None, 4, Interrupts(false)),
+    Opcode::new("db $f4", None, 0, Undefined),
+    Opcode::new("push af", None, 16, PushR16(AF)),
+    Opcode::new("or d8", Some(Data8), 8, OrD8),
+    Opcode::new("rst $30", None, 16, Restart),
+    Opcode::new("ld hl, sp+r8", Some(Signed8), 12, LoadRegHlRegSpD8),
+    Opcode::new("ld sp, hl", None, 8, LoadR16R16(SP, HL)),
+    Opcode::new("ld a, [a16]", Some(Address16), 16, LoadRegAA16),
+    Opcode::new("ei", None, 4, Interrupts(true)),
+    Opcode::new("db $fc", None, 0, Undefined),
+    Opcode::new("db $fd", None, 0, Undefined),
+    Opcode::new("cp d8", Some(Data8), 8, CpD8),
+    Opcode::new("rst $38", None, 16, Restart),
+];
+
+pub static CB_OPCODES: [Opcode; 256] = [
+    // 0x00
+    Opcode::prefixed("rlc b", 8, RotateLeftR8(B, false, false)),
+    Opcode::prefixed("rlc c", 8, RotateLeftR8(C, false, false)),
+    Opcode::prefixed("rlc d", 8, RotateLeftR8(D, false, false)),
+    Opcode::prefixed("rlc e", 8, RotateLeftR8(E, false, false)),
+    Opcode::prefixed("rlc h", 8, RotateLeftR8(H, false, false)),
+    Opcode::prefixed("rlc l", 8, RotateLeftR8(L, false, false)),
+    Opcode::prefixed("rlc [hl]", 16, RotateLeftIndirectHl(false, false)),
+    Opcode::prefixed("rlc a", 8, RotateLeftR8(A, false, false)),
+    Opcode::prefixed("rrc b", 8, RotateRightR8(B, false, false)),
+    Opcode::prefixed("rrc c", 8, RotateRightR8(C, false, false)),
+    Opcode::prefixed("rrc d", 8, RotateRightR8(D, false, false)),
+    Opcode::prefixed("rrc e", 8, RotateRightR8(E, false, false)),
+    Opcode::prefixed("rrc h", 8, RotateRightR8(H, false, false)),
+    Opcode::prefixed("rrc l", 8, RotateRightR8(L, false, false)),
+    Opcode::prefixed("rrc [hl]", 16, RotateRightIndirectHl(false, false)),
+    Opcode::prefixed("rrc a", 8, RotateRightR8(A, false, false)),
+    // 0x10
+    Opcode::prefixed("rl b", 8, RotateLeftR8(B, true, false)),
+    Opcode::prefixed("rl c", 8, RotateLeftR8(C, true, false)),
+    Opcode::prefixed("rl d", 8, RotateLeftR8(D, true, false)),
+    Opcode::prefixed("rl e", 8, RotateLeftR8(E, true, false)),
+    Opcode::prefixed("rl h", 8, RotateLeftR8(H, true, false)),
+    Opcode::prefixed("rl l", 8, RotateLeftR8(L, true, false)),
+    Opcode::prefixed("rl [hl]", 16, RotateLeftIndirectHl(true, false)),
+    Opcode::prefixed("rl a", 8, RotateLeftR8(A, true, false)),
+    Opcode::prefixed("rr b", 8, RotateRightR8(B, true, false)),
+    Opcode::prefixed("rr c", 8, RotateRightR8(C, true, false)),
+    Opcode::prefixed("rr d", 8, RotateRightR8(D, true, false)),
+    Opcode::prefixed("rr e", 8, RotateRightR8(E, true, false)),
+    Opcode::prefixed("rr h", 8, RotateRightR8(H, true, false)),
+    Opcode::prefixed("rr l", 8, RotateRightR8(L, true, false)),
+    Opcode::prefixed("rr [hl]", 16, RotateRightIndirectHl(true, false)),
+    Opcode::prefixed("rr a", 8, RotateRightR8(A, true, false)),
+    // 0x20
+    Opcode::prefixed("sla b", 8, ShiftLeftR8(B)),
+    Opcode::prefixed("sla c", 8, ShiftLeftR8(C)),
+    Opcode::prefixed("sla d", 8, ShiftLeftR8(D)),
+    Opcode::prefixed("sla e", 8, ShiftLeftR8(E)),
+    Opcode::prefixed("sla h", 8, ShiftLeftR8(H)),
+    Opcode::prefixed("sla l", 8, ShiftLeftR8(L)),
+    Opcode::prefixed("sla [hl]", 16, ShiftLeftIndirectHl),
+    Opcode::prefixed("sla a", 8, ShiftLeftR8(A)),
+    Opcode::prefixed("sra b", 8, ShiftRightR8(B, false)),
+    Opcode::prefixed("sra c", 8, ShiftRightR8(C, false)),
+    Opcode::prefixed("sra d", 8, ShiftRightR8(D, false)),
+    Opcode::prefixed("sra e", 8, ShiftRightR8(E, false)),
+    Opcode::prefixed("sra h", 8, ShiftRightR8(H, false)),
+    Opcode::prefixed("sra l", 8, ShiftRightR8(L, false)),
+    Opcode::prefixed("sra [hl]", 16, ShiftRightIndirectHl(false)),
+    Opcode::prefixed("sra a", 8, ShiftRightR8(A, false)),
+    // 0x30
+    Opcode::prefixed("swap b", 8, SwapR8(B)),
+    Opcode::prefixed("swap c", 8, SwapR8(C)),
+    Opcode::prefixed("swap d", 8, SwapR8(D)),
+    Opcode::prefixed("swap e", 8, SwapR8(E)),
+    Opcode::prefixed("swap h", 8, SwapR8(H)),
+    Opcode::prefixed("swap l", 8, SwapR8(L)),
+    Opcode::prefixed("swap [hl]", 16, SwapIndirectHl),
+    Opcode::prefixed("swap a", 8, SwapR8(A)),
+    Opcode::prefixed("srl b", 8, ShiftRightR8(B, true)),
+    Opcode::prefixed("srl c", 8, ShiftRightR8(C, true)),
+    Opcode::prefixed("srl d", 8, ShiftRightR8(D, true)),
+    Opcode::prefixed("srl e", 8, ShiftRightR8(E, true)),
+    Opcode::prefixed("srl h", 8, ShiftRightR8(H, true)),
+    Opcode::prefixed("srl l", 8, ShiftRightR8(L, true)),
+    Opcode::prefixed("srl [hl]", 16, ShiftRightIndirectHl(true)),
+    Opcode::prefixed("srl a", 8, ShiftRightR8(A, true)),
+    // 0x40
+    Opcode::prefixed("bit 0, b", 8, BitTest),
+    Opcode::prefixed("bit 0, c", 8, BitTest),
+    Opcode::prefixed("bit 0, d", 8, BitTest),
+    Opcode::prefixed("bit 0, e", 8, BitTest),
+    Opcode::prefixed("bit 0, h", 8, BitTest),
+    Opcode::prefixed("bit 0, l", 8, BitTest),
+    Opcode::prefixed("bit 0, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 0, a", 8, BitTest),
+    Opcode::prefixed("bit 1, b", 8, BitTest),
+    Opcode::prefixed("bit 1, c", 8, BitTest),
+    Opcode::prefixed("bit 1, d", 8, BitTest),
+    Opcode::prefixed("bit 1, e", 8, BitTest),
+    Opcode::prefixed("bit 1, h", 8, BitTest),
+    Opcode::prefixed("bit 1, l", 8, BitTest),
+    Opcode::prefixed("bit 1, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 1, a", 8, BitTest),
+    // 0x50
+    Opcode::prefixed("bit 2, b", 8, BitTest),
+    Opcode::prefixed("bit 2, c", 8, BitTest),
+    Opcode::prefixed("bit 2, d", 8, BitTest),
+    Opcode::prefixed("bit 2, e", 8, BitTest),
+    Opcode::prefixed("bit 2, h", 8, BitTest),
+    Opcode::prefixed("bit 2, l", 8, BitTest),
+    Opcode::prefixed("bit 2, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 2, a", 8, BitTest),
+    Opcode::prefixed("bit 3, b", 8, BitTest),
+    Opcode::prefixed("bit 3, c", 8, BitTest),
+    Opcode::prefixed("bit 3, d", 8, BitTest),
+    Opcode::prefixed("bit 3, e", 8, BitTest),
+    Opcode::prefixed("bit 3, h", 8, BitTest),
+    Opcode::prefixed("bit 3, l", 8, BitTest),
+    Opcode::prefixed("bit 3, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 3, a", 8, BitTest),
+    // 0x60
+    Opcode::prefixed("bit 4, b", 8, BitTest),
+    Opcode::prefixed("bit 4, c", 8, BitTest),
+    Opcode::prefixed("bit 4, d", 8, BitTest),
+    Opcode::prefixed("bit 4, e", 8, BitTest),
+    Opcode::prefixed("bit 4, h", 8, BitTest),
+    Opcode::prefixed("bit 4, l", 8, BitTest),
+    Opcode::prefixed("bit 4, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 4, a", 8, BitTest),
+    Opcode::prefixed("bit 5, b", 8, BitTest),
+    Opcode::prefixed("bit 5, c", 8, BitTest),
+    Opcode::prefixed("bit 5, d", 8, BitTest),
+    Opcode::prefixed("bit 5, e", 8, BitTest),
+    Opcode::prefixed("bit 5, h", 8, BitTest),
+    Opcode::prefixed("bit 5, l", 8, BitTest),
+    Opcode::prefixed("bit 5, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 5, a", 8, BitTest),
+    // 0x70
+    Opcode::prefixed("bit 6, b", 8, BitTest),
+    Opcode::prefixed("bit 6, c", 8, BitTest),
+    Opcode::prefixed("bit 6, d", 8, BitTest),
+    Opcode::prefixed("bit 6, e", 8, BitTest),
+    Opcode::prefixed("bit 6, h", 8, BitTest),
+    Opcode::prefixed("bit 6, l", 8, BitTest),
+    Opcode::prefixed("bit 6, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 6, a", 8, BitTest),
+    Opcode::prefixed("bit 7, b", 8, BitTest),
+    Opcode::prefixed("bit 7, c", 8, BitTest),
+    Opcode::prefixed("bit 7, d", 8, BitTest),
+    Opcode::prefixed("bit 7, e", 8, BitTest),
+    Opcode::prefixed("bit 7, h", 8, BitTest),
+    Opcode::prefixed("bit 7, l", 8, BitTest),
+    Opcode::prefixed("bit 7, [hl]", 12, BitTest),
+    Opcode::prefixed("bit 7, a", 8, BitTest),
+    // 0x80
+    Opcode::prefixed("res 0, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 0, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 0, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 0, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 0, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 0, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 0, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 0, a", 8, BitAssign(false)),
+    Opcode::prefixed("res 1, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 1, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 1, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 1, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 1, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 1, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 1, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 1, a", 8, BitAssign(false)),
+    // 0x90
+    Opcode::prefixed("res 2, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 2, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 2, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 2, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 2, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 2, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 2, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 2, a", 8, BitAssign(false)),
+    Opcode::prefixed("res 3, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 3, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 3, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 3, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 3, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 3, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 3, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 3, a", 8, BitAssign(false)),
+    // 0xA0
+    Opcode::prefixed("res 4, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 4, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 4, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 4, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 4, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 4, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 4, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 4, a", 8, BitAssign(false)),
+    Opcode::prefixed("res 5, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 5, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 5, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 5, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 5, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 5, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 5, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 5, a", 8, BitAssign(false)),
+    // 0xB0
+    Opcode::prefixed("res 6, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 6, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 6, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 6, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 6, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 6, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 6, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 6, a", 8, BitAssign(false)),
+    Opcode::prefixed("res 7, b", 8, BitAssign(false)),
+    Opcode::prefixed("res 7, c", 8, BitAssign(false)),
+    Opcode::prefixed("res 7, d", 8, BitAssign(false)),
+    Opcode::prefixed("res 7, e", 8, BitAssign(false)),
+    Opcode::prefixed("res 7, h", 8, BitAssign(false)),
+    Opcode::prefixed("res 7, l", 8, BitAssign(false)),
+    Opcode::prefixed("res 7, [hl]", 16, BitAssign(false)),
+    Opcode::prefixed("res 7, a", 8, BitAssign(false)),
+    // 0xC0
+    Opcode::prefixed("set 0, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 0, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 0, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 0, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 0, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 0, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 0, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 0, a", 8, BitAssign(true)),
+    Opcode::prefixed("set 1, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 1, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 1, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 1, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 1, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 1, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 1, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 1, a", 8, BitAssign(true)),
+    // 0xD0
+    Opcode::prefixed("set 2, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 2, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 2, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 2, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 2, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 2, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 2, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 2, a", 8, BitAssign(true)),
+    Opcode::prefixed("set 3, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 3, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 3, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 3, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 3, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 3, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 3, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 3, a", 8, BitAssign(true)),
+    // 0xE0
+    Opcode::prefixed("set 4, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 4, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 4, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 4, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 4, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 4, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 4, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 4, a", 8, BitAssign(true)),
+    Opcode::prefixed("set 5, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 5, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 5, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 5, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 5, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 5, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 5, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 5, a", 8, BitAssign(true)),
+    // 0xF0
+    Opcode::prefixed("set 6, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 6, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 6, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 6, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 6, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 6, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 6, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 6, a", 8, BitAssign(true)),
+    Opcode::prefixed("set 7, b", 8, BitAssign(true)),
+    Opcode::prefixed("set 7, c", 8, BitAssign(true)),
+    Opcode::prefixed("set 7, d", 8, BitAssign(true)),
+    Opcode::prefixed("set 7, e", 8, BitAssign(true)),
+    Opcode::prefixed("set 7, h", 8, BitAssign(true)),
+    Opcode::prefixed("set 7, l", 8, BitAssign(true)),
+    Opcode::prefixed("set 7, [hl]", 16, BitAssign(true)),
+    Opcode::prefixed("set 7, a", 8, BitAssign(true)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{Op, Opcode, CB_OPCODES, OPCODES};
+    use crate::lameboy::cpu::registers::Flags;
+    use crate::lameboy::Lameboy;
+
+    const CODE_ADDR: u16 = 0xC000;
+
+    /// Base opcode durations in machine cycles from Pan Docs, with conditional branches taken and
+    /// undefined opcodes as 0.
+    #[rustfmt::skip]
+    const REFERENCE_CYCLES: [u8; 256] = [
+        1, 3, 2, 2, 1, 1, 2, 1, 5, 2, 2, 2, 1, 1, 2, 1, // 0x00
+        1, 3, 2, 2, 1, 1, 2, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 0x10
+        3, 3, 2, 2, 1, 1, 2, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 0x20
+        3, 3, 2, 2, 3, 3, 3, 1, 3, 2, 2, 2, 1, 1, 2, 1, // 0x30
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 0x40
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 0x50
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 0x60
+        2, 2, 2, 2, 2, 2, 1, 2, 1, 1, 1, 1, 1, 1, 2, 1, // 0x70
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 0x80
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 0x90
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 0xA0
+        1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 2, 1, // 0xB0
+        5, 3, 4, 4, 6, 4, 2, 4, 5, 4, 4, 1, 6, 6, 2, 4, // 0xC0
+        5, 3, 4, 0, 6, 4, 2, 4, 5, 4, 4, 0, 6, 0, 2, 4, // 0xD0
+        3, 3, 2, 0, 0, 4, 2, 4, 4, 1, 4, 0, 0, 0, 2, 4, // 0xE0
+        3, 3, 2, 1, 0, 4, 2, 4, 3, 2, 4, 1, 0, 0, 2, 4, // 0xF0
+    ];
+
+    #[test]
+    fn opcode_timings_match_reference() {
+        for (op, opcode) in OPCODES.iter().enumerate() {
+            let cycles_not_taken = match op {
+                0x20 | 0x28 | 0x30 | 0x38 | 0xC0 | 0xC8 | 0xD0 | 0xD8 => 8,
+                0xC2 | 0xCA | 0xD2 | 0xDA | 0xC4 | 0xCC | 0xD4 | 0xDC => 12,
+                _ => REFERENCE_CYCLES[op] * 4,
+            };
+
+            assert_eq!(
+                REFERENCE_CYCLES[op] * 4,
+                opcode.cycles,
+                "{} has the wrong duration",
+                opcode.mnemonic
+            );
+            assert_eq!(
+                cycles_not_taken, opcode.cycles_not_taken,
+                "{} has the wrong duration when not taken",
+                opcode.mnemonic
+            );
+        }
+    }
+
+    #[test]
+    fn cb_opcode_timings_match_reference() {
+        for (op, opcode) in CB_OPCODES.iter().enumerate() {
+            // Register operands take 2 machine cycles, [hl] takes 4 or only 3 for BIT
+            let cycles = match (op & 0x07, op) {
+                (6, 0x40..=0x7F) => 12,
+                (6, _) => 16,
+                _ => 8,
+            };
+
+            assert_eq!(cycles, opcode.cycles, "{}", opcode.mnemonic);
+            assert!(!opcode.is_conditional(), "{}", opcode.mnemonic);
+        }
+    }
+
+    /// Execute a single instruction from work RAM with the given flags and return its duration.
+    fn run_opcode(bytes: &[u8], flags: Flags) -> u8 {
+        let mut cpu = Lameboy::with_program(&[]).cpu;
+        for (offset, byte) in bytes.iter().enumerate() {
+            cpu.mmu.write8(CODE_ADDR + offset as u16, *byte);
+        }
+
+        // Point every register pair used for indirect access into work RAM
+        cpu.registers.b = 0xC2;
+        cpu.registers.d = 0xC2;
+        cpu.registers.h = 0xC1;
+        cpu.registers.sp = 0xDFF0;
+        cpu.registers.f = flags;
+        cpu.registers.pc = CODE_ADDR;
+
+        cpu.handle_instruction()
+    }
+
+    fn check_execution_timing(bytes: &[u8], opcode: &Opcode) {
+        // Every condition code is taken with one of these flag sets and not taken with the other
+        let durations = [
+            run_opcode(bytes, Flags::empty()),
+            run_opcode(bytes, Flags::ZERO | Flags::CARRY),
+        ];
+
+        if opcode.is_conditional() {
+            assert!(
+                durations.contains(&opcode.cycles) && durations.contains(&opcode.cycles_not_taken),
+                "{} took {:?} cycles, expected {}/{}",
+                opcode.mnemonic,
+                durations,
+                opcode.cycles,
+                opcode.cycles_not_taken
+            );
+        } else {
+            assert_eq!(
+                [opcode.cycles; 2], durations,
+                "{} took the wrong number of cycles",
+                opcode.mnemonic
+            );
+        }
+    }
+
+    #[test]
+    fn opcodes_take_table_durations() {
+        for (op, opcode) in OPCODES.iter().enumerate() {
+            if matches!(opcode.operation, Op::Undefined | Op::PrefixCb) {
+                continue;
+            }
+            check_execution_timing(&[op as u8], opcode);
+        }
+    }
+
+    #[test]
+    fn cb_opcodes_take_table_durations() {
+        for (op, opcode) in CB_OPCODES.iter().enumerate() {
+            check_execution_timing(&[0xCB, op as u8], opcode);
+        }
+    }
+
+    #[test]
+    fn opcode_lengths_match_operands() {
+        for opcode in OPCODES.iter() {
+            let placeholder_length = ["d16", "a16"]
+                .iter()
+                .any(|p| opcode.mnemonic.contains(p))
+                .then_some(3)
+                .or_else(|| {
+                    ["d8", "a8", "r8"]
+                        .iter()
+                        .any(|p| opcode.mnemonic.contains(p))
+                        .then_some(2)
+                })
+                .unwrap_or(1);
+
+            if !matches!(opcode.operation, Op::Stop | Op::PrefixCb) {
+                assert_eq!(placeholder_length, opcode.length, "{}", opcode.mnemonic);
+            }
+        }
+
+        assert_eq!(2, OPCODES[0x10].length);
+        assert!(CB_OPCODES.iter().all(|opcode| opcode.length == 2));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::lameboy::state::{StateReader, StateWriter, Stateful};
+
 bitflags! {
     pub struct Flags: u8 {
         const ZERO          = 0b_1000_0000;
@@ -34,6 +36,7 @@ pub enum Reg16 {
 }
 
 /// Registers for the CPU core
+#[derive(Debug, Clone, Copy)]
 pub struct Registers {
     pub a: u8,
     pub f: Flags,
@@ -136,3 +139,37 @@ impl Registers {
         }
     }
 }
+
+impl Stateful for Registers {
+    fn save_state(&self, state: &mut StateWriter) {
+        for value in [
+            self.a,
+            self.f.bits(),
+            self.b,
+            self.c,
+            self.d,
+            self.e,
+            self.h,
+            self.l,
+        ] {
+            state.u8(value);
+        }
+        state.u16(self.pc);
+        state.u16(self.sp);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.a = state.u8()?;
+        self.f = Flags::from_bits_truncate(state.u8()?);
+        self.b = state.u8()?;
+        self.c = state.u8()?;
+        self.d = state.u8()?;
+        self.e = state.u8()?;
+        self.h = state.u8()?;
+        self.l = state.u8()?;
+        self.pc = state.u16()?;
+        self.sp = state.u16()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::lameboy::cpu::registers::Flags;
+use crate::lameboy::cpu::Cpu;
+use crate::lameboy::mmu::bus::Bus;
+
+/// A flat 64KB address space with no memory mapped hardware, recording every access so the bus
+/// activity of an instruction can be checked.
+struct FlatMemory {
+    memory: Vec<u8>,
+    accesses: Vec<BusAccess>,
+}
+
+#[derive(Debug, PartialEq)]
+struct BusAccess {
+    addr: u16,
+    data: u8,
+    write: bool,
+}
+
+impl FlatMemory {
+    fn new() -> FlatMemory {
+        FlatMemory {
+            memory: vec![0x00; 0x10000],
+            accesses: Vec::new(),
+        }
+    }
+}
+
+impl Bus for FlatMemory {
+    fn read8(&mut self, addr: u16) -> u8 {
+        let data = self.memory[addr as usize];
+        self.accesses.push(BusAccess {
+            addr,
+            data,
+            write: false,
+        });
+        data
+    }
+
+    fn write8(&mut self, addr: u16, data: u8) {
+        self.memory[addr as usize] = data;
+        self.accesses.push(BusAccess {
+            addr,
+            data,
+            write: true,
+        });
+    }
+}
+
+#[derive(Deserialize)]
+struct TestCase {
+    name: String,
+    initial: CpuState,
+    #[serde(rename = "final")]
+    expected: CpuState,
+    cycles: Vec<Option<BusCycle>>,
+}
+
+/// One M-cycle of expected bus activity, as address, data and a `r-m`/`-wm`/`---` access kind.
+#[derive(Deserialize)]
+struct BusCycle(Option<u16>, Option<u8>, String);
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct CpuState {
+    a: u8,
+    b: u8,
+    c: u8,
+    d: u8,
+    e: u8,
+    f: u8,
+    h: u8,
+    l: u8,
+    pc: u16,
+    sp: u16,
+    ime: Option<u8>,
+    ie: Option<u8>,
+    ram: Vec<(u16, u8)>,
+}
+
+fn setup_cpu(state: &CpuState) -> Cpu<FlatMemory> {
+    let mut cpu = Cpu::new(FlatMemory::new());
+
+    cpu.registers.a = state.a;
+    cpu.registers.f = Flags::from_bits_truncate(state.f);
+    cpu.registers.b = state.b;
+    cpu.registers.c = state.c;
+    cpu.registers.d = state.d;
+    cpu.registers.e = state.e;
+    cpu.registers.h = state.h;
+    cpu.registers.l = state.l;
+    cpu.registers.pc = state.pc;
+    cpu.registers.sp = state.sp;
+    cpu.ime = state.ime.unwrap_or(0) != 0;
+
+    if let Some(ie) = state.ie {
+        cpu.mmu.memory[0xFFFF] = ie;
+    }
+    for &(addr, data) in &state.ram {
+        cpu.mmu.memory[addr as usize] = data;
+    }
+
+    cpu
+}
+
+/// Capture the CPU in the same shape as the test vector's expected state, using the expected RAM
+/// addresses so the two can be compared directly.
+fn capture_state(cpu: &Cpu<FlatMemory>, expected: &CpuState) -> CpuState {
+    CpuState {
+        a: cpu.registers.a,
+        b: cpu.registers.b,
+        c: cpu.registers.c,
+        d: cpu.registers.d,
+        e: cpu.registers.e,
+        f: cpu.registers.f.bits(),
+        h: cpu.registers.h,
+        l: cpu.registers.l,
+        pc: cpu.registers.pc,
+        sp: cpu.registers.sp,
+        ime: expected.ime.map(|_| u8::from(cpu.ime)),
+        ie: expected.ie.map(|_| cpu.mmu.memory[0xFFFF]),
+        ram: expected
+            .ram
+            .iter()
+            .map(|&(addr, _)| (addr, cpu.mmu.memory[addr as usize]))
+            .collect(),
+    }
+}
+
+/// Run a single test case, returning a description of the first mismatch if it fails.
+///
+/// Internal cycles (marked `---`) have no bus activity so only reads and writes are compared,
+/// along with the total duration of the instruction.
+fn run_case(case: &TestCase) -> Result<(), String> {
+    let mut cpu = setup_cpu(&case.initial);
+
+    let duration = panic::catch_unwind(AssertUnwindSafe(|| cpu.handle_instruction()))
+        .map_err(|_| String::from("instruction handler panicked"))?;
+
+    let state = capture_state(&cpu, &case.expected);
+    if state != case.expected {
+        return Err(format!(
+            "state mismatch\n  expected: {:?}\n  actual:   {:?}",
+            case.expected, state
+        ));
+    }
+
+    let expected_duration = case.cycles.len() * 4;
+    if usize::from(duration) != expected_duration {
+        return Err(format!(
+            "took {duration} cycles, expected {expected_duration}"
+        ));
+    }
+
+    let expected_accesses: Vec<BusAccess> = case
+        .cycles
+        .iter()
+        .flatten()
+        .filter_map(
+            |BusCycle(addr, data, kind)| match (addr, data, kind.as_bytes()) {
+                (Some(addr), Some(data), [b'r', ..]) => Some(BusAccess {
+                    addr: *addr,
+                    data: *data,
+                    write: false,
+                }),
+                (Some(addr), Some(data), [_, b'w', ..]) => Some(BusAccess {
+                    addr: *addr,
+                    data: *data,
+                    write: true,
+                }),
+                _ => None,
+            },
+        )
+        .collect();
+    if cpu.mmu.accesses != expected_accesses {
+        return Err(format!(
+            "bus mismatch\n  expected: {:?}\n  actual:   {:?}",
+            expected_accesses, cpu.mmu.accesses
+        ));
+    }
+
+    Ok(())
+}
+
+/// Run every case in a test vector file, returning how many ran or the first failure.
+fn run_vector_file(path: &Path) -> Result<usize, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Unable to read file: {e}"))?;
+    let cases: Vec<TestCase> =
+        serde_json::from_str(&json).map_err(|e| format!("Unable to parse file: {e}"))?;
+
+    for case in &cases {
+        run_case(case).map_err(|e| format!("{}: {e}", case.name))?;
+    }
+
+    Ok(cases.len())
+}
+
+#[test]
+fn flat_memory_vector() {
+    // ld [hl+], a then push bc
+    let json = r#"[
+        {
+            "name": "22 0000",
+            "initial": {"pc": 256, "sp": 65534, "a": 171, "b": 0, "c": 0, "d": 0, "e": 0,
+                        "f": 176, "h": 192, "l": 255, "ime": 0, "ie": 0,
+                        "ram": [[256, 34], [49407, 0]]},
+            "final":   {"pc": 257, "sp": 65534, "a": 171, "b": 0, "c": 0, "d": 0, "e": 0,
+                        "f": 176, "h": 193, "l": 0, "ime": 0, "ie": 0,
+                        "ram": [[256, 34], [49407, 171]]},
+            "cycles": [[256, 34, "r-m"], [49407, 171, "-wm"]]
+        },
+        {
+            "name": "c5 0000",
+            "initial": {"pc": 256, "sp": 57344, "a": 0, "b": 18, "c": 52, "d": 0, "e": 0,
+                        "f": 0, "h": 0, "l": 0, "ime": 0, "ie": 0,
+                        "ram": [[256, 197]]},
+            "final":   {"pc": 257, "sp": 57342, "a": 0, "b": 18, "c": 52, "d": 0, "e": 0,
+                        "f": 0, "h": 0, "l": 0, "ime": 0, "ie": 0,
+                        "ram": [[256, 197], [57343, 18], [57342, 52]]},
+            "cycles": [[256, 197, "r-m"], [57343, null, "---"],
+                       [57343, 18, "-wm"], [57342, 52, "-wm"]]
+        }
+    ]"#;
+
+    let cases: Vec<TestCase> = serde_json::from_str(json).unwrap();
+    for case in &cases {
+        assert_eq!(Ok(()), run_case(case), "{}", case.name);
+    }
+}
+
+/// Run the SM83 single step test vectors from the directory named by `LAMEBOY_SM83_TESTS`
+/// (defaulting to `roms/sm83/`), one JSON file per opcode. Ignored by default, run with
+/// `cargo test -- --ignored sm83_test_vectors`.
+#[test]
+#[ignore]
+fn sm83_test_vectors() {
+    let dir = PathBuf::from(std::env::var("LAMEBOY_SM83_TESTS").unwrap_or("roms/sm83".into()));
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("Unable to read {}: {e}", dir.display()))
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    let mut failures = 0;
+    for path in &files {
+        let name = path.file_name().unwrap().to_string_lossy();
+        match run_vector_file(path) {
+            Ok(count) => println!("{name: <12} | PASS ({count} cases)"),
+            Err(e) => {
+                failures += 1;
+                println!("{name: <12} | FAIL - {e}");
+            }
+        }
+    }
+    println!("{}/{} passed", files.len() - failures, files.len());
+
+    assert_eq!(0, failures, "Not all opcodes passed");
+}
@@ -1,76 +1,184 @@
 use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::breakpoint::Breakpoint;
+use crate::lameboy::mmu::watchpoint::{WatchKind, Watchpoint, Watchpoints};
 use imgui::{Condition, Ui};
 
-pub fn breakpoint_windows(ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+pub fn breakpoint_window(ui: &Ui, imgui_debug: &mut ImguiDebugState) {
     ui.window("Breakpoints")
-        .size([225.0, 150.0], Condition::FirstUseEver)
+        .size([300.0, 220.0], Condition::FirstUseEver)
         .resizable(true)
         .build(|| {
             if ui.button("Set") {
-                let breakpoint_addr = imgui_debug.input_breakpoint_addr as u16;
-                if !imgui_debug.breakpoints.contains(&breakpoint_addr) {
-                    imgui_debug.breakpoints.push(breakpoint_addr);
+                match imgui_debug
+                    .annotations
+                    .resolve(&imgui_debug.input_breakpoint_location, &imgui_debug.symbols)
+                {
+                    Ok(location) => {
+                        imgui_debug.input_breakpoint_error.clear();
+                        if !imgui_debug
+                            .breakpoints
+                            .iter()
+                            .any(|b| b.location() == location)
+                        {
+                            imgui_debug.breakpoints.push(Breakpoint::at(location));
+                        }
+                    }
+                    Err(e) => imgui_debug.input_breakpoint_error = e,
                 }
             }
             ui.same_line();
-            ui.input_int("Addr", &mut imgui_debug.input_breakpoint_addr)
-                .chars_hexadecimal(true)
+            ui.input_text("Addr", &mut imgui_debug.input_breakpoint_location)
+                .hint("0x0150, 03:4000 or a label")
                 .build();
+            if !imgui_debug.input_breakpoint_error.is_empty() {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], &imgui_debug.input_breakpoint_error);
+            }
 
             if ui.button("Clear All") {
                 imgui_debug.breakpoints.clear();
             }
 
-            let mut removal_index: Option<usize> = None;
             ui.text("Breakpoints:");
             ui.separator();
             if imgui_debug.breakpoints.is_empty() {
                 ui.text("None yet");
             } else {
                 for index in 0..imgui_debug.breakpoints.len() {
-                    if ui.selectable(format!("0x{:04X}", imgui_debug.breakpoints[index])) {
-                        removal_index = Some(index);
-                    };
+                    let breakpoint = &mut imgui_debug.breakpoints[index];
+                    ui.checkbox(format!("##enabled{index}"), &mut breakpoint.enabled);
+                    ui.same_line();
+                    let label = format!(
+                        "{}  hits: {}  {}",
+                        breakpoint.location(),
+                        breakpoint.hit_count,
+                        breakpoint.condition_source()
+                    );
+                    if ui
+                        .selectable_config(label)
+                        .selected(imgui_debug.selected_breakpoint == Some(index))
+                        .build()
+                    {
+                        imgui_debug.selected_breakpoint = Some(index);
+                        imgui_debug.input_breakpoint_condition =
+                            breakpoint.condition_source().to_string();
+                        imgui_debug.breakpoint_condition_error.clear();
+                    }
                 }
             }
-            if let Some(index) = removal_index {
-                imgui_debug.breakpoints.remove(index);
+
+            let selected = imgui_debug
+                .selected_breakpoint
+                .filter(|&index| index < imgui_debug.breakpoints.len());
+            if let Some(index) = selected {
+                ui.separator();
+                breakpoint_editor(ui, imgui_debug, index);
             }
         });
+}
 
-    ui.window("Memory Breakpoints")
-        .size([225.0, 150.0], Condition::FirstUseEver)
+pub fn watchpoint_window(
+    ui: &Ui,
+    watchpoints: &mut Watchpoints,
+    imgui_debug: &mut ImguiDebugState,
+) {
+    ui.window("Watchpoints")
+        .size([300.0, 250.0], Condition::FirstUseEver)
         .resizable(true)
         .build(|| {
-            if ui.button("Set") {
-                let breakpoint_addr = imgui_debug.input_breakpoint_addr as u16;
-                if !imgui_debug.memory_breakpoints.contains(&breakpoint_addr) {
-                    imgui_debug.memory_breakpoints.push(breakpoint_addr);
-                }
-            }
-            ui.same_line();
-            ui.input_int("Addr", &mut imgui_debug.input_breakpoint_addr)
+            ui.input_int("Start", &mut imgui_debug.input_watchpoint_start)
+                .chars_hexadecimal(true)
+                .build();
+            ui.input_int("End", &mut imgui_debug.input_watchpoint_end)
                 .chars_hexadecimal(true)
                 .build();
+            ui.combo(
+                "Kind",
+                &mut imgui_debug.input_watchpoint_kind,
+                &WatchKind::ALL,
+                |kind| kind.name().into(),
+            );
+            ui.checkbox("Log only", &mut imgui_debug.input_watchpoint_log_only);
 
+            if ui.button("Set") {
+                let mut watchpoint = Watchpoint::new(
+                    imgui_debug.input_watchpoint_start as u16,
+                    imgui_debug.input_watchpoint_end as u16,
+                    WatchKind::ALL[imgui_debug.input_watchpoint_kind],
+                );
+                watchpoint.log_only = imgui_debug.input_watchpoint_log_only;
+                watchpoints.add(watchpoint);
+            }
+            ui.same_line();
             if ui.button("Clear All") {
-                imgui_debug.memory_breakpoints.clear();
+                watchpoints.clear();
             }
 
-            let mut removal_index: Option<usize> = None;
-            ui.text("Breakpoints:");
+            ui.text("Watchpoints:");
             ui.separator();
-            if imgui_debug.memory_breakpoints.is_empty() {
+            if watchpoints.list().is_empty() {
                 ui.text("None yet");
-            } else {
-                for index in 0..imgui_debug.memory_breakpoints.len() {
-                    if ui.selectable(format!("0x{:04X}", imgui_debug.memory_breakpoints[index])) {
-                        removal_index = Some(index);
-                    };
+            }
+            let mut removal_index: Option<usize> = None;
+            for index in 0..watchpoints.list().len() {
+                let watchpoint = watchpoints.list()[index];
+                let mut enabled = watchpoint.enabled;
+                if ui.checkbox(format!("{watchpoint}##{index}"), &mut enabled) {
+                    watchpoints.set_enabled(index, enabled);
+                }
+                ui.same_line();
+                if ui.small_button(format!("x##{index}")) {
+                    removal_index = Some(index);
                 }
             }
             if let Some(index) = removal_index {
-                imgui_debug.memory_breakpoints.remove(index);
+                watchpoints.remove(index);
+            }
+
+            ui.separator();
+            ui.text("Hits:");
+            ui.same_line();
+            if ui.small_button("Clear") {
+                watchpoints.log.clear();
+            }
+            for hit in watchpoints.log.iter().rev() {
+                ui.text(hit.to_string());
             }
         });
 }
+
+fn breakpoint_editor(ui: &Ui, imgui_debug: &mut ImguiDebugState, index: usize) {
+    ui.input_text("Condition", &mut imgui_debug.input_breakpoint_condition)
+        .hint("e.g. a > 3 && [0xC123] == 0x05")
+        .build();
+
+    let breakpoint = &mut imgui_debug.breakpoints[index];
+
+    let mut ignore_count = breakpoint.ignore_count as i32;
+    if ui.input_int("Ignore count", &mut ignore_count).build() {
+        breakpoint.ignore_count = ignore_count.max(0) as u32;
+    }
+
+    if ui.button("Apply") {
+        imgui_debug.breakpoint_condition_error =
+            match breakpoint.set_condition(&imgui_debug.input_breakpoint_condition) {
+                Ok(()) => String::new(),
+                Err(e) => e,
+            };
+    }
+    ui.same_line();
+    if ui.button("Reset hits") {
+        breakpoint.hit_count = 0;
+    }
+    ui.same_line();
+    if ui.button("Delete") {
+        imgui_debug.breakpoints.remove(index);
+        imgui_debug.selected_breakpoint = None;
+    }
+
+    if !imgui_debug.breakpoint_condition_error.is_empty() {
+        ui.text_colored(
+            [1.0, 0.4, 0.4, 1.0],
+            &imgui_debug.breakpoint_condition_error,
+        );
+    }
+}
@@ -1,5 +1,9 @@
-use crate::lameboy::Lameboy;
+use crate::lameboy::trace::{diff_trace_files, TraceWriter};
+use crate::lameboy::{Lameboy, CYCLES_PER_FRAME};
 use imgui::{Condition, Ui};
+use std::path::Path;
+
+const TRACE_DIFF_CONTEXT: usize = 5;
 
 pub fn emulator_window(lameboy: &mut Lameboy, ui: &Ui) {
     ui.window("Emulator")
@@ -15,23 +19,120 @@ pub fn emulator_window(lameboy: &mut Lameboy, ui: &Ui) {
             }
             ui.same_line();
             if ui.button("Continue") {
-                lameboy.step();
-                lameboy.running = true;
+                lameboy.resume();
             }
             ui.same_line();
-            ui.checkbox("running", &mut lameboy.running);
+            if ui.checkbox("running", &mut lameboy.running) && !lameboy.running {
+                lameboy.stop();
+            }
 
-            if ui.button("Dump PC history") {
-                info!("Dumping PC history");
-                for i in 0..lameboy.get_cpu().pc_history.len() {
-                    let hp = lameboy.get_cpu().pc_history_pointer.wrapping_add(i)
-                        % lameboy.get_cpu().pc_history.len();
-                    info!("[{}] - PC = 0x{:04X}", i, lameboy.get_cpu().pc_history[hp]);
-                }
+            if ui.button("Step Over") {
+                lameboy.step_over();
+            }
+            ui.same_line();
+            if ui.button("Step Out") {
+                lameboy.step_out();
+            }
+            ui.same_line();
+            if ui.button("Step Scanline") {
+                lameboy.step_scanline();
+            }
+            ui.same_line();
+            if ui.button("Step Frame") {
+                lameboy.step_frame();
+            }
+            if let Some(condition) = lameboy.get_stop_condition() {
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], format!("Until: {condition:?}"));
             }
 
             ui.input_int("Trace Count", &mut lameboy.trace_count)
                 .chars_decimal(true)
                 .build();
+
+            ui.separator();
+            trace_controls(lameboy, ui);
+
+            ui.separator();
+            rewind_controls(lameboy, ui);
         });
 }
+
+fn rewind_controls(lameboy: &mut Lameboy, ui: &Ui) {
+    let rewind = lameboy.get_rewind();
+    ui.checkbox("Rewind snapshots", &mut rewind.enabled);
+
+    let mut interval = rewind.interval as i32;
+    if ui.input_int("Frames per snapshot", &mut interval).build() {
+        rewind.interval = interval.max(1) as u32;
+    }
+    let mut budget_mb = (rewind.budget / (1024 * 1024)) as i32;
+    if ui.input_int("Budget (MB)", &mut budget_mb).build() {
+        rewind.budget = budget_mb.max(1) as usize * 1024 * 1024;
+    }
+    ui.text(format!(
+        "{} snapshots, {} KB",
+        rewind.len(),
+        rewind.memory_used() / 1024
+    ));
+    if let Some(oldest) = rewind.oldest_cycle() {
+        let frames = lameboy.get_cycle_count().saturating_sub(oldest) / u64::from(CYCLES_PER_FRAME);
+        ui.same_line();
+        ui.text(format!("- back {frames} frames"));
+    }
+
+    if ui.button("Rewind") {
+        lameboy.stop();
+        lameboy.rewind();
+    }
+    ui.same_line();
+    if ui.button("Reverse Step") {
+        if let Err(e) = lameboy.reverse_step() {
+            error!("{}", e);
+        }
+    }
+    ui.same_line();
+    ui.text_colored([0.5, 0.5, 0.5, 1.0], "Hold backspace to rewind");
+}
+
+fn trace_controls(lameboy: &mut Lameboy, ui: &Ui) {
+    ui.input_text("Trace file", &mut lameboy.debug.trace_path)
+        .build();
+    ui.input_scalar("Start cycle", &mut lameboy.debug.trace_start)
+        .build();
+    ui.input_scalar("End cycle (0 = none)", &mut lameboy.debug.trace_end)
+        .build();
+    ui.text_colored(
+        [0.5, 0.5, 0.5, 1.0],
+        format!("Cycles from starting the trace, {CYCLES_PER_FRAME} per frame"),
+    );
+
+    if lameboy.is_tracing() {
+        if ui.button("Stop trace") {
+            lameboy.stop_trace();
+        }
+    } else if ui.button("Start trace") {
+        match TraceWriter::create(Path::new(&lameboy.debug.trace_path)) {
+            Ok(trace) => {
+                let start_cycle = lameboy.debug.trace_start;
+                let end_cycle = lameboy.debug.trace_end;
+                lameboy.start_trace(trace, start_cycle, (end_cycle > 0).then_some(end_cycle));
+            }
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    ui.input_text("Reference", &mut lameboy.debug.trace_reference_path)
+        .build();
+    if ui.button("Diff") {
+        lameboy.debug.trace_diff = match diff_trace_files(
+            Path::new(&lameboy.debug.trace_path),
+            Path::new(&lameboy.debug.trace_reference_path),
+            TRACE_DIFF_CONTEXT,
+        ) {
+            Ok(Some(divergence)) => divergence.to_string(),
+            Ok(None) => String::from("Traces match"),
+            Err(e) => e,
+        };
+    }
+    ui.text_wrapped(&lameboy.debug.trace_diff);
+}
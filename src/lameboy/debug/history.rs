@@ -0,0 +1,77 @@
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::history::ExecutionHistory;
+use imgui::{Condition, ListClipper, MouseButton, Ui};
+use std::path::{Path, PathBuf};
+
+pub fn history_window(history: &mut ExecutionHistory, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("Execution History")
+        .size([720.0, 300.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            ui.checkbox("Record", &mut history.enabled);
+            ui.same_line();
+            ui.set_next_item_width(100.0);
+            ui.input_int("Size", &mut imgui_debug.history_size).build();
+            ui.same_line();
+            if ui.button("Apply") {
+                history.set_capacity(imgui_debug.history_size.max(1) as usize);
+                imgui_debug.history_size = history.capacity() as i32;
+            }
+            ui.same_line();
+            if ui.button("Clear") {
+                history.clear();
+            }
+
+            ui.input_text("Export file", &mut imgui_debug.history_export_path)
+                .build();
+            ui.same_line();
+            if ui.button("Export") {
+                if let Err(e) = history.export(Path::new(&imgui_debug.history_export_path)) {
+                    error!("{}", e);
+                }
+            }
+            let mut export_on_break = history.export_path.is_some();
+            if ui.checkbox("Export on break or crash", &mut export_on_break) {
+                history.export_path =
+                    export_on_break.then(|| PathBuf::from(&imgui_debug.history_export_path));
+            }
+
+            ui.input_text("Search", &mut imgui_debug.history_search)
+                .hint("PC, bytes or mnemonic")
+                .build();
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Click to show in the disassembly, right click to rewind to it",
+            );
+            ui.separator();
+
+            let matches: Vec<usize> = history
+                .entries()
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.matches(&imgui_debug.history_search))
+                .map(|(index, _)| index)
+                .collect();
+            ui.text(format!(
+                "{} of {} instructions",
+                matches.len(),
+                history.entries().len()
+            ));
+
+            ui.child_window("history_entries").build(|| {
+                let mut clipper = ListClipper::new(matches.len() as i32).begin(ui);
+                while clipper.step() {
+                    for row in clipper.display_start()..clipper.display_end() {
+                        let entry = &history.entries()[matches[row as usize]];
+                        if ui.selectable(entry.to_string()) {
+                            imgui_debug.disassemble_memory_pc_lock = false;
+                            imgui_debug.disassemble_memory_addr = i32::from(entry.pc());
+                        }
+                        if ui.is_item_clicked_with_button(MouseButton::Right) {
+                            imgui_debug.rewind_to_cycle = Some(entry.cycle + 1);
+                        }
+                    }
+                }
+            });
+        });
+}
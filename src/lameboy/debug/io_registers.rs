@@ -0,0 +1,90 @@
+use crate::lameboy::io_registers::{BitField, FieldKind, IoRegister, Subsystem, IO_REGISTERS};
+use crate::lameboy::mmu::Mmu;
+use imgui::{Condition, TableColumnFlags, TableColumnSetup, TableFlags, TreeNodeFlags, Ui};
+
+pub fn io_registers_window(mmu: &mut Mmu, ui: &Ui) {
+    ui.window("I/O Registers")
+        .size([460.0, 520.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Edits are written through the MMU, so read only bits keep their value",
+            );
+
+            for subsystem in Subsystem::ALL {
+                if !ui.collapsing_header(subsystem.name(), TreeNodeFlags::DEFAULT_OPEN) {
+                    continue;
+                }
+
+                let flags = TableFlags::RESIZABLE | TableFlags::ROW_BG | TableFlags::BORDERS;
+                let Some(_table) = ui.begin_table_with_flags(subsystem.name(), 3, flags) else {
+                    continue;
+                };
+                for (name, width) in [("Register", 110.0), ("Value", 50.0)] {
+                    ui.table_setup_column_with(TableColumnSetup {
+                        flags: TableColumnFlags::WIDTH_FIXED,
+                        init_width_or_weight: width,
+                        ..TableColumnSetup::new(name)
+                    });
+                }
+                ui.table_setup_column("Fields");
+                ui.table_headers_row();
+
+                for register in IO_REGISTERS.iter().filter(|r| r.subsystem == subsystem) {
+                    register_row(mmu, ui, register);
+                }
+            }
+        });
+}
+
+fn register_row(mmu: &mut Mmu, ui: &Ui, register: &IoRegister) {
+    let addr = register.addr;
+    let mut value = mmu.read8_safe(addr);
+    let _id = ui.push_id_usize(usize::from(addr));
+
+    ui.table_next_row();
+    ui.table_next_column();
+    ui.text(format!("${addr:04X} {}", register.name()));
+
+    ui.table_next_column();
+    ui.set_next_item_width(-1.0);
+    if ui
+        .input_scalar("##value", &mut value)
+        .display_format("%02X")
+        .chars_hexadecimal(true)
+        .enter_returns_true(true)
+        .build()
+    {
+        mmu.write8(addr, value);
+    }
+
+    ui.table_next_column();
+    for field in register.fields {
+        if let Some(field_value) = field_editor(ui, field, field.get(value)) {
+            mmu.write8(addr, field.set(value, field_value));
+        }
+    }
+}
+
+/// Show a widget for a field, returning its new value if it was changed.
+fn field_editor(ui: &Ui, field: &BitField, current: u8) -> Option<u8> {
+    match field.kind {
+        FieldKind::Flag => {
+            let mut set = current != 0;
+            ui.checkbox(field.name, &mut set).then_some(u8::from(set))
+        }
+        FieldKind::Choice(values) => {
+            let mut index = usize::from(current);
+            ui.set_next_item_width(110.0);
+            ui.combo_simple_string(field.name, &mut index, values)
+                .then_some(index as u8)
+        }
+        FieldKind::Number => {
+            let mut number = current;
+            ui.set_next_item_width(110.0);
+            ui.slider(field.name, 0, field.max(), &mut number)
+                .then_some(number)
+        }
+    }
+}
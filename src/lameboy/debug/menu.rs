@@ -20,6 +20,15 @@ pub fn build_menu(lameboy: &mut Lameboy, ui: &Ui) {
             ui.checkbox("PPU", &mut lameboy.debug.show_ppu);
             ui.checkbox("Cart", &mut lameboy.debug.show_cart);
             ui.checkbox("Joypad", &mut lameboy.debug.show_joypad);
+            ui.checkbox("Watches", &mut lameboy.debug.show_watches);
+            ui.checkbox("History", &mut lameboy.debug.show_history);
+            ui.checkbox("Profiler", &mut lameboy.debug.show_profiler);
+            ui.checkbox("Timeline", &mut lameboy.debug.show_timeline);
+            ui.checkbox("Tiles", &mut lameboy.debug.show_tiles);
+            ui.checkbox("Tile Maps", &mut lameboy.debug.show_tile_maps);
+            ui.checkbox("OAM", &mut lameboy.debug.show_oam);
+            ui.checkbox("Palettes", &mut lameboy.debug.show_palettes);
+            ui.checkbox("I/O", &mut lameboy.debug.show_io_registers);
 
             menu.end();
         }
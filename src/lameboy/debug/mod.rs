@@ -3,14 +3,25 @@ use crate::lameboy::Lameboy;
 use imgui::Ui;
 
 use crate::lameboy::debug::about::about_window;
-use crate::lameboy::debug::breakpoints::breakpoint_windows;
+use crate::lameboy::debug::breakpoints::{breakpoint_window, watchpoint_window};
 use crate::lameboy::debug::emulator::emulator_window;
+use crate::lameboy::debug::history::history_window;
+use crate::lameboy::debug::io_registers::io_registers_window;
 use crate::lameboy::debug::menu::build_menu;
+use crate::lameboy::debug::profiler::profiler_window;
+use crate::lameboy::debug::timeline::timeline_window;
+use crate::lameboy::debug::watches::watch_window;
+use crate::lameboy::ppu::debug::{oam_window, palettes_window, tile_map_window, tiles_window};
 
 mod about;
 mod breakpoints;
 mod emulator;
+mod history;
+mod io_registers;
 mod menu;
+mod profiler;
+mod timeline;
+mod watches;
 
 impl Lameboy {
     pub fn imgui_display(&mut self, ui: &Ui) {
@@ -42,13 +53,60 @@ impl Lameboy {
             self.cpu.mmu.ppu.imgui_display(ui, &mut self.debug);
         }
 
+        if self.debug.show_tiles {
+            tiles_window(&self.cpu.mmu.ppu, ui, &mut self.debug);
+        }
+
+        if self.debug.show_tile_maps {
+            tile_map_window(&self.cpu.mmu.ppu, ui, &mut self.debug);
+        }
+
+        if self.debug.show_oam {
+            oam_window(&self.cpu.mmu.ppu, ui, &mut self.debug);
+        }
+
+        if self.debug.show_palettes {
+            palettes_window(&mut self.cpu.mmu, ui);
+        }
+
+        if self.debug.show_io_registers {
+            io_registers_window(&mut self.cpu.mmu, ui);
+        }
+
         if self.debug.show_emulator {
             emulator_window(self, ui);
-            breakpoint_windows(ui, &mut self.debug);
+            breakpoint_window(ui, &mut self.debug);
+            watchpoint_window(ui, &mut self.cpu.mmu.watchpoints, &mut self.debug);
+        }
+
+        if self.debug.show_history {
+            history_window(&mut self.history, ui, &mut self.debug);
+        }
+
+        if self.debug.show_profiler {
+            profiler_window(&mut self.profiler, ui, &mut self.debug);
+        }
+
+        if self.debug.show_timeline {
+            timeline_window(&mut self.cpu.mmu.timeline, ui, &mut self.debug);
+        }
+
+        if self.debug.show_watches {
+            watch_window(&mut self.cpu, ui, &mut self.debug);
         }
 
         if self.debug.show_about {
             about_window(ui, &mut self.debug);
         }
+
+        if let Some(addr) = self.debug.run_to_cursor.take() {
+            self.run_to(addr);
+        }
+
+        if let Some(cycle) = self.debug.rewind_to_cycle.take() {
+            if let Err(e) = self.rewind_to_cycle(cycle) {
+                error!("{}", e);
+            }
+        }
     }
 }
@@ -0,0 +1,194 @@
+use crate::dis::{Annotations, SymbolTable};
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::mmu::location::Location;
+use crate::lameboy::profiler::{Function, Profiler};
+use imgui::{Condition, TableColumnFlags, TableColumnSetup, TableFlags, TableSortDirection, Ui};
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// A line of the hotspot table, for either a function or a single PC.
+struct ProfileRow {
+    name: String,
+    location: Option<(u16, u16)>,
+    calls: Option<u64>,
+    self_cycles: u64,
+    total_cycles: u64,
+}
+
+pub fn profiler_window(profiler: &mut Profiler, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("Profiler")
+        .size([560.0, 320.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            ui.checkbox("Profile", &mut profiler.enabled);
+            ui.same_line();
+            if ui.button("Clear") {
+                profiler.clear();
+            }
+            ui.same_line();
+            let percent = |cycles: u64| 100.0 * cycles as f64 / profiler.total_cycles.max(1) as f64;
+            ui.text(format!(
+                "{} cycles, {:.1}% halted, {:.1}% in interrupts",
+                profiler.total_cycles,
+                percent(profiler.halt_cycles),
+                percent(profiler.interrupt_cycles)
+            ));
+
+            ui.input_text("Export file", &mut imgui_debug.profiler_export_path)
+                .build();
+            ui.same_line();
+            if ui.button("Export") {
+                let result = profiler.export_folded(
+                    Path::new(&imgui_debug.profiler_export_path),
+                    |function| {
+                        function_name(function, &imgui_debug.annotations, &imgui_debug.symbols)
+                    },
+                );
+                if let Err(e) = result {
+                    error!("{}", e);
+                }
+            }
+
+            ui.radio_button("Functions", &mut imgui_debug.profiler_by_pc, false);
+            ui.same_line();
+            ui.radio_button("Instructions", &mut imgui_debug.profiler_by_pc, true);
+            ui.same_line();
+            ui.text_colored([0.5, 0.5, 0.5, 1.0], "Click to show in the disassembly");
+            ui.separator();
+
+            let mut rows = if imgui_debug.profiler_by_pc {
+                pc_rows(profiler, imgui_debug)
+            } else {
+                function_rows(profiler, imgui_debug)
+            };
+
+            let flags = TableFlags::SORTABLE
+                | TableFlags::RESIZABLE
+                | TableFlags::ROW_BG
+                | TableFlags::BORDERS
+                | TableFlags::SCROLL_Y;
+            let Some(_table) = ui.begin_table_with_flags("profile", 6, flags) else {
+                return;
+            };
+            for (index, name) in ["Name", "Calls", "Self", "Self %", "Total", "Total %"]
+                .into_iter()
+                .enumerate()
+            {
+                let mut column = TableColumnSetup::new(name);
+                column.flags = TableColumnFlags::PREFER_SORT_DESCENDING;
+                if index == 2 {
+                    column.flags |= TableColumnFlags::DEFAULT_SORT;
+                }
+                ui.table_setup_column_with(column);
+            }
+            ui.table_setup_scroll_freeze(0, 1);
+            ui.table_headers_row();
+
+            if let Some(sort_specs) = ui.table_sort_specs_mut() {
+                sort_specs.conditional_sort(|specs| {
+                    if let Some(spec) = specs.iter().next() {
+                        imgui_debug.profiler_sort = (
+                            spec.column_idx(),
+                            spec.sort_direction() == Some(TableSortDirection::Descending),
+                        );
+                    }
+                });
+            }
+            let (column, descending) = imgui_debug.profiler_sort;
+            rows.sort_by(|a, b| {
+                let ordering = compare_rows(a, b, column);
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+
+            for row in rows {
+                ui.table_next_row();
+                ui.table_next_column();
+                if ui
+                    .selectable_config(&row.name)
+                    .span_all_columns(true)
+                    .build()
+                {
+                    if let Some((bank, addr)) = row.location {
+                        imgui_debug.disassemble_memory_pc_lock = false;
+                        imgui_debug.disassemble_memory_addr = i32::from(addr);
+                        if let Some(bank) = Location::new(bank, addr).bank {
+                            imgui_debug.disassemble_bank_pinned = true;
+                            imgui_debug.disassemble_bank = i32::from(bank);
+                        }
+                    }
+                }
+                ui.table_next_column();
+                if let Some(calls) = row.calls {
+                    ui.text(calls.to_string());
+                }
+                ui.table_next_column();
+                ui.text(row.self_cycles.to_string());
+                ui.table_next_column();
+                ui.text(format!("{:.1}", percent(row.self_cycles)));
+                ui.table_next_column();
+                ui.text(row.total_cycles.to_string());
+                ui.table_next_column();
+                ui.text(format!("{:.1}", percent(row.total_cycles)));
+            }
+        });
+}
+
+fn function_name(function: &Function, annotations: &Annotations, symbols: &SymbolTable) -> String {
+    let label = match function {
+        Function::Routine { bank, addr } => annotations.label_in_bank(*bank, *addr, symbols),
+        _ => None,
+    };
+    function.name(label)
+}
+
+fn function_rows(profiler: &Profiler, imgui_debug: &ImguiDebugState) -> Vec<ProfileRow> {
+    profiler
+        .functions()
+        .iter()
+        .map(|(function, stats)| ProfileRow {
+            name: function_name(function, &imgui_debug.annotations, &imgui_debug.symbols),
+            location: match function {
+                Function::Routine { bank, addr } => Some((*bank, *addr)),
+                _ => None,
+            },
+            calls: Some(stats.calls),
+            self_cycles: stats.self_cycles,
+            total_cycles: stats.total_cycles,
+        })
+        .collect()
+}
+
+fn pc_rows(profiler: &Profiler, imgui_debug: &ImguiDebugState) -> Vec<ProfileRow> {
+    profiler
+        .pc_cycles()
+        .iter()
+        .map(|(&(bank, pc), &cycles)| {
+            let label = imgui_debug
+                .annotations
+                .label_in_bank(bank, pc, &imgui_debug.symbols);
+            ProfileRow {
+                name: match label {
+                    Some(label) => format!("[{bank:02X}:{pc:04X}] {label}"),
+                    None => format!("[{bank:02X}:{pc:04X}]"),
+                },
+                location: Some((bank, pc)),
+                calls: None,
+                self_cycles: cycles,
+                total_cycles: cycles,
+            }
+        })
+        .collect()
+}
+
+fn compare_rows(a: &ProfileRow, b: &ProfileRow, column: usize) -> Ordering {
+    match column {
+        0 => a.name.cmp(&b.name),
+        1 => a.calls.cmp(&b.calls),
+        2 | 3 => a.self_cycles.cmp(&b.self_cycles),
+        _ => a.total_cycles.cmp(&b.total_cycles),
+    }
+}
@@ -0,0 +1,173 @@
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::timeline::{
+    EventKind, Timeline, TimelineEvent, DOTS_PER_LINE, LINES_PER_FRAME,
+};
+use imgui::{Condition, ListClipper, StyleColor, Ui};
+
+/// Kinds of event that can be shown or hidden together, with the colour they're plotted in.
+const CATEGORIES: [(&str, [f32; 4]); 7] = [
+    ("Requests", [1.0, 0.3, 0.3, 1.0]),
+    ("Services", [1.0, 0.6, 0.2, 1.0]),
+    ("Modes", [0.5, 0.6, 0.9, 1.0]),
+    ("LYC", [1.0, 1.0, 0.3, 1.0]),
+    ("Registers", [0.3, 0.9, 0.3, 1.0]),
+    ("DMA", [1.0, 0.3, 1.0, 1.0]),
+    ("MBC", [0.3, 0.9, 0.9, 1.0]),
+];
+
+/// How close in pixels the mouse has to be to an event on the grid to pick it.
+const PICK_RADIUS: f32 = 4.0;
+
+fn category(kind: &EventKind) -> usize {
+    match kind {
+        EventKind::InterruptRequest(_) => 0,
+        EventKind::InterruptService(_) => 1,
+        EventKind::Mode(_) => 2,
+        EventKind::LycMatch(_) => 3,
+        EventKind::RegisterWrite { .. } => 4,
+        EventKind::Dma(_) => 5,
+        EventKind::MbcWrite { .. } => 6,
+    }
+}
+
+fn describe(event: &TimelineEvent) -> String {
+    format!(
+        "LY {:3} dot {:3} [{:04X}] {}",
+        event.ly,
+        event.dot,
+        event.pc,
+        event.kind.description()
+    )
+}
+
+fn jump_to(imgui_debug: &mut ImguiDebugState, event: &TimelineEvent) {
+    imgui_debug.disassemble_memory_pc_lock = false;
+    imgui_debug.disassemble_memory_addr = i32::from(event.pc);
+}
+
+pub fn timeline_window(timeline: &mut Timeline, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("Timeline")
+        .size([520.0, 560.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            ui.checkbox("Record", &mut timeline.enabled);
+            ui.same_line();
+            if ui.button("Clear") {
+                timeline.clear();
+            }
+            ui.same_line();
+            ui.radio_button("Current frame", &mut imgui_debug.timeline_last_frame, false);
+            ui.same_line();
+            ui.radio_button("Last frame", &mut imgui_debug.timeline_last_frame, true);
+            ui.same_line();
+            ui.set_next_item_width(80.0);
+            ui.slider("Zoom", 1.0, 4.0, &mut imgui_debug.timeline_zoom);
+
+            for (index, (name, colour)) in CATEGORIES.iter().enumerate() {
+                if index > 0 {
+                    ui.same_line();
+                }
+                let _colour = ui.push_style_color(StyleColor::Text, *colour);
+                ui.checkbox(name, &mut imgui_debug.timeline_shown[index]);
+            }
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Click an event to show the instruction that caused it in the disassembly",
+            );
+            ui.separator();
+
+            let events: Vec<&TimelineEvent> = if imgui_debug.timeline_last_frame {
+                timeline.last()
+            } else {
+                timeline.current()
+            }
+            .iter()
+            .filter(|event| imgui_debug.timeline_shown[category(&event.kind)])
+            .collect();
+
+            let zoom = imgui_debug.timeline_zoom;
+            let size = [
+                f32::from(DOTS_PER_LINE) * zoom,
+                f32::from(LINES_PER_FRAME) * zoom,
+            ];
+            ui.child_window("timeline_grid")
+                .size([0.0, size[1] + 20.0])
+                .horizontal_scrollbar(true)
+                .build(|| {
+                    let origin = ui.cursor_screen_pos();
+                    ui.invisible_button("timeline_canvas", size);
+                    let hovered = ui.is_item_hovered();
+                    let clicked = ui.is_item_clicked();
+
+                    let point = |ly: f32, dot: f32| [origin[0] + dot * zoom, origin[1] + ly * zoom];
+                    let draw_list = ui.get_window_draw_list();
+                    // Shade the nominal OAM scan, drawing, HBlank and VBlank periods
+                    let shade = |ly: (f32, f32), dot: (f32, f32), colour: [f32; 4]| {
+                        draw_list
+                            .add_rect(point(ly.0, dot.0), point(ly.1, dot.1), colour)
+                            .filled(true)
+                            .build();
+                    };
+                    shade((0.0, 144.0), (0.0, 80.0), [0.20, 0.20, 0.28, 1.0]);
+                    shade((0.0, 144.0), (80.0, 252.0), [0.26, 0.22, 0.22, 1.0]);
+                    shade((0.0, 144.0), (252.0, 456.0), [0.16, 0.16, 0.16, 1.0]);
+                    shade((144.0, 154.0), (0.0, 456.0), [0.12, 0.12, 0.20, 1.0]);
+
+                    let dot_size = zoom.max(2.0);
+                    for event in &events {
+                        let top_left = point(f32::from(event.ly), f32::from(event.dot));
+                        draw_list
+                            .add_rect(
+                                top_left,
+                                [top_left[0] + dot_size, top_left[1] + dot_size],
+                                CATEGORIES[category(&event.kind)].1,
+                            )
+                            .filled(true)
+                            .build();
+                    }
+
+                    if !hovered {
+                        return;
+                    }
+                    let mouse = ui.io().mouse_pos;
+                    let ly = ((mouse[1] - origin[1]) / zoom) as u16;
+                    let dot = ((mouse[0] - origin[0]) / zoom) as u16;
+                    let picked: Vec<&&TimelineEvent> = events
+                        .iter()
+                        .filter(|event| {
+                            let at = point(f32::from(event.ly), f32::from(event.dot));
+                            (at[0] - mouse[0]).abs() <= PICK_RADIUS
+                                && (at[1] - mouse[1]).abs() <= PICK_RADIUS
+                        })
+                        .collect();
+                    ui.tooltip(|| {
+                        ui.text(format!("LY {ly} dot {dot}"));
+                        for event in &picked {
+                            ui.text_colored(CATEGORIES[category(&event.kind)].1, describe(event));
+                        }
+                    });
+                    if clicked {
+                        if let Some(event) = picked.first() {
+                            jump_to(imgui_debug, event);
+                        }
+                    }
+                });
+
+            ui.text(format!("{} events", events.len()));
+            ui.child_window("timeline_events").build(|| {
+                let mut clipper = ListClipper::new(events.len() as i32).begin(ui);
+                while clipper.step() {
+                    for row in clipper.display_start()..clipper.display_end() {
+                        let event = events[row as usize];
+                        let _colour = ui.push_style_color(
+                            StyleColor::Text,
+                            CATEGORIES[category(&event.kind)].1,
+                        );
+                        if ui.selectable(format!("{}##{row}", describe(event))) {
+                            jump_to(imgui_debug, event);
+                        }
+                    }
+                }
+            });
+        });
+}
@@ -0,0 +1,107 @@
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::cpu::Cpu;
+use crate::lameboy::watch::{Watch, WatchFormat};
+use imgui::{Condition, Ui};
+
+pub fn watch_window(cpu: &mut Cpu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("Watches")
+        .size([360.0, 300.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            ui.input_text("Location", &mut imgui_debug.input_watch)
+                .hint("0xC123, hl + 2, rLY, wLabel")
+                .build();
+            ui.combo(
+                "Format",
+                &mut imgui_debug.input_watch_format,
+                &WatchFormat::ALL,
+                |format| format.name().into(),
+            );
+            if ui.button("Add") {
+                let format = WatchFormat::ALL[imgui_debug.input_watch_format];
+                let label = imgui_debug
+                    .annotations
+                    .find(&imgui_debug.input_watch, &imgui_debug.symbols);
+                let watch = match label {
+                    Some((_, addr)) => Ok(Watch::labelled(&imgui_debug.input_watch, addr, format)),
+                    None => Watch::new(&imgui_debug.input_watch, format),
+                };
+                match watch {
+                    Ok(watch) => {
+                        imgui_debug.watches.push(watch);
+                        imgui_debug.input_watch.clear();
+                        imgui_debug.input_watch_error.clear();
+                    }
+                    Err(e) => imgui_debug.input_watch_error = e,
+                }
+            }
+            if !imgui_debug.input_watch_error.is_empty() {
+                ui.same_line();
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], &imgui_debug.input_watch_error);
+            }
+            ui.separator();
+
+            if imgui_debug.watches.is_empty() {
+                ui.text("None yet");
+            }
+
+            let mut removal_index: Option<usize> = None;
+            for (index, watch) in imgui_debug.watches.iter_mut().enumerate() {
+                let _id = ui.push_id_usize(index);
+
+                match watch
+                    .address(cpu)
+                    .and_then(|addr| Ok((addr, watch.read_raw(cpu)?)))
+                {
+                    Ok((addr, raw)) => {
+                        let (hex, binary, decimal) = watch.format_value(raw);
+                        ui.text(format!("{} [0x{addr:04X}]", watch.source()));
+                        ui.text_colored(
+                            [0.7, 0.7, 0.7, 1.0],
+                            format!("{hex}  {binary}  {decimal}"),
+                        );
+
+                        let mut value = watch.read(cpu).unwrap_or(0);
+                        ui.set_next_item_width(100.0);
+                        if ui
+                            .input_int("Value", &mut value)
+                            .enter_returns_true(true)
+                            .build()
+                        {
+                            watch.write(addr, &mut cpu.mmu, value);
+                        }
+                    }
+                    Err(e) => {
+                        ui.text(watch.source());
+                        ui.text_colored([1.0, 0.4, 0.4, 1.0], e);
+                    }
+                }
+
+                let mut format_index = WatchFormat::ALL
+                    .iter()
+                    .position(|&format| format == watch.format)
+                    .unwrap_or(0);
+                ui.same_line();
+                ui.set_next_item_width(60.0);
+                if ui.combo("##format", &mut format_index, &WatchFormat::ALL, |format| {
+                    format.name().into()
+                }) {
+                    watch.format = WatchFormat::ALL[format_index];
+                    watch.history.clear();
+                }
+                ui.same_line();
+                if ui.small_button("Remove") {
+                    removal_index = Some(index);
+                }
+
+                let history: Vec<f32> = watch.history.iter().map(|&v| v as f32).collect();
+                ui.plot_lines("##history", &history)
+                    .graph_size([0.0, 40.0])
+                    .build();
+                ui.separator();
+            }
+            if let Some(index) = removal_index {
+                imgui_debug.watches.remove(index);
+            }
+        });
+}
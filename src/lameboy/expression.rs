@@ -0,0 +1,504 @@
+use std::fmt;
+
+use crate::dis::get_memory_comment;
+use crate::lameboy::cpu::registers::{Flags, Reg16, Reg8, Registers};
+use crate::lameboy::cpu::Cpu;
+
+/// Anything an expression can be evaluated against.
+pub trait ExpressionContext {
+    fn registers(&self) -> &Registers;
+    /// Read memory without side effects.
+    fn read8(&self, addr: u16) -> u8;
+    /// The bank currently mapped at an address.
+    fn bank_of(&self, addr: u16) -> u16;
+}
+
+impl ExpressionContext for Cpu {
+    fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    fn read8(&self, addr: u16) -> u8 {
+        self.mmu.read8_safe(addr)
+    }
+
+    fn bank_of(&self, addr: u16) -> u16 {
+        self.mmu.bank_of(addr)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnaryOp {
+    Not,
+    Negate,
+    Complement,
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Number(i64),
+    Reg8(Reg8),
+    Reg16(Reg16),
+    RegF,
+    RegPc,
+    Flag(Flags),
+    Memory(Box<Node>),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+}
+
+/// A parsed expression over the CPU registers, flags, and memory, used for breakpoint conditions
+/// and watches.
+///
+/// Supports C style operators with the usual precedence, numbers in decimal, `0x`/`$` hex or
+/// `0b`/`%` binary, and `[addr]` to read a byte of memory. Names can be registers (`a`, `hl`,
+/// `pc`...), flags (`zf`, `nf`, `hf`, `cf`) or hardware registers with or without their `r`
+/// prefix (`ly`, `rLCDC`...).
+///
+/// ```text
+/// [0xC123] == 0x05
+/// a > 3 && ly == 144
+/// cf || [hl + 1] & %1000_0000
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Expression, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let root = parser.parse_binary(0)?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected '{token}'"));
+        }
+
+        Ok(Expression {
+            source: source.trim().to_string(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn evaluate(&self, context: &dyn ExpressionContext) -> Result<i64, String> {
+        evaluate(&self.root, context)
+    }
+
+    /// Evaluate as a condition, where any non-zero value is true.
+    pub fn is_true(&self, context: &dyn ExpressionContext) -> Result<bool, String> {
+        self.evaluate(context).map(|value| value != 0)
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn evaluate(node: &Node, context: &dyn ExpressionContext) -> Result<i64, String> {
+    let registers = context.registers();
+
+    let value = match node {
+        Node::Number(value) => *value,
+        Node::Reg8(r8) => i64::from(registers.read8(r8)),
+        Node::Reg16(r16) => i64::from(registers.read16(r16)),
+        Node::RegF => i64::from(registers.f.bits()),
+        Node::RegPc => i64::from(registers.pc),
+        Node::Flag(flag) => i64::from(registers.f.contains(*flag)),
+        Node::Memory(addr) => i64::from(context.read8(evaluate(addr, context)? as u16)),
+        Node::Unary(op, operand) => {
+            let operand = evaluate(operand, context)?;
+            match op {
+                UnaryOp::Not => i64::from(operand == 0),
+                UnaryOp::Negate => operand.wrapping_neg(),
+                UnaryOp::Complement => !operand,
+            }
+        }
+        Node::Binary(BinaryOp::And, left, right) => {
+            i64::from(evaluate(left, context)? != 0 && evaluate(right, context)? != 0)
+        }
+        Node::Binary(BinaryOp::Or, left, right) => {
+            i64::from(evaluate(left, context)? != 0 || evaluate(right, context)? != 0)
+        }
+        Node::Binary(op, left, right) => {
+            let left = evaluate(left, context)?;
+            let right = evaluate(right, context)?;
+            match op {
+                BinaryOp::BitOr => left | right,
+                BinaryOp::BitXor => left ^ right,
+                BinaryOp::BitAnd => left & right,
+                BinaryOp::Equal => i64::from(left == right),
+                BinaryOp::NotEqual => i64::from(left != right),
+                BinaryOp::Less => i64::from(left < right),
+                BinaryOp::LessEqual => i64::from(left <= right),
+                BinaryOp::Greater => i64::from(left > right),
+                BinaryOp::GreaterEqual => i64::from(left >= right),
+                BinaryOp::ShiftLeft => left.wrapping_shl(right as u32),
+                BinaryOp::ShiftRight => left.wrapping_shr(right as u32),
+                BinaryOp::Add => left.wrapping_add(right),
+                BinaryOp::Sub => left.wrapping_sub(right),
+                BinaryOp::Mul => left.wrapping_mul(right),
+                BinaryOp::Div => left
+                    .checked_div(right)
+                    .ok_or_else(|| String::from("Division by zero"))?,
+                BinaryOp::Rem => left
+                    .checked_rem(right)
+                    .ok_or_else(|| String::from("Division by zero"))?,
+                BinaryOp::And | BinaryOp::Or => unreachable!(),
+            }
+        }
+    };
+
+    Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Name(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{value}"),
+            Token::Name(name) => write!(f, "{name}"),
+            Token::Symbol(symbol) => write!(f, "{symbol}"),
+        }
+    }
+}
+
+/// Symbols in the order they are matched, longest first so `<=` is not read as `<` then `=`.
+const SYMBOLS: [&str; 24] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<<", ">>", "|", "^", "&", "<", ">", "+", "-", "*", "/",
+    "%", "!", "~", "(", ")", "[", "]",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c.is_ascii_digit() || c == '$' || (c == '%' && expects_operand(&tokens)) {
+            let (number, remainder) = take_number(rest)?;
+            tokens.push(Token::Number(number));
+            rest = remainder;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else {
+            return Err(format!("Unexpected character '{c}'"));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Whether the next token must be an operand, used to tell a `%` binary number from modulo.
+fn expects_operand(tokens: &[Token]) -> bool {
+    !matches!(
+        tokens.last(),
+        Some(Token::Number(_)) | Some(Token::Name(_)) | Some(Token::Symbol(")" | "]"))
+    )
+}
+
+fn take_number(source: &str) -> Result<(i64, &str), String> {
+    let (radix, digits) = if let Some(hex) = source.strip_prefix('$') {
+        (16, hex)
+    } else if let Some(binary) = source.strip_prefix('%') {
+        (2, binary)
+    } else if let Some(hex) = source
+        .strip_prefix("0x")
+        .or_else(|| source.strip_prefix("0X"))
+    {
+        (16, hex)
+    } else if let Some(binary) = source
+        .strip_prefix("0b")
+        .or_else(|| source.strip_prefix("0B"))
+    {
+        (2, binary)
+    } else {
+        (10, source)
+    };
+
+    let end = digits
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(digits.len());
+    let text = digits[..end].replace('_', "");
+
+    i64::from_str_radix(&text, radix)
+        .map(|value| (value, &digits[end..]))
+        .map_err(|_| {
+            format!(
+                "Invalid number '{}'",
+                &source[..source.len() - digits.len() + end]
+            )
+        })
+}
+
+/// Binary operators by precedence level, loosest binding first.
+const PRECEDENCE: [&[(&str, BinaryOp)]; 10] = [
+    &[("||", BinaryOp::Or)],
+    &[("&&", BinaryOp::And)],
+    &[("|", BinaryOp::BitOr)],
+    &[("^", BinaryOp::BitXor)],
+    &[("&", BinaryOp::BitAnd)],
+    &[("==", BinaryOp::Equal), ("!=", BinaryOp::NotEqual)],
+    &[
+        ("<", BinaryOp::Less),
+        ("<=", BinaryOp::LessEqual),
+        (">", BinaryOp::Greater),
+        (">=", BinaryOp::GreaterEqual),
+    ],
+    &[("<<", BinaryOp::ShiftLeft), (">>", BinaryOp::ShiftRight)],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+    &[
+        ("*", BinaryOp::Mul),
+        ("/", BinaryOp::Div),
+        ("%", BinaryOp::Rem),
+    ],
+];
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Symbol(s)) if *s == symbol => Ok(()),
+            Some(token) => Err(format!("Expected '{symbol}' but found '{token}'")),
+            None => Err(format!("Expected '{symbol}'")),
+        }
+    }
+
+    fn parse_binary(&mut self, level: usize) -> Result<Node, String> {
+        if level == PRECEDENCE.len() {
+            return self.parse_unary();
+        }
+
+        let mut left = self.parse_binary(level + 1)?;
+
+        while let Some(Token::Symbol(symbol)) = self.peek() {
+            let Some(&(_, op)) = PRECEDENCE[level].iter().find(|(s, _)| s == symbol) else {
+                break;
+            };
+            self.position += 1;
+            let right = self.parse_binary(level + 1)?;
+            left = Node::Binary(op, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, String> {
+        let op = match self.peek() {
+            Some(Token::Symbol("!")) => UnaryOp::Not,
+            Some(Token::Symbol("-")) => UnaryOp::Negate,
+            Some(Token::Symbol("~")) => UnaryOp::Complement,
+            _ => return self.parse_primary(),
+        };
+        self.position += 1;
+
+        Ok(Node::Unary(op, Box::new(self.parse_unary()?)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Node::Number(*value)),
+            Some(Token::Name(name)) => resolve_name(name),
+            Some(Token::Symbol("(")) => {
+                let node = self.parse_binary(0)?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Some(Token::Symbol("[")) => {
+                let node = self.parse_binary(0)?;
+                self.expect("]")?;
+                Ok(Node::Memory(Box::new(node)))
+            }
+            Some(token) => Err(format!("Unexpected '{token}'")),
+            None => Err(String::from("Unexpected end of expression")),
+        }
+    }
+}
+
+fn resolve_name(name: &str) -> Result<Node, String> {
+    let node = match name.to_ascii_lowercase().as_str() {
+        "a" => Node::Reg8(Reg8::A),
+        "b" => Node::Reg8(Reg8::B),
+        "c" => Node::Reg8(Reg8::C),
+        "d" => Node::Reg8(Reg8::D),
+        "e" => Node::Reg8(Reg8::E),
+        "h" => Node::Reg8(Reg8::H),
+        "l" => Node::Reg8(Reg8::L),
+        "f" => Node::RegF,
+        "af" => Node::Reg16(Reg16::AF),
+        "bc" => Node::Reg16(Reg16::BC),
+        "de" => Node::Reg16(Reg16::DE),
+        "hl" => Node::Reg16(Reg16::HL),
+        "sp" => Node::Reg16(Reg16::SP),
+        "pc" => Node::RegPc,
+        "zf" => Node::Flag(Flags::ZERO),
+        "nf" => Node::Flag(Flags::SUBTRACT),
+        "hf" => Node::Flag(Flags::HALF_CARRY),
+        "cf" => Node::Flag(Flags::CARRY),
+        _ => {
+            let addr = named_address(name).ok_or_else(|| format!("Unknown name '{name}'"))?;
+            Node::Memory(Box::new(Node::Number(i64::from(addr))))
+        }
+    };
+
+    Ok(node)
+}
+
+/// Look up the address of a hardware register by its name from the memory comments, with or
+/// without the `r` prefix.
+pub fn named_address(name: &str) -> Option<u16> {
+    (0xFF00..=0xFFFF).find(|&addr| {
+        get_memory_comment(addr).is_some_and(|comment| {
+            comment.eq_ignore_ascii_case(name)
+                || comment
+                    .strip_prefix('r')
+                    .is_some_and(|short| short.eq_ignore_ascii_case(name))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestContext {
+        registers: Registers,
+        memory: Vec<u8>,
+    }
+
+    impl ExpressionContext for TestContext {
+        fn registers(&self) -> &Registers {
+            &self.registers
+        }
+
+        fn read8(&self, addr: u16) -> u8 {
+            self.memory[addr as usize]
+        }
+
+        fn bank_of(&self, _addr: u16) -> u16 {
+            0
+        }
+    }
+
+    fn context() -> TestContext {
+        let mut registers = Registers::new();
+        registers.a = 0x05;
+        registers.h = 0xC1;
+        registers.l = 0x23;
+        registers.f = Flags::CARRY;
+
+        let mut memory = vec![0x00; 0x10000];
+        memory[0xC123] = 0x05;
+        memory[0xFF44] = 144;
+
+        TestContext { registers, memory }
+    }
+
+    fn eval(source: &str) -> Result<i64, String> {
+        Expression::parse(source)?.evaluate(&context())
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Ok(42), eval("42"));
+        assert_eq!(Ok(0xC123), eval("0xC123"));
+        assert_eq!(Ok(0xC123), eval("$c123"));
+        assert_eq!(Ok(0b1010), eval("0b1010"));
+        assert_eq!(Ok(0b1000_0000), eval("%1000_0000"));
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(Ok(7), eval("1 + 2 * 3"));
+        assert_eq!(Ok(9), eval("(1 + 2) * 3"));
+        assert_eq!(Ok(1), eval("1 + 1 == 2"));
+        assert_eq!(Ok(1), eval("1 || 0 && 0"));
+        assert_eq!(Ok(0x0F), eval("0xFF & 0x0F | 0x01"));
+        assert_eq!(Ok(1), eval("10 % 3"));
+        assert_eq!(Ok(0), eval("!5"));
+        assert_eq!(Ok(-2), eval("-2"));
+    }
+
+    #[test]
+    fn registers_flags_and_memory() {
+        assert_eq!(Ok(1), eval("[0xC123] == 0x05"));
+        assert_eq!(Ok(1), eval("[hl] == a"));
+        assert_eq!(Ok(1), eval("a > 3 && ly == 144"));
+        assert_eq!(Ok(1), eval("rLY == 144"));
+        assert_eq!(Ok(1), eval("cf && !zf"));
+        assert_eq!(Ok(0xC123), eval("HL"));
+        assert_eq!(Ok(0x0510), eval("af"));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(eval("").is_err());
+        assert!(eval("1 +").is_err());
+        assert!(eval("(1").is_err());
+        assert!(eval("[0xC000").is_err());
+        assert!(eval("1 2").is_err());
+        assert!(eval("foo").is_err());
+        assert!(eval("1 @ 2").is_err());
+        assert!(eval("1 / 0").is_err());
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::lameboy::cpu::opcodes::{lookup, Opcode, Operand};
+use crate::lameboy::cpu::registers::Registers;
+
+/// How many instructions are kept by default.
+pub const DEFAULT_HISTORY_SIZE: usize = 10_000;
+
+/// Most memory accesses a single instruction makes, past fetching itself.
+const MAX_ACCESSES: usize = 4;
+
+/// A read or write the CPU made through the MMU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryAccess {
+    pub addr: u16,
+    pub value: u8,
+    pub write: bool,
+}
+
+impl fmt::Display for MemoryAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.write { 'W' } else { 'R' };
+        write!(f, "{kind}[0x{:04X}]=0x{:02X}", self.addr, self.value)
+    }
+}
+
+/// An executed instruction, with the registers as they were before it ran.
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryEntry {
+    pub cycle: u64,
+    pub registers: Registers,
+    pub bytes: [u8; 3],
+    /// An interrupt was taken straight after the instruction.
+    pub interrupt: bool,
+    accesses: [MemoryAccess; MAX_ACCESSES],
+    access_count: u8,
+}
+
+impl HistoryEntry {
+    /// Build an entry from the accesses the CPU made executing an instruction, dropping the
+    /// fetches of the instruction itself.
+    pub fn new(
+        cycle: u64,
+        registers: Registers,
+        bytes: [u8; 3],
+        bus_accesses: &[MemoryAccess],
+        interrupt: bool,
+    ) -> HistoryEntry {
+        let length = usize::from(lookup(bytes[0], bytes[1]).length).min(bus_accesses.len());
+
+        let instruction_accesses = &bus_accesses[length..];
+        let access_count = instruction_accesses.len().min(MAX_ACCESSES);
+        let mut accesses = [MemoryAccess::default(); MAX_ACCESSES];
+        accesses[..access_count].copy_from_slice(&instruction_accesses[..access_count]);
+
+        HistoryEntry {
+            cycle,
+            registers,
+            bytes,
+            interrupt,
+            accesses,
+            access_count: access_count as u8,
+        }
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.registers.pc
+    }
+
+    pub fn opcode(&self) -> &'static Opcode {
+        lookup(self.bytes[0], self.bytes[1])
+    }
+
+    /// Memory the instruction read or wrote, past fetching itself.
+    pub fn accesses(&self) -> &[MemoryAccess] {
+        &self.accesses[..usize::from(self.access_count)]
+    }
+
+    pub fn raw_bytes(&self) -> String {
+        self.bytes[..usize::from(self.opcode().length)]
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// The instruction's mnemonic with its operand filled in.
+    pub fn disassembly(&self) -> String {
+        let opcode = self.opcode();
+        let d8 = self.bytes[1];
+        let d16 = u16::from(self.bytes[2]) << 8 | u16::from(self.bytes[1]);
+        match &opcode.operand {
+            None => String::from(opcode.mnemonic),
+            Some(Operand::Data8) => opcode.mnemonic.replace("d8", &format!("0x{d8:02X}")),
+            Some(Operand::Signed8) => opcode.mnemonic.replace("r8", &format!("{}", d8 as i8)),
+            Some(Operand::Address8) => opcode
+                .mnemonic
+                .replace("a8", &format!("0xFF00 + 0x{d8:02X}")),
+            Some(Operand::Data16) => opcode.mnemonic.replace("d16", &format!("0x{d16:04X}")),
+            Some(Operand::Address16) => opcode.mnemonic.replace("a16", &format!("0x{d16:04X}")),
+        }
+    }
+
+    /// Match a search against the PC, given in hex, or the instruction's bytes or disassembly.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_ascii_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+
+        let hex = query.trim_start_matches("0x").trim_start_matches('$');
+        if u16::from_str_radix(hex, 16).is_ok_and(|pc| pc == self.pc()) {
+            return true;
+        }
+
+        self.disassembly().contains(&query) || self.raw_bytes().to_ascii_lowercase() == query
+    }
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let r = &self.registers;
+        write!(
+            f,
+            "{:>12} 0x{:04X}  {:<8}  {:<22} A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} \
+             H:{:02X} L:{:02X} SP:{:04X}",
+            self.cycle,
+            r.pc,
+            self.raw_bytes(),
+            self.disassembly(),
+            r.a,
+            r.f.bits(),
+            r.b,
+            r.c,
+            r.d,
+            r.e,
+            r.h,
+            r.l,
+            r.sp
+        )?;
+        for access in self.accesses() {
+            write!(f, " {access}")?;
+        }
+        if self.interrupt {
+            write!(f, " [int]")?;
+        }
+        Ok(())
+    }
+}
+
+/// A ring buffer of the most recently executed instructions.
+pub struct ExecutionHistory {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+    pub enabled: bool,
+    /// Where to export the history when a breakpoint or watchpoint stops execution, or when the
+    /// emulator panics.
+    pub export_path: Option<PathBuf>,
+}
+
+impl ExecutionHistory {
+    pub fn new(capacity: usize) -> ExecutionHistory {
+        ExecutionHistory {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            enabled: true,
+            export_path: None,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change how many instructions are kept, dropping the oldest if shrinking.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The recorded instructions, oldest first.
+    pub fn entries(&self) -> &VecDeque<HistoryEntry> {
+        &self.entries
+    }
+
+    /// Drop the entries from a cycle onwards, as after rewinding they haven't happened yet.
+    pub fn discard_from(&mut self, cycle: u64) {
+        while self
+            .entries
+            .back()
+            .is_some_and(|entry| entry.cycle >= cycle)
+        {
+            self.entries.pop_back();
+        }
+    }
+
+    pub fn record(&mut self, entry: HistoryEntry) {
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        for entry in &self.entries {
+            writeln!(writer, "{entry}")?;
+        }
+        writer.flush()
+    }
+
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Unable to create history file {}: {e}", path.display()))?;
+        self.write_to(&mut BufWriter::new(file))
+            .map_err(|e| format!("Unable to write history file {}: {e}", path.display()))?;
+        info!(
+            "Exported {} instructions of history to {}",
+            self.entries.len(),
+            path.display()
+        );
+
+        Ok(())
+    }
+}
+
+impl Drop for ExecutionHistory {
+    /// Export the history if the emulator is being torn down by a panic, so the instructions
+    /// leading up to a crash aren't lost.
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            return;
+        }
+        if let Some(path) = &self.export_path {
+            if let Err(e) = self.export(path) {
+                error!("{}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    /// `ld a, 0x05; ld [0xC000], a; push bc; nop`
+    const PROGRAM: &[u8] = &[0x3E, 0x05, 0xEA, 0x00, 0xC0, 0xC5];
+
+    #[test]
+    fn records_instructions() {
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+        for _ in 0..3 {
+            lameboy.step();
+        }
+
+        let entries = lameboy.get_history().entries();
+        assert_eq!(3, entries.len());
+        assert_eq!(0x0100, entries[0].pc());
+        assert_eq!(0x01, entries[0].registers.a);
+        assert!(entries[0].accesses().is_empty());
+
+        assert_eq!(0x0102, entries[1].pc());
+        assert_eq!(0x05, entries[1].registers.a);
+        assert_eq!("EA 00 C0", entries[1].raw_bytes());
+        assert_eq!("ld [0xC000], a", entries[1].disassembly());
+        assert_eq!(
+            &[MemoryAccess {
+                addr: 0xC000,
+                value: 0x05,
+                write: true
+            }],
+            entries[1].accesses()
+        );
+        assert!(entries[1].cycle > entries[0].cycle);
+
+        assert_eq!(2, entries[2].accesses().len());
+        assert!(!entries[2].interrupt);
+    }
+
+    #[test]
+    fn records_interrupts_and_self_modifying_code() {
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+        lameboy.get_mmu().write8(0xFFFF, 0x01);
+        lameboy.get_mmu().write8(0xFF0F, 0x01);
+        lameboy.step();
+
+        // The interrupt's check, push and IF write aren't the instruction's accesses
+        let entry = lameboy.get_history().entries()[0];
+        assert_eq!(0x0100, entry.pc());
+        assert!(entry.interrupt);
+        assert!(entry.accesses().is_empty());
+
+        // ld [hl], a overwriting itself with a nop
+        lameboy.get_mmu().write8(0xC000, 0x77);
+        let registers = &mut lameboy.get_cpu().registers;
+        registers.pc = 0xC000;
+        registers.a = 0x00;
+        registers.h = 0xC0;
+        registers.l = 0x00;
+        lameboy.step();
+
+        let entry = lameboy.get_history().entries()[1];
+        assert_eq!("ld [hl], a", entry.disassembly());
+        assert!(!entry.interrupt);
+        assert_eq!(
+            &[MemoryAccess {
+                addr: 0xC000,
+                value: 0x00,
+                write: true
+            }],
+            entry.accesses()
+        );
+    }
+
+    #[test]
+    fn ring_buffer_and_search() {
+        let mut lameboy = Lameboy::with_program(PROGRAM);
+        lameboy.get_history().set_capacity(2);
+        for _ in 0..3 {
+            lameboy.step();
+        }
+
+        let history = lameboy.get_history();
+        assert_eq!(2, history.entries().len());
+        assert_eq!(0x0102, history.entries()[0].pc());
+
+        let entry = &history.entries()[1];
+        assert!(entry.matches("0x0105"));
+        assert!(entry.matches("push"));
+        assert!(entry.matches("c5"));
+        assert!(!entry.matches("0x0102"));
+        assert!(!entry.matches("pop"));
+
+        let mut exported = Vec::new();
+        history.write_to(&mut exported).unwrap();
+        let exported = String::from_utf8(exported).unwrap();
+        assert_eq!(2, exported.lines().count());
+        assert!(exported.contains("W[0xC000]=0x05"));
+    }
+}
@@ -0,0 +1,488 @@
+use crate::dis::get_memory_comment;
+
+/// The part of the hardware an I/O register belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    Joypad,
+    Serial,
+    Timer,
+    Interrupts,
+    Sound,
+    Lcd,
+    Cgb,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 7] = [
+        Subsystem::Joypad,
+        Subsystem::Serial,
+        Subsystem::Timer,
+        Subsystem::Interrupts,
+        Subsystem::Sound,
+        Subsystem::Lcd,
+        Subsystem::Cgb,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Joypad => "Joypad",
+            Subsystem::Serial => "Serial",
+            Subsystem::Timer => "Timer",
+            Subsystem::Interrupts => "Interrupts",
+            Subsystem::Sound => "Sound",
+            Subsystem::Lcd => "LCD",
+            Subsystem::Cgb => "CGB",
+        }
+    }
+}
+
+/// How a bit field's value is shown and edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// A single bit that's on or off.
+    Flag,
+    /// One of a set of named values, indexed by the field's value.
+    Choice(&'static [&'static str]),
+    /// A plain number.
+    Number,
+}
+
+/// A named run of bits within a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitField {
+    pub name: &'static str,
+    /// The lowest bit of the field.
+    pub shift: u8,
+    pub width: u8,
+    pub kind: FieldKind,
+}
+
+impl BitField {
+    const fn flag(name: &'static str, bit: u8) -> BitField {
+        BitField {
+            name,
+            shift: bit,
+            width: 1,
+            kind: FieldKind::Flag,
+        }
+    }
+
+    const fn choice(
+        name: &'static str,
+        shift: u8,
+        width: u8,
+        values: &'static [&'static str],
+    ) -> BitField {
+        BitField {
+            name,
+            shift,
+            width,
+            kind: FieldKind::Choice(values),
+        }
+    }
+
+    const fn number(name: &'static str, shift: u8, width: u8) -> BitField {
+        BitField {
+            name,
+            shift,
+            width,
+            kind: FieldKind::Number,
+        }
+    }
+
+    /// The largest value the field can hold.
+    pub fn max(&self) -> u8 {
+        (0xFFu16 >> (8 - self.width)) as u8
+    }
+
+    /// The field's bits within the register.
+    pub fn mask(&self) -> u8 {
+        self.max() << self.shift
+    }
+
+    /// Read the field from a register value.
+    pub fn get(&self, register: u8) -> u8 {
+        (register >> self.shift) & self.max()
+    }
+
+    /// Replace the field in a register value, keeping the other bits.
+    pub fn set(&self, register: u8, value: u8) -> u8 {
+        (register & !self.mask()) | ((value & self.max()) << self.shift)
+    }
+}
+
+/// A hardware register in the I/O area, with its bits decoded into fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoRegister {
+    pub addr: u16,
+    pub subsystem: Subsystem,
+    pub fields: &'static [BitField],
+}
+
+impl IoRegister {
+    pub fn name(&self) -> String {
+        match self.addr {
+            0xFF30..=0xFF3F => format!("Wave RAM {:X}", self.addr & 0x0F),
+            _ => String::from(get_memory_comment(self.addr).unwrap_or("-")),
+        }
+    }
+}
+
+const INTERRUPT_FIELDS: &[BitField] = &[
+    BitField::flag("Joypad", 4),
+    BitField::flag("Serial", 3),
+    BitField::flag("Timer", 2),
+    BitField::flag("LCD STAT", 1),
+    BitField::flag("VBlank", 0),
+];
+
+const BYTE: &[BitField] = &[BitField::number("Value", 0, 8)];
+
+const SWEEP_FIELDS: &[BitField] = &[
+    BitField::number("Sweep pace", 4, 3),
+    BitField::choice("Sweep direction", 3, 1, &["Increase", "Decrease"]),
+    BitField::number("Sweep step", 0, 3),
+];
+
+const DUTY_LENGTH_FIELDS: &[BitField] = &[
+    BitField::choice("Duty cycle", 6, 2, &["12.5%", "25%", "50%", "75%"]),
+    BitField::number("Length", 0, 6),
+];
+
+const ENVELOPE_FIELDS: &[BitField] = &[
+    BitField::number("Initial volume", 4, 4),
+    BitField::choice("Envelope direction", 3, 1, &["Decrease", "Increase"]),
+    BitField::number("Envelope pace", 0, 3),
+];
+
+const PERIOD_LOW_FIELDS: &[BitField] = &[BitField::number("Period low", 0, 8)];
+
+const PERIOD_HIGH_FIELDS: &[BitField] = &[
+    BitField::flag("Trigger", 7),
+    BitField::flag("Length enable", 6),
+    BitField::number("Period high", 0, 3),
+];
+
+const WAVE_FIELDS: &[BitField] = &[
+    BitField::number("First sample", 4, 4),
+    BitField::number("Second sample", 0, 4),
+];
+
+const DMG_PALETTE_FIELDS: &[BitField] = &[
+    BitField::choice("Colour 3", 6, 2, SHADES),
+    BitField::choice("Colour 2", 4, 2, SHADES),
+    BitField::choice("Colour 1", 2, 2, SHADES),
+    BitField::choice("Colour 0", 0, 2, SHADES),
+];
+
+const SHADES: &[&str] = &["White", "Light grey", "Dark grey", "Black"];
+
+const CGB_PALETTE_INDEX_FIELDS: &[BitField] = &[
+    BitField::flag("Auto increment", 7),
+    BitField::number("Address", 0, 6),
+];
+
+const TILE_MAPS: &[&str] = &["$9800", "$9C00"];
+
+const fn register(addr: u16, subsystem: Subsystem, fields: &'static [BitField]) -> IoRegister {
+    IoRegister {
+        addr,
+        subsystem,
+        fields,
+    }
+}
+
+const fn wave(addr: u16) -> IoRegister {
+    register(addr, Subsystem::Sound, WAVE_FIELDS)
+}
+
+/// Every hardware register in 0xFF00-0xFF7F and 0xFFFF, in address order within each subsystem.
+pub const IO_REGISTERS: &[IoRegister] = &[
+    register(
+        0xFF00,
+        Subsystem::Joypad,
+        &[
+            BitField::choice("Buttons", 5, 1, &["Selected", "Not selected"]),
+            BitField::choice("D-pad", 4, 1, &["Selected", "Not selected"]),
+            BitField::choice("Start / Down", 3, 1, &["Pressed", "Released"]),
+            BitField::choice("Select / Up", 2, 1, &["Pressed", "Released"]),
+            BitField::choice("B / Left", 1, 1, &["Pressed", "Released"]),
+            BitField::choice("A / Right", 0, 1, &["Pressed", "Released"]),
+        ],
+    ),
+    register(0xFF01, Subsystem::Serial, BYTE),
+    register(
+        0xFF02,
+        Subsystem::Serial,
+        &[
+            BitField::flag("Transfer enable", 7),
+            BitField::choice("Clock speed", 1, 1, &["Normal", "Fast (CGB)"]),
+            BitField::choice("Clock select", 0, 1, &["External", "Internal"]),
+        ],
+    ),
+    register(0xFF04, Subsystem::Timer, BYTE),
+    register(0xFF05, Subsystem::Timer, BYTE),
+    register(0xFF06, Subsystem::Timer, BYTE),
+    register(
+        0xFF07,
+        Subsystem::Timer,
+        &[
+            BitField::flag("Enable", 2),
+            BitField::choice(
+                "Clock select",
+                0,
+                2,
+                &["4096 Hz", "262144 Hz", "65536 Hz", "16384 Hz"],
+            ),
+        ],
+    ),
+    register(0xFF0F, Subsystem::Interrupts, INTERRUPT_FIELDS),
+    register(0xFFFF, Subsystem::Interrupts, INTERRUPT_FIELDS),
+    register(0xFF10, Subsystem::Sound, SWEEP_FIELDS),
+    register(0xFF11, Subsystem::Sound, DUTY_LENGTH_FIELDS),
+    register(0xFF12, Subsystem::Sound, ENVELOPE_FIELDS),
+    register(0xFF13, Subsystem::Sound, PERIOD_LOW_FIELDS),
+    register(0xFF14, Subsystem::Sound, PERIOD_HIGH_FIELDS),
+    register(0xFF16, Subsystem::Sound, DUTY_LENGTH_FIELDS),
+    register(0xFF17, Subsystem::Sound, ENVELOPE_FIELDS),
+    register(0xFF18, Subsystem::Sound, PERIOD_LOW_FIELDS),
+    register(0xFF19, Subsystem::Sound, PERIOD_HIGH_FIELDS),
+    register(0xFF1A, Subsystem::Sound, &[BitField::flag("DAC enable", 7)]),
+    register(
+        0xFF1B,
+        Subsystem::Sound,
+        &[BitField::number("Length", 0, 8)],
+    ),
+    register(
+        0xFF1C,
+        Subsystem::Sound,
+        &[BitField::choice(
+            "Output level",
+            5,
+            2,
+            &["Mute", "100%", "50%", "25%"],
+        )],
+    ),
+    register(0xFF1D, Subsystem::Sound, PERIOD_LOW_FIELDS),
+    register(0xFF1E, Subsystem::Sound, PERIOD_HIGH_FIELDS),
+    register(
+        0xFF20,
+        Subsystem::Sound,
+        &[BitField::number("Length", 0, 6)],
+    ),
+    register(0xFF21, Subsystem::Sound, ENVELOPE_FIELDS),
+    register(
+        0xFF22,
+        Subsystem::Sound,
+        &[
+            BitField::number("Clock shift", 4, 4),
+            BitField::choice("LFSR width", 3, 1, &["15 bit", "7 bit"]),
+            BitField::number("Clock divider", 0, 3),
+        ],
+    ),
+    register(
+        0xFF23,
+        Subsystem::Sound,
+        &[
+            BitField::flag("Trigger", 7),
+            BitField::flag("Length enable", 6),
+        ],
+    ),
+    register(
+        0xFF24,
+        Subsystem::Sound,
+        &[
+            BitField::flag("VIN left", 7),
+            BitField::number("Left volume", 4, 3),
+            BitField::flag("VIN right", 3),
+            BitField::number("Right volume", 0, 3),
+        ],
+    ),
+    register(
+        0xFF25,
+        Subsystem::Sound,
+        &[
+            BitField::flag("CH4 left", 7),
+            BitField::flag("CH3 left", 6),
+            BitField::flag("CH2 left", 5),
+            BitField::flag("CH1 left", 4),
+            BitField::flag("CH4 right", 3),
+            BitField::flag("CH3 right", 2),
+            BitField::flag("CH2 right", 1),
+            BitField::flag("CH1 right", 0),
+        ],
+    ),
+    register(
+        0xFF26,
+        Subsystem::Sound,
+        &[
+            BitField::flag("Audio enable", 7),
+            BitField::flag("CH4 on", 3),
+            BitField::flag("CH3 on", 2),
+            BitField::flag("CH2 on", 1),
+            BitField::flag("CH1 on", 0),
+        ],
+    ),
+    wave(0xFF30),
+    wave(0xFF31),
+    wave(0xFF32),
+    wave(0xFF33),
+    wave(0xFF34),
+    wave(0xFF35),
+    wave(0xFF36),
+    wave(0xFF37),
+    wave(0xFF38),
+    wave(0xFF39),
+    wave(0xFF3A),
+    wave(0xFF3B),
+    wave(0xFF3C),
+    wave(0xFF3D),
+    wave(0xFF3E),
+    wave(0xFF3F),
+    register(
+        0xFF40,
+        Subsystem::Lcd,
+        &[
+            BitField::flag("LCD enable", 7),
+            BitField::choice("Window tile map", 6, 1, TILE_MAPS),
+            BitField::flag("Window enable", 5),
+            BitField::choice("BG and window tiles", 4, 1, &["$8800", "$8000"]),
+            BitField::choice("BG tile map", 3, 1, TILE_MAPS),
+            BitField::choice("Sprite size", 2, 1, &["8x8", "8x16"]),
+            BitField::flag("Sprites enable", 1),
+            BitField::flag("BG and window enable", 0),
+        ],
+    ),
+    register(
+        0xFF41,
+        Subsystem::Lcd,
+        &[
+            BitField::flag("LYC interrupt", 6),
+            BitField::flag("Mode 2 interrupt", 5),
+            BitField::flag("Mode 1 interrupt", 4),
+            BitField::flag("Mode 0 interrupt", 3),
+            BitField::flag("LY = LYC", 2),
+            BitField::choice("Mode", 0, 2, &["HBlank", "VBlank", "OAM scan", "Drawing"]),
+        ],
+    ),
+    register(0xFF42, Subsystem::Lcd, BYTE),
+    register(0xFF43, Subsystem::Lcd, BYTE),
+    register(0xFF44, Subsystem::Lcd, BYTE),
+    register(0xFF45, Subsystem::Lcd, BYTE),
+    register(
+        0xFF46,
+        Subsystem::Lcd,
+        &[BitField::number("Source page", 0, 8)],
+    ),
+    register(0xFF47, Subsystem::Lcd, DMG_PALETTE_FIELDS),
+    register(0xFF48, Subsystem::Lcd, DMG_PALETTE_FIELDS),
+    register(0xFF49, Subsystem::Lcd, DMG_PALETTE_FIELDS),
+    register(0xFF4A, Subsystem::Lcd, BYTE),
+    register(0xFF4B, Subsystem::Lcd, BYTE),
+    register(
+        0xFF4D,
+        Subsystem::Cgb,
+        &[
+            BitField::choice("Current speed", 7, 1, &["Normal", "Double"]),
+            BitField::flag("Switch armed", 0),
+        ],
+    ),
+    register(
+        0xFF4F,
+        Subsystem::Cgb,
+        &[BitField::number("VRAM bank", 0, 1)],
+    ),
+    register(
+        0xFF51,
+        Subsystem::Cgb,
+        &[BitField::number("Source high", 0, 8)],
+    ),
+    register(
+        0xFF52,
+        Subsystem::Cgb,
+        &[BitField::number("Source low", 4, 4)],
+    ),
+    register(
+        0xFF53,
+        Subsystem::Cgb,
+        &[BitField::number("Destination high", 0, 5)],
+    ),
+    register(
+        0xFF54,
+        Subsystem::Cgb,
+        &[BitField::number("Destination low", 4, 4)],
+    ),
+    register(
+        0xFF55,
+        Subsystem::Cgb,
+        &[
+            BitField::choice("Mode", 7, 1, &["General", "HBlank"]),
+            BitField::number("Length", 0, 7),
+        ],
+    ),
+    register(
+        0xFF56,
+        Subsystem::Cgb,
+        &[
+            BitField::number("Read enable", 6, 2),
+            BitField::choice("Receiving", 1, 1, &["Light", "No light"]),
+            BitField::flag("Emitting", 0),
+        ],
+    ),
+    register(0xFF68, Subsystem::Cgb, CGB_PALETTE_INDEX_FIELDS),
+    register(0xFF69, Subsystem::Cgb, BYTE),
+    register(0xFF6A, Subsystem::Cgb, CGB_PALETTE_INDEX_FIELDS),
+    register(0xFF6B, Subsystem::Cgb, BYTE),
+    register(
+        0xFF70,
+        Subsystem::Cgb,
+        &[BitField::number("WRAM bank", 0, 3)],
+    ),
+    register(
+        0xFF76,
+        Subsystem::Cgb,
+        &[BitField::number("CH2", 4, 4), BitField::number("CH1", 0, 4)],
+    ),
+    register(
+        0xFF77,
+        Subsystem::Cgb,
+        &[BitField::number("CH4", 4, 4), BitField::number("CH3", 0, 4)],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_fields_are_named_and_disjoint() {
+        for register in IO_REGISTERS {
+            assert!(
+                (0xFF00..=0xFF7F).contains(&register.addr) || register.addr == 0xFFFF,
+                "{:04X} isn't an I/O register",
+                register.addr
+            );
+            assert_ne!("-", register.name(), "{:04X} has no name", register.addr);
+
+            let mut used = 0u8;
+            for field in register.fields {
+                assert_eq!(0, used & field.mask(), "{} overlaps", field.name);
+                used |= field.mask();
+                if let FieldKind::Choice(values) = field.kind {
+                    assert_eq!(usize::from(field.max()) + 1, values.len(), "{}", field.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_field_keeps_other_bits() {
+        let mode = BitField::choice("Mode", 0, 2, &["0", "1", "2", "3"]);
+        assert_eq!(0b1000_0010, mode.set(0b1000_0001, 2));
+        assert_eq!(2, mode.get(0b1000_0010));
+
+        let volume = BitField::number("Volume", 4, 4);
+        assert_eq!(0b1010_0101, volume.set(0b0000_0101, 0xFA));
+        assert_eq!(0xFF, BitField::number("Value", 0, 8).mask());
+    }
+}
@@ -3,6 +3,7 @@ use imgui::{Condition, Ui};
 use crate::gui::imgui_debug_state::ImguiDebugState;
 use crate::gui::imgui_debuggable::ImguiDebuggable;
 use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::state::{StateReader, StateWriter, Stateful};
 
 const LOW_NIBBLE_MASK: u8 = 0x0F;
 const COLUMN_MASK: u8 = 0b0011_0000;
@@ -97,6 +98,19 @@ impl MmuObject for Joypad {
     }
 }
 
+/// Only the column select is saved, the buttons held belong to whoever is playing.
+impl Stateful for Joypad {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.selected_column);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.selected_column = state.u8()?;
+
+        Ok(())
+    }
+}
+
 impl ImguiDebuggable for Joypad {
     fn imgui_display(&mut self, ui: &Ui, _imgui_debug: &mut ImguiDebugState) {
         ui.window("Joypad")
@@ -0,0 +1,35 @@
+use crate::lameboy::mmu::Mmu;
+use crate::lameboy::timeline::EventKind;
+
+/// The CPU's view of the address space.
+///
+/// The CPU only ever needs to read and write bytes, so anything implementing this can stand in
+/// for the full `Mmu`, such as a flat block of memory when running instruction test vectors.
+pub trait Bus {
+    fn read8(&mut self, addr: u16) -> u8;
+    /// Read a byte of an instruction, the opcode being its first byte.
+    fn fetch8(&mut self, addr: u16, _opcode: bool) -> u8 {
+        self.read8(addr)
+    }
+    fn write8(&mut self, addr: u16, data: u8);
+    /// Note something the CPU did on the frame's event timeline.
+    fn log_event(&mut self, _kind: EventKind) {}
+}
+
+impl Bus for Mmu {
+    fn read8(&mut self, addr: u16) -> u8 {
+        Mmu::read8(self, addr)
+    }
+
+    fn fetch8(&mut self, addr: u16, opcode: bool) -> u8 {
+        Mmu::fetch8(self, addr, opcode)
+    }
+
+    fn write8(&mut self, addr: u16, data: u8) {
+        Mmu::write8(self, addr, data)
+    }
+
+    fn log_event(&mut self, kind: EventKind) {
+        Mmu::log_event(self, kind)
+    }
+}
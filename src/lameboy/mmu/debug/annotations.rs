@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use crate::dis::Annotation;
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::mmu::location::Location;
+use imgui::{Condition, Ui};
+
+/// Pick an address in a bank to edit the annotation of.
+pub(super) fn select_annotation(imgui_debug: &mut ImguiDebugState, bank: u16, addr: u16) {
+    let annotation = imgui_debug
+        .annotations
+        .get(bank, addr)
+        .cloned()
+        .unwrap_or_default();
+
+    imgui_debug.annotate_location = Some((bank, addr));
+    imgui_debug.annotation_label = annotation.label;
+    imgui_debug.annotation_comment = annotation.comment;
+    imgui_debug.annotation_error.clear();
+}
+
+pub fn annotation_window(ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("Annotations")
+        .size([300.0, 240.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            match imgui_debug.annotate_location {
+                None => ui.text_colored(
+                    [0.5, 0.5, 0.5, 1.0],
+                    "Middle click the disassembly or right click the dump to annotate",
+                ),
+                Some((bank, addr)) => {
+                    ui.text(format!("{bank:02X}:{addr:04X}"));
+                    ui.input_text("Label", &mut imgui_debug.annotation_label)
+                        .build();
+                    ui.input_text("Comment", &mut imgui_debug.annotation_comment)
+                        .build();
+
+                    let mut update = None;
+                    if ui.button("Save") {
+                        update = Some(Annotation {
+                            label: imgui_debug.annotation_label.clone(),
+                            comment: imgui_debug.annotation_comment.clone(),
+                        });
+                    }
+                    ui.same_line();
+                    if ui.button("Delete") {
+                        update = Some(Annotation::default());
+                    }
+                    ui.same_line();
+                    if ui.button("Close") {
+                        imgui_debug.annotate_location = None;
+                    }
+
+                    if let Some(annotation) = update {
+                        match imgui_debug.annotations.set(bank, addr, annotation) {
+                            Ok(()) => select_annotation(imgui_debug, bank, addr),
+                            Err(e) => imgui_debug.annotation_error = e,
+                        }
+                    }
+                    if !imgui_debug.annotation_error.is_empty() {
+                        ui.text_colored([1.0, 0.4, 0.4, 1.0], &imgui_debug.annotation_error);
+                    }
+                }
+            }
+            ui.separator();
+
+            ui.input_text("##export", &mut imgui_debug.annotation_export_path)
+                .build();
+            ui.same_line();
+            if ui.button("Export .sym") {
+                if let Err(e) = imgui_debug.annotations.export_sym(
+                    Path::new(&imgui_debug.annotation_export_path),
+                    &imgui_debug.symbols,
+                ) {
+                    error!("{}", e);
+                }
+            }
+            ui.separator();
+
+            let mut jump_location = None;
+            for (&(bank, addr), annotation) in imgui_debug.annotations.iter() {
+                let text = if annotation.comment.is_empty() {
+                    format!("{bank:02X}:{addr:04X} {}", annotation.label)
+                } else {
+                    format!(
+                        "{bank:02X}:{addr:04X} {} ; {}",
+                        annotation.label, annotation.comment
+                    )
+                };
+                if ui.selectable(text) {
+                    jump_location = Some((bank, addr));
+                }
+            }
+            if let Some((bank, addr)) = jump_location {
+                imgui_debug.disassemble_memory_pc_lock = false;
+                imgui_debug.disassemble_memory_addr = i32::from(addr);
+                if let Some(bank) = Location::new(bank, addr).bank {
+                    imgui_debug.disassemble_bank_pinned = true;
+                    imgui_debug.disassemble_bank = i32::from(bank);
+                }
+                select_annotation(imgui_debug, bank, addr);
+            }
+        });
+}
@@ -1,10 +1,17 @@
 use crate::dis;
-use crate::dis::{Instruction, InstructionArg};
+use crate::dis::{Annotations, SymbolTable};
 use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::breakpoint::Breakpoint;
+use crate::lameboy::cdl::{CdlFlags, CodeDataLog};
+use crate::lameboy::cpu::opcodes::{Opcode, Operand};
+use crate::lameboy::mmu::location::Location;
 use crate::lameboy::mmu::Mmu;
-use imgui::{Condition, StyleColor, Ui};
+use imgui::{Condition, MouseButton, StyleColor, Ui};
+use std::path::Path;
 
-pub fn disassembly_window(mmu: &Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+use super::annotations::select_annotation;
+
+pub fn disassembly_window(mmu: &mut Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
     ui.window("Disassembled code")
         .size([260.0, 140.0], Condition::FirstUseEver)
         .resizable(true)
@@ -16,6 +23,39 @@ pub fn disassembly_window(mmu: &Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState)
             ui.input_int("Addr", &mut imgui_debug.disassemble_memory_addr)
                 .chars_hexadecimal(true)
                 .build();
+            bank_selector(
+                ui,
+                "##disassemble",
+                mmu,
+                &mut imgui_debug.disassemble_bank_pinned,
+                &mut imgui_debug.disassemble_bank,
+            );
+            if ui
+                .input_text("Go to", &mut imgui_debug.disassemble_goto)
+                .hint("03:4000 or a label")
+                .enter_returns_true(true)
+                .build()
+            {
+                match imgui_debug
+                    .annotations
+                    .resolve(&imgui_debug.disassemble_goto, &imgui_debug.symbols)
+                {
+                    Ok(location) => {
+                        imgui_debug.disassemble_memory_pc_lock = false;
+                        imgui_debug.disassemble_memory_addr = i32::from(location.addr);
+                        if let Some(bank) = location.bank {
+                            imgui_debug.disassemble_bank_pinned = true;
+                            imgui_debug.disassemble_bank = i32::from(bank);
+                        }
+                    }
+                    Err(e) => warn!("{}", e),
+                }
+            }
+            code_data_log_controls(mmu, ui, imgui_debug);
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Click to toggle a breakpoint, right click to run to cursor, middle click to annotate",
+            );
             ui.separator();
 
             let context_size = 100;
@@ -28,111 +68,242 @@ pub fn disassembly_window(mmu: &Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState)
             };
 
             for _ in 0..context_size {
-                let instruction = dis::decode_instruction(instruction_addr, mmu);
+                let location = if imgui_debug.disassemble_bank_pinned {
+                    Location::new(imgui_debug.disassemble_bank as u16, instruction_addr)
+                } else {
+                    Location::from(instruction_addr)
+                };
+                let bank = mmu.bank_at(location);
+                let instruction = dis::decode_instruction(location, mmu);
 
                 let raw_instruction_debug_string =
-                    get_raw_instruction_debug_string(&instruction, mmu, instruction_addr);
+                    get_raw_instruction_debug_string(instruction, mmu, location);
                 let instruction_debug_str = get_instruction_debug_string(
-                    &instruction,
+                    instruction,
                     imgui_debug.disassemble_read_args,
                     mmu,
-                    instruction_addr,
+                    &imgui_debug.annotations,
+                    &imgui_debug.symbols,
+                    location,
                 );
 
-                let addr_string = format!("[0x{instruction_addr:04X}]");
+                if let Some(label) = imgui_debug.annotations.label_in_bank(
+                    bank,
+                    instruction_addr,
+                    &imgui_debug.symbols,
+                ) {
+                    ui.text_colored([0.9, 0.8, 0.4, 1.0], format!("{label}:"));
+                }
+
+                let addr_string = format!("[{bank:02X}:{instruction_addr:04X}]");
                 let disassembly_string =
                     format!("{raw_instruction_debug_string: <14} | {instruction_debug_str}");
 
-                let style = if imgui_debug.breakpoints.contains(&instruction_addr) {
+                let coverage = mmu
+                    .code_data_log
+                    .as_ref()
+                    .filter(|_| imgui_debug.disassemble_coverage)
+                    .and_then(|log| Some(log.get(mmu.rom_offset(location)?)));
+
+                let style = if imgui_debug
+                    .breakpoints
+                    .iter()
+                    .any(|b| b.is_at(bank, instruction_addr) && b.enabled)
+                {
                     ui.push_style_color(StyleColor::Text, [1.0, 0.4, 0.4, 1.0])
                 } else {
                     ui.push_style_color(StyleColor::Text, [0.7, 0.7, 0.7, 1.0])
                 };
+                let is_current = instruction_addr == imgui_debug.program_counter
+                    && bank == mmu.bank_of(instruction_addr);
                 if ui
                     .selectable_config(&addr_string)
-                    .selected(instruction_addr == imgui_debug.program_counter)
+                    .selected(is_current)
                     .build()
                 {
                     match imgui_debug
                         .breakpoints
                         .iter()
-                        .position(|&r| r == instruction_addr)
+                        .position(|b| b.is_at(bank, instruction_addr))
                     {
                         None => {
-                            imgui_debug.breakpoints.push(instruction_addr);
+                            imgui_debug
+                                .breakpoints
+                                .push(Breakpoint::at(Location::new(bank, instruction_addr)));
                         }
                         Some(idx) => {
                             imgui_debug.breakpoints.remove(idx);
                         }
                     }
                 }
+                if ui.is_item_clicked_with_button(MouseButton::Right) {
+                    imgui_debug.run_to_cursor = Some(instruction_addr);
+                }
+                if ui.is_item_clicked_with_button(MouseButton::Middle) {
+                    select_annotation(imgui_debug, bank, instruction_addr);
+                }
                 style.pop();
 
                 ui.same_line();
 
-                ui.text(disassembly_string);
+                match coverage {
+                    Some(flags) => ui.text_colored(coverage_colour(flags), disassembly_string),
+                    None => ui.text(disassembly_string),
+                }
 
                 if let Some(memory_comment) = dis::get_memory_comment(instruction_addr) {
                     ui.same_line();
                     ui.text_colored([0.5, 0.5, 0.5, 1.0], format!(" ; {memory_comment}"));
                 }
+                if let Some(annotation) = imgui_debug.annotations.get(bank, instruction_addr) {
+                    if !annotation.comment.is_empty() {
+                        ui.same_line();
+                        ui.text_colored(
+                            [0.5, 0.8, 0.5, 1.0],
+                            format!(" ; {}", annotation.comment),
+                        );
+                    }
+                }
 
-                instruction_addr =
-                    instruction_addr.wrapping_add(u16::from(instruction.get_length()));
+                instruction_addr = instruction_addr.wrapping_add(u16::from(instruction.length));
             }
         });
 }
 
-fn get_raw_instruction_debug_string(
-    instruction: &Instruction,
-    mmu: &Mmu,
-    instruction_addr: u16,
-) -> String {
-    format!(
-        "0x{:02X}{}",
-        mmu.read8_safe(instruction_addr),
-        match &instruction.arg {
-            None => String::new(),
-            Some(arg) => match arg {
-                InstructionArg::Data8 => format!(" 0x{:02X}", mmu.read8_safe(instruction_addr + 1)),
-                InstructionArg::Data16 => format!(
-                    " 0x{:02X} 0x{:02X}",
-                    mmu.read8_safe(instruction_addr + 1),
-                    mmu.read8_safe(instruction_addr + 2)
-                ),
-            },
+/// Start, stop and save the code/data log, and toggle colouring the disassembly by it.
+fn code_data_log_controls(mmu: &mut Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    let rom_length = mmu.cart.rom_length();
+    match &mut mmu.code_data_log {
+        None => {
+            if ui.button("Start code/data log") {
+                mmu.code_data_log = Some(CodeDataLog::new(rom_length));
+            }
+        }
+        Some(log) => {
+            ui.checkbox("Coverage", &mut imgui_debug.disassemble_coverage);
+            ui.same_line();
+            ui.text_colored([0.5, 0.5, 0.5, 1.0], log.coverage());
+
+            ui.input_text("CDL file", &mut imgui_debug.cdl_path).build();
+            if ui.button("Save") {
+                if let Err(e) = log.save(Path::new(&imgui_debug.cdl_path)) {
+                    error!("{}", e);
+                }
+            }
+            ui.same_line();
+            if ui.button("Load") {
+                match CodeDataLog::load_or_new(Path::new(&imgui_debug.cdl_path), rom_length) {
+                    Ok(loaded) => *log = loaded,
+                    Err(e) => error!("{}", e),
+                }
+            }
+            ui.same_line();
+            if ui.button("Clear") {
+                log.clear();
+            }
+            ui.same_line();
+            if ui.button("Stop") {
+                mmu.code_data_log = None;
+            }
         }
-    )
+    }
+}
+
+/// Executed code is green, data read from the ROM is blue and bytes never touched are dimmed.
+fn coverage_colour(flags: CdlFlags) -> [f32; 4] {
+    if flags.contains(CdlFlags::OPCODE) {
+        [0.5, 0.9, 0.5, 1.0]
+    } else if flags.intersects(CdlFlags::DATA | CdlFlags::DMA) {
+        [0.5, 0.7, 1.0, 1.0]
+    } else if flags.is_empty() {
+        [0.45, 0.45, 0.45, 1.0]
+    } else {
+        [1.0, 1.0, 1.0, 1.0]
+    }
+}
+
+/// A checkbox to pin a view to a ROM bank rather than the one mapped, and the bank to pin it to.
+pub(super) fn bank_selector(ui: &Ui, id: &str, mmu: &Mmu, pinned: &mut bool, bank: &mut i32) {
+    ui.checkbox(format!("Pin bank{id}"), pinned);
+    if *pinned {
+        ui.same_line();
+        ui.set_next_item_width(80.0);
+        ui.input_int(format!("Bank{id}"), bank)
+            .chars_hexadecimal(true)
+            .build();
+        *bank = (*bank).clamp(0, i32::from(mmu.cart.rom_banks().max(1)) - 1);
+    } else {
+        ui.same_line();
+        ui.text_colored(
+            [0.5, 0.5, 0.5, 1.0],
+            format!("Mapped ROM bank {:02X}", mmu.cart.rom_bank()),
+        );
+    }
+}
+
+fn get_raw_instruction_debug_string(instruction: &Opcode, mmu: &Mmu, location: Location) -> String {
+    (0..u16::from(instruction.length))
+        .map(|offset| {
+            format!(
+                "0x{:02X}",
+                mmu.read_location(location.with_addr(location.addr.wrapping_add(offset)))
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
 }
 
 fn get_instruction_debug_string(
-    instruction: &Instruction,
+    instruction: &Opcode,
     read_args: bool,
     mmu: &Mmu,
-    instruction_addr: u16,
+    annotations: &Annotations,
+    symbols: &SymbolTable,
+    location: Location,
 ) -> String {
     if read_args {
-        match &instruction.arg {
-            None => String::from(instruction.name),
-            Some(arg) => match arg {
-                InstructionArg::Data8 => {
-                    let formatted_arg = format!("0x{:02X}", mmu.read8_safe(instruction_addr + 1));
-                    let formatted_addr_arg =
-                        format!("0xFF00 + 0x{:02X}", mmu.read8_safe(instruction_addr + 1));
-                    String::from(instruction.name)
-                        .replace("d8", formatted_arg.as_str())
-                        .replace("r8", formatted_arg.as_str())
-                        .replace("a8", formatted_addr_arg.as_str())
-                }
-                InstructionArg::Data16 => {
-                    let formatted_arg = format!("0x{:04X}", mmu.read16_safe(instruction_addr + 1));
-                    String::from(instruction.name)
-                        .replace("d16", formatted_arg.as_str())
-                        .replace("a16", formatted_arg.as_str())
+        let arg_location = location.with_addr(location.addr.wrapping_add(1));
+        let d8 = mmu.read_location(arg_location);
+        let d16 =
+            u16::from(mmu.read_location(arg_location.with_addr(arg_location.addr.wrapping_add(1))))
+                << 8
+                | u16::from(d8);
+        match &instruction.operand {
+            None => String::from(instruction.mnemonic),
+            Some(arg) => {
+                match arg {
+                    Operand::Data8 => String::from(instruction.mnemonic)
+                        .replace("d8", format!("0x{d8:02X}").as_str()),
+                    Operand::Signed8 => String::from(instruction.mnemonic)
+                        .replace("r8", format!("0x{d8:02X}").as_str()),
+                    Operand::Address8 => String::from(instruction.mnemonic)
+                        .replace("a8", format!("0xFF00 + 0x{d8:02X}").as_str()),
+                    Operand::Data16 => String::from(instruction.mnemonic).replace(
+                        "d16",
+                        &address_operand(location.with_addr(d16), mmu, annotations, symbols),
+                    ),
+                    Operand::Address16 => String::from(instruction.mnemonic).replace(
+                        "a16",
+                        &address_operand(location.with_addr(d16), mmu, annotations, symbols),
+                    ),
                 }
-            },
+            }
         }
     } else {
-        String::from(instruction.name)
+        String::from(instruction.mnemonic)
+    }
+}
+
+/// A 16-bit operand as its label if it has one, otherwise in hex. Targets in the same switchable
+/// region as the instruction are looked up in its bank, anything else in the bank mapped there.
+fn address_operand(
+    target: Location,
+    mmu: &Mmu,
+    annotations: &Annotations,
+    symbols: &SymbolTable,
+) -> String {
+    match annotations.label_in_bank(mmu.bank_at(target), target.addr, symbols) {
+        Some(label) => label.to_string(),
+        None => format!("0x{:04X}", target.addr),
     }
 }
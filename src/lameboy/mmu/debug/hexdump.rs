@@ -1,8 +1,12 @@
 use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::mmu::location::Location;
 use crate::lameboy::mmu::Mmu;
-use imgui::{Condition, StyleColor, Ui};
+use imgui::{Condition, MouseButton, StyleColor, Ui};
 
-pub fn hexdump_window(mmu: &Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+use super::annotations::select_annotation;
+use super::disassembly::bank_selector;
+
+pub fn hexdump_window(mmu: &mut Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
     ui.window("MMU - dump")
         .size([260.0, 140.0], Condition::FirstUseEver)
         .resizable(true)
@@ -12,6 +16,17 @@ pub fn hexdump_window(mmu: &Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
             ui.input_int("Addr", &mut imgui_debug.dump_memory_addr)
                 .chars_hexadecimal(true)
                 .build();
+            bank_selector(
+                ui,
+                "##dump",
+                mmu,
+                &mut imgui_debug.dump_bank_pinned,
+                &mut imgui_debug.dump_bank,
+            );
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Click to toggle a watchpoint, right click to annotate",
+            );
             ui.separator();
 
             let bytes_per_row = 16;
@@ -33,17 +48,29 @@ pub fn hexdump_window(mmu: &Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
             }
 
             let mut selected_mem_ptr = None;
+            let mut annotate_mem_ptr = None;
             for row in 0..=(context_size * 2) {
                 let row_addr = memory_addr_low + row * bytes_per_row;
 
-                ui.text_colored([0.7, 0.7, 0.7, 1.0], format!("[0x{row_addr:04X}]"));
+                let row_location = if imgui_debug.dump_bank_pinned {
+                    Location::new(imgui_debug.dump_bank as u16, row_addr)
+                } else {
+                    Location::from(row_addr)
+                };
+                let bank = mmu.bank_at(row_location);
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], format!("[{bank:02X}:{row_addr:04X}]"));
 
                 for offset in 0..bytes_per_row {
                     let mem_ptr = row_addr + offset;
+                    let annotation = imgui_debug.annotations.get(bank, mem_ptr);
                     let colour = if mem_ptr == dump_memory_addr {
                         [0.5, 1.0, 0.5, 1.0]
-                    } else if imgui_debug.memory_breakpoints.contains(&mem_ptr) {
+                    } else if mmu.watchpoints.watches_read(mem_ptr)
+                        || mmu.watchpoints.watches_write(mem_ptr)
+                    {
                         [1.0, 0.4, 0.4, 1.0]
+                    } else if annotation.is_some() {
+                        [0.9, 0.8, 0.4, 1.0]
                     } else {
                         [0.8, 0.8, 0.8, 1.0]
                     };
@@ -52,29 +79,32 @@ pub fn hexdump_window(mmu: &Mmu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
 
                     let style = ui.push_style_color(StyleColor::Text, colour);
                     if ui
-                        .selectable_config(format!("{:02X}", mmu.read8_safe(mem_ptr)))
+                        .selectable_config(format!(
+                            "{:02X}",
+                            mmu.read_location(row_location.with_addr(mem_ptr))
+                        ))
                         .size([11.0, 11.0])
                         .build()
                     {
                         selected_mem_ptr = Some(mem_ptr);
                     }
+                    if ui.is_item_clicked_with_button(MouseButton::Right) {
+                        annotate_mem_ptr = Some((bank, mem_ptr));
+                    }
                     style.pop();
+                    if let Some(annotation) = annotation {
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(format!("{} {}", annotation.label, annotation.comment));
+                        }
+                    }
                 }
             }
 
             if let Some(mem_ptr) = selected_mem_ptr {
-                match imgui_debug
-                    .memory_breakpoints
-                    .iter()
-                    .position(|&r| r == mem_ptr)
-                {
-                    None => {
-                        imgui_debug.memory_breakpoints.push(mem_ptr);
-                    }
-                    Some(idx) => {
-                        imgui_debug.memory_breakpoints.remove(idx);
-                    }
-                }
+                mmu.watchpoints.toggle_address(mem_ptr);
+            }
+            if let Some((bank, mem_ptr)) = annotate_mem_ptr {
+                select_annotation(imgui_debug, bank, mem_ptr);
             }
         });
 }
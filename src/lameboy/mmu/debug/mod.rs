@@ -1,6 +1,8 @@
+pub use crate::lameboy::mmu::debug::annotations::annotation_window;
 pub use crate::lameboy::mmu::debug::disassembly::disassembly_window;
 pub use crate::lameboy::mmu::debug::hexdump::hexdump_window;
 
+mod annotations;
 mod disassembly;
 mod hexdump;
 
@@ -21,7 +23,7 @@ impl ImguiDebuggable for Mmu {
                 ui.text(format!(
                     "[0x{:04X}] = 0x{:02x}",
                     imgui_debug.input_memory_addr,
-                    self.read8(imgui_debug.input_memory_addr as u16)
+                    self.read8_safe(imgui_debug.input_memory_addr as u16)
                 ));
                 ui.separator();
                 ui.input_int("Value", &mut imgui_debug.input_memory_value)
@@ -37,5 +39,6 @@ impl ImguiDebuggable for Mmu {
 
         hexdump_window(self, ui, imgui_debug);
         disassembly_window(self, ui, imgui_debug);
+        annotation_window(ui, imgui_debug);
     }
 }
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use crate::lameboy::mmu::{
+    CART_RAM_BANK_X_END, CART_RAM_BANK_X_START, CART_ROM_BANK_X_END, CART_ROM_BANK_X_START,
+    RAM_BANK_X_END, RAM_BANK_X_START,
+};
+
+/// An address, optionally pinned to one bank of a switchable region rather than whichever bank
+/// is mapped there. Written `bank:address` in hex, as in RGBDS symbol files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub bank: Option<u16>,
+    pub addr: u16,
+}
+
+impl Location {
+    /// A location in a bank, dropping the bank for addresses that are never banked.
+    pub fn new(bank: u16, addr: u16) -> Location {
+        Location {
+            bank: is_banked(addr).then_some(bank),
+            addr,
+        }
+    }
+
+    /// Parse `bank:address`, or just an address, with an optional `0x` or `$` prefix.
+    pub fn parse(text: &str) -> Result<Location, String> {
+        let text = text.trim();
+        let parse_hex = |hex: &str| {
+            let digits = hex
+                .strip_prefix("0x")
+                .or_else(|| hex.strip_prefix('$'))
+                .unwrap_or(hex);
+            u16::from_str_radix(digits, 16).map_err(|_| format!("Invalid location '{text}'"))
+        };
+
+        match text.split_once(':') {
+            Some((bank, addr)) => Ok(Location::new(parse_hex(bank)?, parse_hex(addr)?)),
+            None => Ok(Location::from(parse_hex(text)?)),
+        }
+    }
+
+    /// Another address, keeping the bank if it falls in the same switchable region.
+    pub fn with_addr(&self, addr: u16) -> Location {
+        Location {
+            bank: self
+                .bank
+                .filter(|_| banked_region(addr) == banked_region(self.addr)),
+            addr,
+        }
+    }
+}
+
+impl From<u16> for Location {
+    fn from(addr: u16) -> Location {
+        Location { bank: None, addr }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bank {
+            Some(bank) => write!(f, "{bank:02X}:{:04X}", self.addr),
+            None => write!(f, "0x{:04X}", self.addr),
+        }
+    }
+}
+
+/// Which switchable region an address is in, if any.
+fn banked_region(addr: u16) -> Option<u8> {
+    match addr {
+        CART_ROM_BANK_X_START..=CART_ROM_BANK_X_END => Some(0),
+        CART_RAM_BANK_X_START..=CART_RAM_BANK_X_END => Some(1),
+        RAM_BANK_X_START..=RAM_BANK_X_END => Some(2),
+        _ => None,
+    }
+}
+
+/// Whether an address is in a region with switchable banks.
+pub fn is_banked(addr: u16) -> bool {
+    banked_region(addr).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(Ok(Location::new(3, 0x4000)), Location::parse("03:4000"));
+        assert_eq!("03:4000", Location::new(3, 0x4000).to_string());
+        assert_eq!(Ok(Location::from(0x4000)), Location::parse("$4000"));
+        assert_eq!("0x4000", Location::from(0x4000).to_string());
+
+        // Bank 0 and WRAM 0 are never switched
+        assert_eq!(Ok(Location::from(0x0150)), Location::parse("0x00:0x0150"));
+        assert_eq!(None, Location::new(2, 0xC000).bank);
+        assert!(Location::parse("03:zz").is_err());
+
+        let location = Location::new(3, 0x4000);
+        assert_eq!(Some(3), location.with_addr(0x7FFF).bank);
+        assert_eq!(None, location.with_addr(0xA000).bank);
+    }
+
+    #[test]
+    fn reads_unmapped_banks() {
+        // 64KB ROM, four banks
+        let mut rom = vec![0x00; 0x10000];
+        rom[0x0148] = 0x01;
+        rom[0x4010] = 0x11;
+        rom[2 * 0x4000 + 0x0010] = 0x22;
+        let mut lameboy = Lameboy::new(rom);
+        let mmu = lameboy.get_mmu();
+
+        assert_eq!(0x11, mmu.read_location(Location::from(0x4010)));
+        assert_eq!(0x11, mmu.read_location(Location::new(1, 0x4010)));
+        assert_eq!(0x22, mmu.read_location(Location::new(2, 0x4010)));
+        assert_eq!(0xFF, mmu.read_location(Location::new(9, 0x4010)));
+        assert_eq!(2, mmu.bank_at(Location::new(2, 0x4010)));
+        assert_eq!(1, mmu.bank_at(Location::from(0x4010)));
+    }
+}
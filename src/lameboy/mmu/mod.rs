@@ -1,9 +1,19 @@
 use crate::lameboy::cart::Cart;
+use crate::lameboy::cdl::{CdlFlags, CodeDataLog};
+use crate::lameboy::history::MemoryAccess;
 use crate::lameboy::joypad::Joypad;
+use crate::lameboy::mmu::location::Location;
 use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::mmu::watchpoint::Watchpoints;
 use crate::lameboy::ppu::Ppu;
+use crate::lameboy::serial::Serial;
+use crate::lameboy::state::{StateReader, StateWriter, Stateful};
+use crate::lameboy::timeline::{EventKind, Timeline};
 
+pub mod bus;
+pub mod location;
 pub mod mmuobject;
+pub mod watchpoint;
 
 mod debug;
 
@@ -37,6 +47,7 @@ pub struct Mmu {
     pub cart: Cart,
     pub ppu: Ppu,
     pub joypad: Joypad,
+    pub serial: Serial,
     /// Work RAM 0 [0xC000 - 0xCFFF]
     wram0: Box<[u8; 0x1000]>,
     /// Work RAM 1 [0xD000 - 0xDFFF] (Bank 1-7 in CGB Mode)
@@ -49,24 +60,34 @@ pub struct Mmu {
     hram: Box<[u8; 0x007F]>,
     /// Interrupt Enable Register [0xFFFF]
     ier: u8,
-    pub memory_breakpoints: Vec<u16>,
-    pub breakpoint_hit: u16,
+    pub watchpoints: Watchpoints,
+    /// Record every read and write into `accesses`, for the execution history.
+    pub record_accesses: bool,
+    pub accesses: Vec<MemoryAccess>,
+    /// Mark how every ROM byte is accessed, while there's a log.
+    pub code_data_log: Option<CodeDataLog>,
+    /// Events in the current and last frame, logged while it's enabled.
+    pub timeline: Timeline,
 }
 
 impl Mmu {
-    pub fn new(cart: Cart, ppu: Ppu, joypad: Joypad) -> Mmu {
+    pub fn new(cart: Cart, ppu: Ppu, joypad: Joypad, serial: Serial) -> Mmu {
         Mmu {
             cart,
             ppu,
             joypad,
+            serial,
             wram0: Box::new([0; 0x1000]),
             wram1: Box::new([0; 0x1000]),
             unusable: 0xFF,
             io: Box::new([0; 0x0080]),
             hram: Box::new([0; 0x007F]),
             ier: 0x00,
-            memory_breakpoints: Vec::new(),
-            breakpoint_hit: 0x0000,
+            watchpoints: Watchpoints::new(),
+            record_accesses: false,
+            accesses: Vec::new(),
+            code_data_log: None,
+            timeline: Timeline::new(),
         }
     }
 
@@ -105,11 +126,92 @@ impl Mmu {
     }
 
     pub fn read8(&mut self, addr: u16) -> u8 {
-        if self.memory_breakpoints.contains(&addr) {
-            self.breakpoint_hit = addr;
+        self.read8_as(addr, CdlFlags::DATA)
+    }
+
+    /// Read a byte of an instruction on behalf of the CPU.
+    pub fn fetch8(&mut self, addr: u16, opcode: bool) -> u8 {
+        let usage = if opcode {
+            CdlFlags::OPCODE
+        } else {
+            CdlFlags::OPERAND
+        };
+        self.read8_as(addr, usage)
+    }
+
+    /// Read a byte, logging how it was used if it comes from the ROM.
+    fn read8_as(&mut self, addr: u16, usage: CdlFlags) -> u8 {
+        let value = self.read8_safe(addr);
+
+        if self.code_data_log.is_some() {
+            if let Some(offset) = self.rom_offset(Location::from(addr)) {
+                if let Some(log) = &mut self.code_data_log {
+                    log.mark(offset, usage);
+                }
+            }
+        }
+
+        if self.watchpoints.watches_read(addr) {
+            self.watchpoints.on_read(addr, value);
+        }
+        if self.record_accesses {
+            self.accesses.push(MemoryAccess {
+                addr,
+                value,
+                write: false,
+            });
+        }
+
+        value
+    }
+
+    /// The bank currently mapped at an address, numbered as in RGBDS symbol files, which is 0 for
+    /// regions that are never banked.
+    pub fn bank_of(&self, addr: u16) -> u16 {
+        match addr {
+            CART_ROM_BANK_X_START..=CART_ROM_BANK_X_END => self.cart.rom_bank(),
+            CART_RAM_BANK_X_START..=CART_RAM_BANK_X_END => self.cart.ram_bank(),
+            RAM_BANK_X_START..=RAM_BANK_X_END => 1,
+            _ => 0,
         }
+    }
+
+    /// Read a location without side effects, straight from its bank if it has one rather than
+    /// whichever bank is mapped.
+    pub fn read_location(&self, location: Location) -> u8 {
+        let Some(bank) = location.bank else {
+            return self.read8_safe(location.addr);
+        };
+
+        match location.addr {
+            CART_ROM_BANK_X_START..=CART_ROM_BANK_X_END => {
+                self.cart.read_rom_bank(bank, location.addr)
+            }
+            CART_RAM_BANK_X_START..=CART_RAM_BANK_X_END => {
+                self.cart.read_ram_bank(bank, location.addr)
+            }
+            // Only CGB has more than one switchable WRAM bank
+            _ => self.read8_safe(location.addr),
+        }
+    }
 
-        self.read8_safe(addr)
+    /// Where a location is in the ROM file, taking the mapped bank if it isn't pinned to one.
+    pub fn rom_offset(&self, location: Location) -> Option<usize> {
+        let addr = usize::from(location.addr);
+        let offset = match location.addr {
+            CART_ROM_BANK_0_START..=CART_ROM_BANK_0_END => addr,
+            CART_ROM_BANK_X_START..=CART_ROM_BANK_X_END => {
+                usize::from(self.bank_at(location)) * 0x4000 + addr - 0x4000
+            }
+            _ => return None,
+        };
+
+        Some(offset).filter(|&offset| offset < self.cart.rom_length())
+    }
+
+    /// The bank an address is read from in a location, the one mapped there if it isn't pinned.
+    pub fn bank_at(&self, location: Location) -> u16 {
+        location.bank.unwrap_or_else(|| self.bank_of(location.addr))
     }
 
     pub fn read8_safe(&self, addr: u16) -> u8 {
@@ -142,8 +244,9 @@ impl Mmu {
             UNUSABLE_START..=UNUSABLE_END => self.unusable,
             IO_PORTS_START..=IO_PORTS_END => match addr {
                 0xFF00 => self.joypad.read8(addr),
+                0xFF01..=0xFF02 => self.serial.read8(addr),
                 0xFF40..=0xFF4B => self.ppu.read8(addr),
-                0xFF01..=0xFF3F | 0xFF4C..=0xFF7F => self.io[(addr as usize) & 0x00FF],
+                0xFF03..=0xFF3F | 0xFF4C..=0xFF7F => self.io[(addr as usize) & 0x00FF],
                 _ => panic!("Attempted to access [RD] memory from an invalid address: {addr:#X}"),
             },
             HIGH_RAM_START..=HIGH_RAM_END => self.hram[((addr as usize) & 0x00FF) - 0x0080],
@@ -152,8 +255,28 @@ impl Mmu {
     }
 
     pub fn write8(&mut self, addr: u16, data: u8) {
-        if self.memory_breakpoints.contains(&addr) {
-            self.breakpoint_hit = addr;
+        if self.watchpoints.watches_write(addr) {
+            let old_value = self.read8_safe(addr);
+            self.watchpoints.on_write(addr, old_value, data);
+        }
+        if self.record_accesses {
+            self.accesses.push(MemoryAccess {
+                addr,
+                value: data,
+                write: true,
+            });
+        }
+
+        if self.timeline.enabled {
+            match addr {
+                CART_ROM_BANK_0_START..=CART_ROM_BANK_X_END => {
+                    self.log_event(EventKind::MbcWrite { addr, value: data })
+                }
+                0xFF46 => self.log_event(EventKind::Dma(data)),
+                // APU and PPU registers
+                0xFF10..=0xFF4B => self.log_event(EventKind::RegisterWrite { addr, value: data }),
+                _ => (),
+            }
         }
 
         match addr {
@@ -182,16 +305,17 @@ impl Mmu {
             IO_PORTS_START..=IO_PORTS_END => {
                 match addr {
                     0xFF00 => self.joypad.write8(addr, data),
+                    0xFF01..=0xFF02 => self.serial.write8(addr, data),
                     0xFF46 => {
                         // DMA
                         let source_addr = (u16::from(data)) << 8;
                         for i in 0..160 {
-                            let val = self.read8(source_addr + i);
+                            let val = self.read8_as(source_addr + i, CdlFlags::DMA);
                             self.write8(0xFE00 + i, val);
                         }
                     }
                     0xFF40..=0xFF45 | 0xFF47..=0xFF4B => self.ppu.write8(addr, data),
-                    0xFF01..=0xFF3F | 0xFF4C..=0xFF7F => self.io[(addr as usize) & 0x00FF] = data,
+                    0xFF03..=0xFF3F | 0xFF4C..=0xFF7F => self.io[(addr as usize) & 0x00FF] = data,
                     _ => {
                         panic!("Attempted to access [WR] memory from an invalid address: {addr:#X}")
                     }
@@ -215,4 +339,54 @@ impl Mmu {
 
         ((u16::from(high)) << 8) | (u16::from(low))
     }
+
+    /// Set bits in the interrupt flag register (IF) on behalf of the hardware, without it counting
+    /// as a CPU access.
+    pub fn request_interrupts(&mut self, int_flags: u8) {
+        let raised = int_flags & !self.io[0x0F];
+        for bit in 0..5 {
+            if raised & (1 << bit) != 0 {
+                self.log_event(EventKind::InterruptRequest(1 << bit));
+            }
+        }
+        self.io[0x0F] |= int_flags;
+    }
+
+    /// Log an event on the timeline at the PPU's current position in the frame.
+    pub fn log_event(&mut self, kind: EventKind) {
+        let (ly, dot) = self.ppu.position();
+        self.timeline.log(ly, dot, kind);
+    }
+
+    /// Both work RAM banks [0xC000 - 0xDFFF], in address order.
+    pub fn get_wram(&self) -> [&[u8]; 2] {
+        [self.wram0.as_ref(), self.wram1.as_ref()]
+    }
+}
+
+/// The cart is left out as the only supported cart type has no state of its own.
+impl Stateful for Mmu {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.bytes(&self.wram0[..]);
+        state.bytes(&self.wram1[..]);
+        state.u8(self.unusable);
+        state.bytes(&self.io[..]);
+        state.bytes(&self.hram[..]);
+        state.u8(self.ier);
+        self.ppu.save_state(state);
+        self.joypad.save_state(state);
+        self.serial.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.bytes(&mut self.wram0[..])?;
+        state.bytes(&mut self.wram1[..])?;
+        self.unusable = state.u8()?;
+        state.bytes(&mut self.io[..])?;
+        state.bytes(&mut self.hram[..])?;
+        self.ier = state.u8()?;
+        self.ppu.load_state(state)?;
+        self.joypad.load_state(state)?;
+        self.serial.load_state(state)
+    }
 }
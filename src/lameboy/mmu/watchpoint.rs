@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+/// How many recent hits are kept for display.
+const HIT_LOG_SIZE: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    /// Writes that change the stored value.
+    Change,
+    ReadWrite,
+}
+
+impl WatchKind {
+    pub const ALL: [WatchKind; 4] = [
+        WatchKind::Read,
+        WatchKind::Write,
+        WatchKind::Change,
+        WatchKind::ReadWrite,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WatchKind::Read => "read",
+            WatchKind::Write => "write",
+            WatchKind::Change => "change",
+            WatchKind::ReadWrite => "read/write",
+        }
+    }
+
+    fn watches_read(&self) -> bool {
+        matches!(self, WatchKind::Read | WatchKind::ReadWrite)
+    }
+
+    fn watches_write(&self) -> bool {
+        !matches!(self, WatchKind::Read)
+    }
+}
+
+/// A watch on an inclusive range of addresses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16,
+    pub kind: WatchKind,
+    /// Only record hits in the log rather than stopping execution.
+    pub log_only: bool,
+    pub enabled: bool,
+}
+
+impl Watchpoint {
+    pub fn new(start: u16, end: u16, kind: WatchKind) -> Watchpoint {
+        Watchpoint {
+            start: start.min(end),
+            end: start.max(end),
+            kind,
+            log_only: false,
+            enabled: true,
+        }
+    }
+
+    pub fn contains(&self, addr: u16) -> bool {
+        (self.start..=self.end).contains(&addr)
+    }
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "0x{:04X} {}", self.start, self.kind.name())?;
+        } else {
+            write!(
+                f,
+                "0x{:04X}-0x{:04X} {}",
+                self.start,
+                self.end,
+                self.kind.name()
+            )?;
+        }
+        if self.log_only {
+            write!(f, " (log)")?;
+        }
+        Ok(())
+    }
+}
+
+/// A recorded memory access that matched a watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchpointHit {
+    pub addr: u16,
+    /// PC of the instruction that made the access.
+    pub pc: u16,
+    pub write: bool,
+    pub old_value: u8,
+    pub new_value: u8,
+}
+
+impl fmt::Display for WatchpointHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.write {
+            write!(
+                f,
+                "PC 0x{:04X} wrote [0x{:04X}] 0x{:02X} -> 0x{:02X}",
+                self.pc, self.addr, self.old_value, self.new_value
+            )
+        } else {
+            write!(
+                f,
+                "PC 0x{:04X} read [0x{:04X}] = 0x{:02X}",
+                self.pc, self.addr, self.new_value
+            )
+        }
+    }
+}
+
+/// The set of watchpoints checked by the MMU on every CPU access.
+///
+/// Every enabled watchpoint is flattened into a bitmap per access type, so the per-access check
+/// is a single bit test and the watchpoint list is only searched once an address is known to be
+/// watched.
+pub struct Watchpoints {
+    watchpoints: Vec<Watchpoint>,
+    read_bits: Box<[u64; 1024]>,
+    write_bits: Box<[u64; 1024]>,
+    /// PC of the instruction currently executing, kept up to date by the emulator.
+    pub pc: u16,
+    pub log: VecDeque<WatchpointHit>,
+    triggered: Option<WatchpointHit>,
+}
+
+impl Watchpoints {
+    pub fn new() -> Watchpoints {
+        Watchpoints {
+            watchpoints: Vec::new(),
+            read_bits: Box::new([0; 1024]),
+            write_bits: Box::new([0; 1024]),
+            pc: 0,
+            log: VecDeque::with_capacity(HIT_LOG_SIZE),
+            triggered: None,
+        }
+    }
+
+    pub fn list(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    pub fn add(&mut self, watchpoint: Watchpoint) {
+        self.watchpoints.push(watchpoint);
+        self.rebuild();
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.watchpoints.remove(index);
+        self.rebuild();
+    }
+
+    pub fn clear(&mut self) {
+        self.watchpoints.clear();
+        self.rebuild();
+    }
+
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.watchpoints[index].enabled = enabled;
+        self.rebuild();
+    }
+
+    /// Add a read/write watchpoint on a single address, or remove it if one already exists.
+    pub fn toggle_address(&mut self, addr: u16) {
+        match self
+            .watchpoints
+            .iter()
+            .position(|w| w.start == addr && w.end == addr)
+        {
+            Some(index) => self.remove(index),
+            None => self.add(Watchpoint::new(addr, addr, WatchKind::ReadWrite)),
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.read_bits.fill(0);
+        self.write_bits.fill(0);
+
+        for watchpoint in self.watchpoints.iter().filter(|w| w.enabled) {
+            for addr in watchpoint.start..=watchpoint.end {
+                let (word, bit) = (addr as usize / 64, 1 << (addr % 64));
+                if watchpoint.kind.watches_read() {
+                    self.read_bits[word] |= bit;
+                }
+                if watchpoint.kind.watches_write() {
+                    self.write_bits[word] |= bit;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn watches_read(&self, addr: u16) -> bool {
+        self.read_bits[addr as usize / 64] & (1 << (addr % 64)) != 0
+    }
+
+    #[inline]
+    pub fn watches_write(&self, addr: u16) -> bool {
+        self.write_bits[addr as usize / 64] & (1 << (addr % 64)) != 0
+    }
+
+    pub fn on_read(&mut self, addr: u16, value: u8) {
+        self.record(WatchpointHit {
+            addr,
+            pc: self.pc,
+            write: false,
+            old_value: value,
+            new_value: value,
+        });
+    }
+
+    pub fn on_write(&mut self, addr: u16, old_value: u8, new_value: u8) {
+        self.record(WatchpointHit {
+            addr,
+            pc: self.pc,
+            write: true,
+            old_value,
+            new_value,
+        });
+    }
+
+    fn record(&mut self, hit: WatchpointHit) {
+        let mut matched = false;
+        let mut should_break = false;
+
+        for watchpoint in self
+            .watchpoints
+            .iter()
+            .filter(|w| w.enabled && w.contains(hit.addr))
+        {
+            let matches = match watchpoint.kind {
+                WatchKind::Read => !hit.write,
+                WatchKind::Write => hit.write,
+                WatchKind::Change => hit.write && hit.old_value != hit.new_value,
+                WatchKind::ReadWrite => true,
+            };
+            matched |= matches;
+            should_break |= matches && !watchpoint.log_only;
+        }
+
+        if !matched {
+            return;
+        }
+
+        debug!("Watchpoint: {}", hit);
+        if self.log.len() == HIT_LOG_SIZE {
+            self.log.pop_front();
+        }
+        self.log.push_back(hit);
+
+        if should_break && self.triggered.is_none() {
+            self.triggered = Some(hit);
+        }
+    }
+
+    /// Take the first hit that should stop execution since this was last called.
+    pub fn take_triggered(&mut self) -> Option<WatchpointHit> {
+        self.triggered.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn bitmap_tracks_enabled_ranges() {
+        let mut watchpoints = Watchpoints::new();
+        watchpoints.add(Watchpoint::new(0xC010, 0xC000, WatchKind::Write));
+        watchpoints.add(Watchpoint::new(0xFFFF, 0xFFFF, WatchKind::Read));
+
+        assert!(watchpoints.watches_write(0xC000));
+        assert!(watchpoints.watches_write(0xC010));
+        assert!(!watchpoints.watches_write(0xC011));
+        assert!(!watchpoints.watches_read(0xC008));
+        assert!(watchpoints.watches_read(0xFFFF));
+
+        watchpoints.set_enabled(0, false);
+        assert!(!watchpoints.watches_write(0xC000));
+
+        watchpoints.remove(1);
+        assert!(!watchpoints.watches_read(0xFFFF));
+    }
+
+    #[test]
+    fn change_only_fires_on_new_value() {
+        let mut watchpoints = Watchpoints::new();
+        watchpoints.add(Watchpoint::new(0xC000, 0xC000, WatchKind::Change));
+        watchpoints.pc = 0x0150;
+
+        watchpoints.on_write(0xC000, 0x05, 0x05);
+        assert_eq!(None, watchpoints.take_triggered());
+
+        watchpoints.on_write(0xC000, 0x05, 0x06);
+        assert_eq!(
+            Some(WatchpointHit {
+                addr: 0xC000,
+                pc: 0x0150,
+                write: true,
+                old_value: 0x05,
+                new_value: 0x06,
+            }),
+            watchpoints.take_triggered()
+        );
+        assert_eq!(None, watchpoints.take_triggered());
+    }
+
+    #[test]
+    fn log_only_does_not_trigger() {
+        let mut watchpoints = Watchpoints::new();
+        let mut watchpoint = Watchpoint::new(0xC000, 0xC0FF, WatchKind::ReadWrite);
+        watchpoint.log_only = true;
+        watchpoints.add(watchpoint);
+
+        watchpoints.on_read(0xC080, 0x12);
+
+        assert_eq!(None, watchpoints.take_triggered());
+        assert_eq!(1, watchpoints.log.len());
+    }
+
+    #[test]
+    fn toggle_address() {
+        let mut watchpoints = Watchpoints::new();
+
+        watchpoints.toggle_address(0xD000);
+        assert!(watchpoints.watches_read(0xD000) && watchpoints.watches_write(0xD000));
+
+        watchpoints.toggle_address(0xD000);
+        assert!(watchpoints.list().is_empty());
+    }
+
+    #[test]
+    fn records_accessing_pc() {
+        // ld a, 0x05; ld [0xC000], a; nop
+        let mut lameboy = Lameboy::with_program(&[0x3E, 0x05, 0xEA, 0x00, 0xC0]);
+        lameboy
+            .get_mmu()
+            .watchpoints
+            .add(Watchpoint::new(0xC000, 0xC000, WatchKind::Write));
+
+        lameboy.run_frame();
+
+        assert_eq!(0x0105, lameboy.get_cpu().registers.pc);
+        assert_eq!(
+            Some(&WatchpointHit {
+                addr: 0xC000,
+                pc: 0x0102,
+                write: true,
+                old_value: 0x00,
+                new_value: 0x05,
+            }),
+            lameboy.get_mmu().watchpoints.log.back()
+        );
+    }
+}
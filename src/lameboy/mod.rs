@@ -1,42 +1,87 @@
+use crate::lameboy::breakpoint::check_breakpoints;
 use crate::lameboy::cart::Cart;
 use crate::lameboy::cpu::Cpu;
+use crate::lameboy::history::{ExecutionHistory, HistoryEntry, DEFAULT_HISTORY_SIZE};
 use crate::lameboy::joypad::Joypad;
+use crate::lameboy::mmu::watchpoint::Watchpoints;
 use crate::lameboy::mmu::Mmu;
 use crate::lameboy::ppu::Ppu;
+use crate::lameboy::profiler::{ProfileStart, Profiler};
+use crate::lameboy::rewind::RewindBuffer;
+use crate::lameboy::serial::Serial;
+use crate::lameboy::state::{StateReader, StateWriter, Stateful, STATE_VERSION};
+use crate::lameboy::stepping::{StopCondition, StopReason};
+use crate::lameboy::trace::{format_trace_line, TraceWriter};
 
+pub mod breakpoint;
 pub mod cart;
+pub mod cdl;
 pub mod cpu;
+pub mod expression;
+pub mod history;
 pub mod interrupts;
+pub mod io_registers;
 pub mod joypad;
 pub mod mmu;
 pub mod ppu;
+pub mod profiler;
+pub mod rewind;
+pub mod serial;
+pub mod state;
+pub mod stepping;
+pub mod timeline;
+pub mod trace;
+pub mod watch;
 
 mod debug;
 
 use crate::gui::imgui_debug_state::ImguiDebugState;
-use crate::gui::Gui;
+
+/// How many cycles the PPU takes to draw a full frame, including vblank.
+pub const CYCLES_PER_FRAME: u32 = 70224;
+
+/// How many cycles the PPU takes to draw a single scanline, including hblank.
+pub const CYCLES_PER_SCANLINE: u32 = 456;
 
 pub struct Lameboy {
     pub active: bool,
+    /// The rewind key is held, so step backwards through snapshots rather than running.
+    pub rewinding: bool,
     cpu: Cpu,
     running: bool,
     trace_count: i32,
+    trace: Option<TraceWriter>,
+    cycle_count: u64,
+    stop_condition: Option<StopCondition>,
+    stop_reason: Option<StopReason>,
+    history: ExecutionHistory,
+    rewind: RewindBuffer,
+    profiler: Profiler,
     pub debug: ImguiDebugState,
 }
 
 impl Lameboy {
-    pub fn new(data: Vec<u8>, gui: &Gui) -> Lameboy {
+    pub fn new(data: Vec<u8>) -> Lameboy {
         let joypad = Joypad::new();
+        let serial = Serial::new();
         let cart = Cart::new(data);
-        let ppu = Ppu::new(&gui.display);
-        let mmu = Mmu::new(cart, ppu, joypad);
+        let ppu = Ppu::new();
+        let mmu = Mmu::new(cart, ppu, joypad, serial);
         let cpu = Cpu::new(mmu);
 
         Lameboy {
             active: true,
+            rewinding: false,
             cpu,
             running: false,
             trace_count: 0,
+            trace: None,
+            cycle_count: 0,
+            stop_condition: None,
+            stop_reason: None,
+            history: ExecutionHistory::new(DEFAULT_HISTORY_SIZE),
+            rewind: RewindBuffer::new(),
+            profiler: Profiler::new(),
             debug: ImguiDebugState::new(),
         }
     }
@@ -47,64 +92,360 @@ impl Lameboy {
 
     pub fn run_frame(&mut self) {
         let mut t_clk: u32 = 0;
-        while t_clk < 70224 {
+        while t_clk < CYCLES_PER_FRAME {
+            // Stop emulator running once a stepping command has finished
+            if let Some(condition) = &mut self.stop_condition {
+                if condition.is_met(&self.cpu, self.cycle_count) {
+                    debug!("Stop condition met: {:?}", condition);
+                    self.stop();
+                    self.stop_reason = Some(StopReason::Condition);
+                    return;
+                }
+            }
+
             // Stop emulator running if the current PC is a breakpoint
-            let current_pc = self.get_cpu().registers.pc;
-            if self.debug.breakpoints.contains(&current_pc) {
+            let current_pc = self.cpu.registers.pc;
+            if check_breakpoints(&mut self.debug.breakpoints, current_pc, &self.cpu) {
                 debug!("Breakpoint hit: 0x{:04X}", current_pc);
-                self.running = false;
+                self.stop();
+                self.stop_reason = Some(StopReason::Breakpoint(current_pc));
+                self.export_history_on_break();
                 return;
             }
-            let breakpoint_hit = self.get_mmu().breakpoint_hit;
-            if breakpoint_hit != 0x0000 {
-                debug!("Memory Breakpoint hit: 0x{:04X}", breakpoint_hit);
-                self.running = false;
-                self.get_mmu().breakpoint_hit = 0x0000;
+            if let Some(hit) = self.get_mmu().watchpoints.take_triggered() {
+                debug!("Watchpoint hit: {}", hit);
+                self.stop();
+                self.stop_reason = Some(StopReason::Watchpoint(hit));
+                self.export_history_on_break();
                 return;
             }
-            self.get_mmu().memory_breakpoints = self.debug.memory_breakpoints.clone();
 
             // Step the emulator through a single opcode
             t_clk += u32::from(self.step());
         }
+
+        for watch in &mut self.debug.watches {
+            watch.sample(&self.cpu);
+        }
+
+        if self.rewind.frame_finished() {
+            let state = self.save_state();
+            self.rewind.push(self.cycle_count, state);
+        }
+    }
+
+    /// Save the state of the emulated machine.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut state = StateWriter::new();
+        state.u8(STATE_VERSION);
+        state.u64(self.cycle_count);
+        self.cpu.save_state(&mut state);
+
+        state.into_bytes()
+    }
+
+    /// Restore a state from `save_state`, dropping any execution history from after it.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let mut state = StateReader::new(data);
+        let version = state.u8()?;
+        if version != STATE_VERSION {
+            return Err(format!(
+                "State version {version} doesn't match {STATE_VERSION}"
+            ));
+        }
+
+        self.cycle_count = state.u64()?;
+        self.cpu.load_state(&mut state)?;
+        if !state.is_finished() {
+            return Err(String::from("State has unexpected data at the end"));
+        }
+
+        self.history.discard_from(self.cycle_count);
+        self.debug.program_counter = self.cpu.registers.pc;
+
+        Ok(())
+    }
+
+    pub fn get_rewind(&mut self) -> &mut RewindBuffer {
+        &mut self.rewind
+    }
+
+    /// Restore the newest snapshot from before now, returning false once there are none left.
+    pub fn rewind(&mut self) -> bool {
+        while let Some((cycle, state)) = self.rewind.pop() {
+            if cycle < self.cycle_count {
+                if let Err(e) = self.load_state(&state) {
+                    error!("Unable to rewind: {}", e);
+                    return false;
+                }
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Go back to the instruction before the current one.
+    pub fn reverse_step(&mut self) -> Result<(), String> {
+        self.rewind_to_cycle(self.cycle_count)
+    }
+
+    /// Go back to the last instruction that started before a cycle, by restoring the newest
+    /// snapshot from before it and executing forwards again.
+    pub fn rewind_to_cycle(&mut self, cycle: u64) -> Result<(), String> {
+        if self
+            .rewind
+            .oldest_cycle()
+            .is_none_or(|oldest| oldest >= cycle)
+        {
+            return Err(format!("No snapshot from before cycle {cycle}"));
+        }
+        self.stop();
+
+        let (snapshot_cycle, snapshot) = loop {
+            match self.rewind.pop() {
+                Some((snapshot_cycle, state)) if snapshot_cycle < cycle => {
+                    break (snapshot_cycle, state)
+                }
+                Some(_) => {}
+                None => unreachable!("Oldest snapshot is from before the cycle"),
+            }
+        };
+
+        // Count the instructions up to the cycle, then replay all but the last of them
+        self.load_state(&snapshot)?;
+        let instructions = self.count_instructions_to(cycle);
+
+        self.load_state(&snapshot)?;
+        for _ in 1..instructions {
+            self.step();
+        }
+        self.rewind.push(snapshot_cycle, snapshot);
+
+        Ok(())
+    }
+
+    /// Run up to a cycle, counting the instructions executed. The trace, profiler, history, CDL,
+    /// timeline and watchpoints are detached while it runs, so that only the replay after it is
+    /// recorded.
+    fn count_instructions_to(&mut self, cycle: u64) -> u32 {
+        let trace = self.trace.take();
+        let trace_count = std::mem::take(&mut self.trace_count);
+        let profiling = std::mem::replace(&mut self.profiler.enabled, false);
+        let history = std::mem::replace(&mut self.history.enabled, false);
+        let mmu = &mut self.cpu.mmu;
+        let code_data_log = mmu.code_data_log.take();
+        let timeline = std::mem::replace(&mut mmu.timeline.enabled, false);
+        let watchpoints = std::mem::replace(&mut mmu.watchpoints, Watchpoints::new());
+        let serial_output = mmu.serial.output.len();
+
+        let mut instructions = 0;
+        while self.cycle_count < cycle {
+            self.step();
+            instructions += 1;
+        }
+
+        self.trace = trace;
+        self.trace_count = trace_count;
+        self.profiler.enabled = profiling;
+        self.history.enabled = history;
+        let mmu = &mut self.cpu.mmu;
+        mmu.code_data_log = code_data_log;
+        mmu.timeline.enabled = timeline;
+        mmu.watchpoints = watchpoints;
+        mmu.serial.output.truncate(serial_output);
+
+        instructions
+    }
+
+    /// Stop running, cancelling any stepping command in progress.
+    pub fn stop(&mut self) {
+        self.running = false;
+        self.stop_condition = None;
+    }
+
+    /// Step past the current instruction and run until stopped by a breakpoint or watchpoint.
+    pub fn resume(&mut self) {
+        self.run_until(None);
+    }
+
+    /// Run until the instruction after the current one, treating a CALL or RST as one instruction
+    /// by running until it returns.
+    pub fn step_over(&mut self) {
+        match StopCondition::step_over(&self.cpu) {
+            Some(condition) => self.run_until(Some(condition)),
+            None => {
+                self.stop();
+                self.step();
+            }
+        }
+    }
+
+    /// Run until the current function returns to its caller.
+    pub fn step_out(&mut self) {
+        self.run_until(Some(StopCondition::step_out(&self.cpu)));
+    }
+
+    /// Run until the PC reaches an address.
+    pub fn run_to(&mut self, addr: u16) {
+        self.run_until(Some(StopCondition::Address { addr, sp: None }));
+    }
+
+    /// Run until LY changes.
+    pub fn step_scanline(&mut self) {
+        self.run_until(Some(StopCondition::scanline(&self.cpu, self.cycle_count)));
+    }
+
+    /// Run until the start of the next vblank.
+    pub fn step_frame(&mut self) {
+        self.run_until(Some(StopCondition::frame(&self.cpu, self.cycle_count)));
+    }
+
+    /// The stepping command in progress, if any.
+    pub fn get_stop_condition(&self) -> Option<&StopCondition> {
+        self.stop_condition.as_ref()
+    }
+
+    /// Why the emulator last stopped by itself, cleared whenever it starts running again.
+    pub fn get_stop_reason(&self) -> Option<&StopReason> {
+        self.stop_reason.as_ref()
+    }
+
+    /// Start running with a temporary stop condition, stepping the first instruction by hand so
+    /// a breakpoint on the current PC doesn't stop it straight away.
+    fn run_until(&mut self, condition: Option<StopCondition>) {
+        self.step();
+        self.stop_condition = condition;
+        self.stop_reason = None;
+        self.running = true;
     }
 
     // Let the CPU fetch, decode, and execute an opcode and update the PPU
     pub fn step(&mut self) -> u8 {
-        // Run the CPU for one opcode and get its cycle duration for the PPU
-        let cpu_duration = self.cpu.cycle();
+        self.log_trace();
+
+        // Any watchpoint hit from the last instruction has either stopped run_frame already, or
+        // is stale because the emulator is being stepped by hand
+        self.cpu.mmu.watchpoints.take_triggered();
+        self.cpu.mmu.watchpoints.pc = self.cpu.registers.pc;
+        self.cpu.mmu.timeline.pc = self.cpu.registers.pc;
+
+        let registers = self.cpu.registers;
+        self.cpu.mmu.record_accesses = self.history.enabled;
+        self.cpu.mmu.accesses.clear();
+        // Read before executing, as the instruction may overwrite itself
+        let bytes = self.history.enabled.then(|| {
+            [0, 1, 2].map(|offset| self.cpu.mmu.read8_safe(registers.pc.wrapping_add(offset)))
+        });
+
+        let profile_start = self.profiler.enabled.then(|| ProfileStart::new(&self.cpu));
+
+        // Run the CPU for one opcode and any interrupt taken after it, getting the cycle duration
+        // for the PPU
+        let instruction_duration = self.cpu.execute_instruction();
+        let instruction_accesses = self.cpu.mmu.accesses.len();
+        let interrupt_duration = self.cpu.handle_interrupt();
+        let cpu_duration = instruction_duration + interrupt_duration;
+
+        if let Some(start) = profile_start {
+            self.profiler.record(start, &self.cpu, cpu_duration);
+        }
+
+        if let Some(bytes) = bytes {
+            self.history.record(HistoryEntry::new(
+                self.cycle_count,
+                registers,
+                bytes,
+                &self.cpu.mmu.accesses[..instruction_accesses],
+                interrupt_duration > 0,
+            ));
+        }
 
         // Run the PPU for one cycle getting any updated interrupt flags back
-        let int_flags = self.get_mmu().read8(0xFF0F);
-        let ppu_int_flags = self.get_ppu().cycle(cpu_duration);
-        self.get_mmu().write8(0xFF0F, int_flags | ppu_int_flags);
+        let mmu = self.get_mmu();
+        let ppu_int_flags = mmu.ppu.cycle(cpu_duration, &mut mmu.timeline);
+        let serial_int_flags = self.get_serial().cycle();
+        self.get_mmu()
+            .request_interrupts(ppu_int_flags | serial_int_flags);
 
         self.debug.program_counter = self.cpu.registers.pc;
+        self.cycle_count += u64::from(cpu_duration);
 
         if self.trace_count > 0 {
             self.trace_count -= 1;
-            trace!(
-                "A: {:02X} F: {:02X} B: {:02X} C: {:02X} D: {:02X} E: {:02X} H: {:02X} L: {:02X} SP: {:04X} PC: 00:{:04X}",
-                self.cpu.registers.a,
-                self.cpu.registers.f.bits(),
-                self.cpu.registers.b,
-                self.cpu.registers.c,
-                self.cpu.registers.d,
-                self.cpu.registers.e,
-                self.cpu.registers.h,
-                self.cpu.registers.l,
-                self.cpu.registers.sp,
-                self.cpu.registers.pc,
-            );
+            trace!("{}", format_trace_line(&self.cpu));
         }
 
         cpu_duration
     }
 
+    pub fn get_history(&mut self) -> &mut ExecutionHistory {
+        &mut self.history
+    }
+
+    pub fn get_profiler(&mut self) -> &mut Profiler {
+        &mut self.profiler
+    }
+
+    fn export_history_on_break(&self) {
+        if let Some(path) = &self.history.export_path {
+            if let Err(e) = self.history.export(path) {
+                error!("{}", e);
+            }
+        }
+    }
+
+    /// Write the state before the next instruction to the trace file, if one is active, closing it
+    /// once its window has passed or if it can no longer be written to.
+    fn log_trace(&mut self) {
+        let Some(trace) = &mut self.trace else {
+            return;
+        };
+
+        if trace.is_finished(self.cycle_count) {
+            self.stop_trace();
+        } else if let Err(e) = trace.log(&self.cpu, self.cycle_count) {
+            error!("{}", e);
+            self.stop_trace();
+        }
+    }
+
+    /// Start writing a Gameboy Doctor trace, replacing any trace already being written.
+    ///
+    /// The trace's window is relative to the cycle count when it is started.
+    pub fn start_trace(&mut self, trace: TraceWriter, start_cycle: u64, end_cycle: Option<u64>) {
+        self.stop_trace();
+
+        let now = self.cycle_count;
+        self.trace = Some(trace.with_window(now + start_cycle, end_cycle.map(|end| now + end)));
+    }
+
+    /// Stop and flush the active trace, returning how many lines were written to it.
+    pub fn stop_trace(&mut self) -> Option<u64> {
+        let mut trace = self.trace.take()?;
+        if let Err(e) = trace.flush() {
+            error!("{}", e);
+        }
+        info!("Trace finished, {} lines written", trace.lines_written);
+
+        Some(trace.lines_written)
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Total cycles executed since the emulator was created.
+    pub fn get_cycle_count(&self) -> u64 {
+        self.cycle_count
+    }
+
     pub fn reset(&mut self) {
+        self.stop_condition = None;
         self.get_ppu().reset();
         self.get_cpu().reset();
         self.get_mmu().reset();
+        self.get_serial().reset();
     }
 
     pub fn get_cpu(&mut self) -> &mut Cpu {
@@ -126,4 +467,38 @@ impl Lameboy {
     pub fn get_joypad(&mut self) -> &mut Joypad {
         &mut self.get_mmu().joypad
     }
+
+    pub fn get_serial(&mut self) -> &mut Serial {
+        &mut self.get_mmu().serial
+    }
+}
+
+/// `ld hl, 0xC000; ld [hl+], a; inc a; jr -4`, counting up in A forever and storing every value in
+/// work RAM.
+#[cfg(test)]
+pub const COUNTER_PROGRAM: &[u8] = &[0x21, 0x00, 0xC0, 0x22, 0x3C, 0x18, 0xFC];
+
+/// A 32KB ROM of NOPs without an MBC, with code placed at the given addresses.
+#[cfg(test)]
+pub fn program_rom(code: &[(usize, &[u8])]) -> Vec<u8> {
+    let mut rom = vec![0x00; 0x8000];
+    for &(addr, bytes) in code {
+        rom[addr..addr + bytes.len()].copy_from_slice(bytes);
+    }
+    rom
+}
+
+#[cfg(test)]
+impl Lameboy {
+    /// A reset emulator running a program from the entry point.
+    pub fn with_program(program: &[u8]) -> Lameboy {
+        Lameboy::with_code(&[(0x0100, program)])
+    }
+
+    /// A reset emulator running a ROM with code placed at any addresses.
+    pub fn with_code(code: &[(usize, &[u8])]) -> Lameboy {
+        let mut lameboy = Lameboy::new(program_rom(code));
+        lameboy.reset();
+        lameboy
+    }
 }
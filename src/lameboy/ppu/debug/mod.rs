@@ -1,15 +1,22 @@
 use crate::gui::imgui_debug_state::ImguiDebugState;
 use crate::gui::imgui_debuggable::ImguiDebuggable;
-use crate::lameboy::ppu::debug::oam::oam_window;
 use crate::lameboy::ppu::debug::registers::registers_window;
 use crate::lameboy::ppu::Ppu;
 use imgui::{Condition, Ui};
 
 mod oam;
+mod palettes;
 mod registers;
+mod tile_map;
+mod tiles;
+
+pub use crate::lameboy::ppu::debug::oam::oam_window;
+pub use crate::lameboy::ppu::debug::palettes::palettes_window;
+pub use crate::lameboy::ppu::debug::tile_map::tile_map_window;
+pub use crate::lameboy::ppu::debug::tiles::tiles_window;
 
 impl ImguiDebuggable for Ppu {
-    fn imgui_display(&mut self, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    fn imgui_display(&mut self, ui: &Ui, _imgui_debug: &mut ImguiDebugState) {
         ui.window("PPU")
             .size([180.0, 115.0], Condition::FirstUseEver)
             .resizable(true)
@@ -19,6 +26,5 @@ impl ImguiDebuggable for Ppu {
             });
 
         registers_window(self, ui);
-        oam_window(self, ui, imgui_debug);
     }
 }
@@ -1,28 +1,162 @@
 use crate::gui::imgui_debug_state::ImguiDebugState;
-use crate::lameboy::ppu::sprite::Sprite;
-use crate::lameboy::ppu::Ppu;
-use imgui::{Condition, Ui};
+use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::ppu::image::Image;
+use crate::lameboy::ppu::palette::ObjectPalette;
+use crate::lameboy::ppu::sprite::{Sprite, SpritePriority, SPRITES_PER_LINE, SPRITE_COUNT};
+use crate::lameboy::ppu::{Ppu, SCREEN_HEIGHT, SCREEN_WIDTH};
+use imgui::{Condition, TableBgTarget, TableColumnSetup, TableFlags, Ui};
+
+const SPRITE_COLOUR: [f32; 4] = [0.3, 1.0, 0.3, 1.0];
+const DROPPED_COLOUR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+const SELECTED_COLOUR: [f32; 4] = [1.0, 1.0, 0.3, 1.0];
 
 pub fn oam_window(ppu: &Ppu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
-    ui.window("PPU-OAM")
-        .size([224.0, 230.0], Condition::FirstUseEver)
+    ui.window("OAM")
+        .size([480.0, 480.0], Condition::FirstUseEver)
         .resizable(true)
         .build(|| {
-            ui.input_int("Sprite Index", &mut imgui_debug.ppu_sprite_index)
-                .build();
-            // Limit index
-            if imgui_debug.ppu_sprite_index < 0 {
-                imgui_debug.ppu_sprite_index = 0
-            };
-            if imgui_debug.ppu_sprite_index > 39 {
-                imgui_debug.ppu_sprite_index = 39
+            let ly = ppu.registers.ly;
+            let on_line = ppu.sprites_on_line(ly);
+            let dropped = on_line.get(SPRITES_PER_LINE..).unwrap_or(&[]);
+
+            ui.checkbox("Overlay on screen", &mut imgui_debug.oam_overlay);
+            ui.same_line();
+            ui.text(format!(
+                "LY {ly}: {} sprites, {} dropped",
+                on_line.len(),
+                dropped.len()
+            ));
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Green rows are on the current line, red ones are past the 10 sprite limit",
+            );
+            ui.separator();
+
+            if imgui_debug.oam_overlay {
+                draw_overlay(ppu, ui, dropped, imgui_debug.oam_selected);
+            }
+
+            // Every sprite side by side in one texture, 8 pixels apart
+            let height = usize::from(ppu.sprite_height());
+            let mut previews = Image::new(usize::from(SPRITE_COUNT) * 8, height);
+            for sprite_number in 0..SPRITE_COUNT {
+                let preview = ppu.sprite_image(sprite_number);
+                previews.draw_image(&preview, usize::from(sprite_number) * 8, 0);
+            }
+            imgui_debug.textures.upload("oam_sprites", previews);
+
+            let flags = TableFlags::RESIZABLE
+                | TableFlags::ROW_BG
+                | TableFlags::BORDERS
+                | TableFlags::SCROLL_Y;
+            let Some(_table) = ui.begin_table_with_flags("oam", 9, flags) else {
+                return;
             };
+            for name in [
+                "#", "Sprite", "X", "Y", "Tile", "Flags", "Flip", "Palette", "Priority",
+            ] {
+                ui.table_setup_column_with(TableColumnSetup::new(name));
+            }
+            ui.table_setup_scroll_freeze(0, 1);
+            ui.table_headers_row();
 
-            let sprite = Sprite::new(ppu, imgui_debug.ppu_sprite_index as u8);
-            ui.text(format!("Position: {:?}, {:?}", sprite.x, sprite.y));
-            ui.text(format!("Tile: {:?}", sprite.tile_index));
-            ui.text(format!("Flip X: {:?}", sprite.flip_x));
-            ui.text(format!("Flip Y: {:?}", sprite.flip_y));
-            ui.text(format!("Priority: {:?}", sprite.priority));
+            for sprite_number in 0..SPRITE_COUNT {
+                let sprite = Sprite::new(ppu, sprite_number);
+                let flags = ppu.read8(0xFE03 + (u16::from(sprite_number) << 2));
+
+                ui.table_next_row();
+                if dropped.contains(&sprite_number) {
+                    ui.table_set_bg_color(TableBgTarget::ROW_BG1, [0.5, 0.1, 0.1, 0.6]);
+                } else if on_line.contains(&sprite_number) {
+                    ui.table_set_bg_color(TableBgTarget::ROW_BG1, [0.1, 0.4, 0.1, 0.6]);
+                }
+
+                ui.table_next_column();
+                let selected = imgui_debug.oam_selected == Some(sprite_number);
+                if ui
+                    .selectable_config(sprite_number.to_string())
+                    .selected(selected)
+                    .span_all_columns(true)
+                    .build()
+                {
+                    imgui_debug.oam_selected = (!selected).then_some(sprite_number);
+                }
+                ui.table_next_column();
+                let left = f32::from(sprite_number) / f32::from(SPRITE_COUNT);
+                let right = f32::from(sprite_number + 1) / f32::from(SPRITE_COUNT);
+                imgui_debug.textures.image_region(
+                    ui,
+                    "oam_sprites",
+                    [16.0, height as f32 * 2.0],
+                    [left, 0.0],
+                    [right, 1.0],
+                );
+                ui.table_next_column();
+                ui.text(format!("{} ({})", sprite.x, i16::from(sprite.x) - 8));
+                ui.table_next_column();
+                ui.text(format!("{} ({})", sprite.y, i16::from(sprite.y) - 16));
+                ui.table_next_column();
+                ui.text(format!("${:02X}", sprite.tile_index));
+                ui.table_next_column();
+                ui.text(format!("${flags:02X}"));
+                ui.table_next_column();
+                ui.text(match (sprite.flip_x, sprite.flip_y) {
+                    (false, false) => "",
+                    (true, false) => "X",
+                    (false, true) => "Y",
+                    (true, true) => "XY",
+                });
+                ui.table_next_column();
+                ui.text(match sprite.palette {
+                    ObjectPalette::Palette0 => "OBP0",
+                    ObjectPalette::Palette1 => "OBP1",
+                });
+                ui.table_next_column();
+                ui.text(match sprite.priority {
+                    SpritePriority::AboveBackground => "Above BG",
+                    SpritePriority::BelowBackground => "Below BG",
+                });
+            }
         });
 }
+
+/// Outline every sprite that's at least partly on screen over the game screen, which fills the
+/// window behind the debug windows.
+fn draw_overlay(ppu: &Ppu, ui: &Ui, dropped: &[u8], selected: Option<u8>) {
+    let [width, height] = ui.io().display_size;
+    let scale = [width / SCREEN_WIDTH as f32, height / SCREEN_HEIGHT as f32];
+    let sprite_height = f32::from(ppu.sprite_height());
+    let draw_list = ui.get_background_draw_list();
+
+    for sprite_number in 0..SPRITE_COUNT {
+        let sprite = Sprite::new(ppu, sprite_number);
+        let left = f32::from(sprite.x) - 8.0;
+        let top = f32::from(sprite.y) - 16.0;
+        if left <= -8.0
+            || top <= -sprite_height
+            || left >= SCREEN_WIDTH as f32
+            || top >= SCREEN_HEIGHT as f32
+        {
+            continue;
+        }
+
+        let colour = if selected == Some(sprite_number) {
+            SELECTED_COLOUR
+        } else if dropped.contains(&sprite_number) {
+            DROPPED_COLOUR
+        } else {
+            SPRITE_COLOUR
+        };
+        let top_left = [left * scale[0], top * scale[1]];
+        let bottom_right = [(left + 8.0) * scale[0], (top + sprite_height) * scale[1]];
+        draw_list
+            .add_rect(top_left, bottom_right, colour)
+            .thickness(2.0)
+            .build();
+        draw_list.add_text(
+            [top_left[0] + 2.0, top_left[1] + 1.0],
+            colour,
+            sprite_number.to_string(),
+        );
+    }
+}
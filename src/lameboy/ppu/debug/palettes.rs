@@ -0,0 +1,68 @@
+use crate::lameboy::mmu::Mmu;
+use crate::lameboy::ppu::palette::{set_palette_shade, unpack_palette, DMG_SHADES};
+use imgui::{ColorEditFlags, Condition, MouseButton, Ui};
+
+/// The DMG palette registers and their names.
+const PALETTES: [(u16, &str); 3] = [(0xFF47, "BGP"), (0xFF48, "OBP0"), (0xFF49, "OBP1")];
+
+pub fn palettes_window(mmu: &mut Mmu, ui: &Ui) {
+    ui.window("Palettes")
+        .size([300.0, 150.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            for (addr, name) in PALETTES {
+                let mut palette = mmu.read8_safe(addr);
+
+                ui.text(format!("{name:<4}"));
+                ui.same_line();
+                ui.set_next_item_width(40.0);
+                if ui
+                    .input_scalar(format!("##{name}"), &mut palette)
+                    .display_format("%02X")
+                    .chars_hexadecimal(true)
+                    .build()
+                {
+                    mmu.write8(addr, palette);
+                }
+
+                for (colour, shade) in unpack_palette(palette).into_iter().enumerate() {
+                    ui.same_line();
+                    let [r, g, b] = DMG_SHADES[usize::from(shade)];
+                    let swatch = [
+                        f32::from(r) / 255.0,
+                        f32::from(g) / 255.0,
+                        f32::from(b) / 255.0,
+                        1.0,
+                    ];
+                    let clicked = ui
+                        .color_button_config(format!("{name} colour {colour}"), swatch)
+                        .flags(ColorEditFlags::NO_TOOLTIP)
+                        .size([32.0, 32.0])
+                        .build();
+
+                    // Left click for the next darker shade, right click for the next lighter
+                    let new_shade = if clicked {
+                        (shade + 1) & 0b11
+                    } else if ui.is_item_clicked_with_button(MouseButton::Right) {
+                        shade.wrapping_sub(1) & 0b11
+                    } else {
+                        shade
+                    };
+                    if new_shade != shade {
+                        mmu.write8(addr, set_palette_shade(palette, colour, new_shade));
+                    }
+
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(format!(
+                            "{name} ${addr:04X}: colour {colour} is shade {shade}"
+                        ));
+                    }
+                }
+            }
+
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Click a shade to darken it, right click to lighten it",
+            );
+        });
+}
@@ -18,9 +18,9 @@ pub fn registers_window(ppu: &Ppu, ui: &Ui) {
             ui.text(format!("LY: {:?}", ppu.registers.ly));
             ui.text(format!("LYC: {:?}", ppu.registers.lyc));
             ui.text(format!("DMA: {:?}", ppu.registers.dma));
-            ui.text(format!("BG Palette: {:?}", ppu.registers.bg_palette));
-            ui.text(format!("OBJ0 Palette: {:?}", ppu.registers.obj0_palette));
-            ui.text(format!("OBJ1 Palette: {:?}", ppu.registers.obj1_palette));
+            ui.text(format!("BG Palette: ${:02X}", ppu.registers.bg_palette));
+            ui.text(format!("OBJ0 Palette: ${:02X}", ppu.registers.obj0_palette));
+            ui.text(format!("OBJ1 Palette: ${:02X}", ppu.registers.obj1_palette));
             ui.text(format!("Window Y: {:?}", ppu.registers.window_y));
             ui.text(format!("Window X: {:?}", ppu.registers.window_x));
         });
@@ -0,0 +1,118 @@
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::ppu::image::Image;
+use crate::lameboy::ppu::registers::ControlFlags;
+use crate::lameboy::ppu::tile::Tile;
+use crate::lameboy::ppu::tile_map::{TILE_MAPS, TILE_MAP_SIZE};
+use crate::lameboy::ppu::Ppu;
+use imgui::{Condition, Ui};
+use std::path::Path;
+
+pub fn tile_map_window(ppu: &Ppu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("Tile Maps")
+        .size([560.0, 620.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            for (index, map) in TILE_MAPS.iter().enumerate() {
+                if index > 0 {
+                    ui.same_line();
+                }
+                ui.radio_button(
+                    format!("${map:04X}"),
+                    &mut imgui_debug.tile_map_index,
+                    index,
+                );
+            }
+            ui.same_line();
+            ui.checkbox("Overlay", &mut imgui_debug.tile_map_overlay);
+            ui.same_line();
+            ui.set_next_item_width(60.0);
+            ui.slider("Zoom", 1.0, 4.0, &mut imgui_debug.tile_map_zoom);
+
+            let map = TILE_MAPS[imgui_debug.tile_map_index];
+            let control = ppu.registers.control;
+            let mut used_by = Vec::new();
+            if control.contains(ControlFlags::BG_DISPLAY) && map == ppu.bg_map() {
+                used_by.push("background");
+            }
+            if control.contains(ControlFlags::WINDOW_DISPLAY) && map == ppu.window_map() {
+                used_by.push("window");
+            }
+            let tile_data = if control.contains(ControlFlags::BG_WIN_TILE_SET) {
+                "$8000"
+            } else {
+                "$8800"
+            };
+            ui.text(format!(
+                "Used by: {}, tiles from {tile_data}, SCX {} SCY {}, WX {} WY {}",
+                if used_by.is_empty() {
+                    String::from("nothing")
+                } else {
+                    used_by.join(" and ")
+                },
+                ppu.registers.scroll_x,
+                ppu.registers.scroll_y,
+                ppu.registers.window_x,
+                ppu.registers.window_y
+            ));
+            ui.text_colored(
+                [0.5, 0.5, 0.5, 1.0],
+                "Red outlines the background on screen, blue the window",
+            );
+
+            ui.input_text("Export file", &mut imgui_debug.tile_map_export_path)
+                .build();
+            ui.same_line();
+            if ui.button("Export") {
+                let image = ppu.tile_map(map, imgui_debug.tile_map_overlay);
+                if let Err(e) = image.save_png(Path::new(&imgui_debug.tile_map_export_path)) {
+                    error!("{}", e);
+                }
+            }
+            ui.separator();
+
+            ui.child_window("tile_map")
+                .horizontal_scrollbar(true)
+                .build(|| {
+                    let zoom = imgui_debug.tile_map_zoom;
+                    let size = [TILE_MAP_SIZE as f32 * zoom; 2];
+                    let image = ppu.tile_map(map, imgui_debug.tile_map_overlay);
+                    let origin = ui.cursor_screen_pos();
+                    imgui_debug.textures.image(ui, "tile_map", image, size);
+
+                    if !ui.is_item_hovered() {
+                        return;
+                    }
+                    let mouse = ui.io().mouse_pos;
+                    let column = (((mouse[0] - origin[0]) / (8.0 * zoom)) as u16).min(31);
+                    let row = (((mouse[1] - origin[1]) / (8.0 * zoom)) as u16).min(31);
+                    ui.tooltip(|| map_entry_tooltip(ppu, ui, imgui_debug, map, column, row));
+                });
+        });
+}
+
+fn map_entry_tooltip(
+    ppu: &Ppu,
+    ui: &Ui,
+    imgui_debug: &mut ImguiDebugState,
+    map: u16,
+    column: u16,
+    row: u16,
+) {
+    let entry_addr = map + row * 32 + column;
+    let tile = ppu.map_entry_tile(entry_addr);
+
+    let mut preview = Image::new(8, 8);
+    Tile::at(ppu, tile).draw(&mut preview, 0, 0, ppu.registers.bg_palette);
+    imgui_debug
+        .textures
+        .image(ui, "tile_map_preview", preview, [64.0, 64.0]);
+
+    ui.text(format!("Column {column}, row {row} (${entry_addr:04X})"));
+    ui.text(format!("Pixels {}, {}", column * 8, row * 8));
+    ui.text(format!(
+        "Index ${:02X}, tile {tile} at ${:04X}",
+        ppu.read8(entry_addr),
+        0x8000 + (tile << 4)
+    ));
+}
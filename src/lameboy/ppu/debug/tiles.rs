@@ -0,0 +1,127 @@
+use crate::gui::imgui_debug_state::ImguiDebugState;
+use crate::lameboy::ppu::image::Image;
+use crate::lameboy::ppu::tile::{Tile, IDENTITY_PALETTE, TILE_COUNT, TILE_SHEET_COLUMNS};
+use crate::lameboy::ppu::Ppu;
+use imgui::{Condition, Ui};
+use std::path::Path;
+
+const PALETTES: [&str; 4] = ["Raw", "BGP", "OBP0", "OBP1"];
+
+/// How many map entries to list when hovering a tile before just counting the rest.
+const LISTED_MAP_ENTRIES: usize = 8;
+
+fn palette(ppu: &Ppu, selected: usize) -> u8 {
+    match selected {
+        1 => ppu.registers.bg_palette,
+        2 => ppu.registers.obj0_palette,
+        3 => ppu.registers.obj1_palette,
+        _ => IDENTITY_PALETTE,
+    }
+}
+
+pub fn tiles_window(ppu: &Ppu, ui: &Ui, imgui_debug: &mut ImguiDebugState) {
+    ui.window("Tiles")
+        .size([300.0, 500.0], Condition::FirstUseEver)
+        .resizable(true)
+        .build(|| {
+            for (index, name) in PALETTES.iter().enumerate() {
+                if index > 0 {
+                    ui.same_line();
+                }
+                ui.radio_button(name, &mut imgui_debug.tiles_palette, index);
+            }
+            ui.same_line();
+            ui.set_next_item_width(60.0);
+            ui.slider("Zoom", 1.0, 4.0, &mut imgui_debug.tiles_zoom);
+
+            let palette = palette(ppu, imgui_debug.tiles_palette);
+            ui.input_text("Export file", &mut imgui_debug.tiles_export_path)
+                .build();
+            ui.same_line();
+            if ui.button("Export") {
+                let sheet = ppu.tile_sheet(palette);
+                if let Err(e) = sheet.save_png(Path::new(&imgui_debug.tiles_export_path)) {
+                    error!("{}", e);
+                }
+            }
+            ui.separator();
+
+            ui.child_window("tile_sheet")
+                .horizontal_scrollbar(true)
+                .build(|| {
+                    let sheet = ppu.tile_sheet(palette);
+                    let zoom = imgui_debug.tiles_zoom;
+                    let size = [sheet.width as f32 * zoom, sheet.height as f32 * zoom];
+                    let origin = ui.cursor_screen_pos();
+                    imgui_debug.textures.image(ui, "tile_sheet", sheet, size);
+
+                    if !ui.is_item_hovered() {
+                        return;
+                    }
+                    let mouse = ui.io().mouse_pos;
+                    let column = ((mouse[0] - origin[0]) / (8.0 * zoom)) as usize;
+                    let row = ((mouse[1] - origin[1]) / (8.0 * zoom)) as usize;
+                    let tile_number =
+                        (row * TILE_SHEET_COLUMNS + column.min(TILE_SHEET_COLUMNS - 1)) as u16;
+                    if tile_number < TILE_COUNT {
+                        ui.tooltip(|| tile_tooltip(ppu, ui, imgui_debug, tile_number, palette));
+                    }
+                });
+        });
+}
+
+fn tile_tooltip(
+    ppu: &Ppu,
+    ui: &Ui,
+    imgui_debug: &mut ImguiDebugState,
+    tile_number: u16,
+    palette: u8,
+) {
+    let mut preview = Image::new(8, 8);
+    Tile::at(ppu, tile_number).draw(&mut preview, 0, 0, palette);
+    imgui_debug
+        .textures
+        .image(ui, "tile_preview", preview, [64.0, 64.0]);
+
+    ui.text(format!(
+        "Tile {tile_number} at ${:04X}",
+        0x8000 + (tile_number << 4)
+    ));
+    // The map index the tile has in each addressing mode it can be reached from
+    if tile_number < 256 {
+        ui.text(format!("$8000 index: ${:02X}", tile_number));
+    }
+    if tile_number >= 128 {
+        ui.text(format!(
+            "$8800 index: ${:02X}",
+            tile_number.wrapping_sub(256) as u8
+        ));
+    }
+
+    let references = ppu.tile_references(tile_number);
+    for (name, base) in [("$9800 map", 0x9800), ("$9C00 map", 0x9C00)] {
+        let entries: Vec<u16> = references
+            .map_entries
+            .iter()
+            .copied()
+            .filter(|&addr| addr & 0xFC00 == base)
+            .collect();
+        let listed: Vec<String> = entries
+            .iter()
+            .take(LISTED_MAP_ENTRIES)
+            .map(|addr| format!("({},{})", (addr & 0x1F), (addr & 0x3FF) >> 5))
+            .collect();
+        let more = entries.len().saturating_sub(LISTED_MAP_ENTRIES);
+        match (entries.len(), more) {
+            (0, _) => ui.text(format!("{name}: unused")),
+            (_, 0) => ui.text(format!("{name}: {}", listed.join(" "))),
+            _ => ui.text(format!("{name}: {} and {more} more", listed.join(" "))),
+        }
+    }
+    if references.sprites.is_empty() {
+        ui.text("Sprites: none");
+    } else {
+        let sprites: Vec<String> = references.sprites.iter().map(u8::to_string).collect();
+        ui.text(format!("Sprites: {}", sprites.join(", ")));
+    }
+}
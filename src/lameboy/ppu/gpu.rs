@@ -7,6 +7,7 @@ use glium::uniforms::*;
 use glium::{IndexBuffer, Program, Surface, VertexBuffer};
 use nalgebra::Matrix4;
 
+use crate::lameboy::ppu::palette::DMG_SHADES;
 use crate::lameboy::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
 
 #[derive(Copy, Clone)]
@@ -121,10 +122,12 @@ impl Gpu {
             0..1,
         );
 
-        let palette = Matrix4::new(
-            224.0, 136.0, 52.0, 8.0, 248.0, 192.0, 104.0, 24.0, 208.0, 112.0, 86.0, 32.0, 1.0, 1.0,
-            1.0, 1.0,
-        ) / 255.0;
+        // One column per shade, so the shader can index it with the palette index
+        let palette = Matrix4::from_fn(|channel, shade| {
+            DMG_SHADES[shade]
+                .get(channel)
+                .map_or(1.0, |&value| f32::from(value) / 255.0)
+        });
 
         Gpu {
             vertex_buffer,
@@ -0,0 +1,71 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::lameboy::ppu::palette::DMG_SHADES;
+
+/// An RGBA picture of some part of the PPU's state, for the debug viewers to show and export.
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Four bytes per pixel, a row at a time from the top.
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![0xFF; width * height * 4],
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, colour: [u8; 4]) {
+        let offset = (y * self.width + x) * 4;
+        self.pixels[offset..offset + 4].copy_from_slice(&colour);
+    }
+
+    /// Set a pixel to one of the four DMG shades, 0 being the lightest.
+    pub fn set_shade(&mut self, x: usize, y: usize, shade: u8) {
+        let [r, g, b] = DMG_SHADES[usize::from(shade & 0x03)];
+        self.set_pixel(x, y, [r, g, b, 0xFF]);
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * self.width + x) * 4;
+        [
+            self.pixels[offset],
+            self.pixels[offset + 1],
+            self.pixels[offset + 2],
+            self.pixels[offset + 3],
+        ]
+    }
+
+    /// Copy another image into this one with its top left at a position.
+    pub fn draw_image(&mut self, image: &Image, left: usize, top: usize) {
+        for y in 0..image.height {
+            for x in 0..image.width {
+                self.set_pixel(left + x, top + y, image.get_pixel(x, y));
+            }
+        }
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Unable to create image {}: {e}", path.display()))?;
+
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| format!("Unable to write image {}: {e}", path.display()))?;
+        info!("Saved image to {}", path.display());
+
+        Ok(())
+    }
+}
@@ -1,22 +1,22 @@
-use glium::backend::Facade;
-use glium::Surface;
-
 use crate::lameboy::interrupts::{INT_LCD_STAT, INT_VBLANK};
 use crate::lameboy::mmu::mmuobject::MmuObject;
-use crate::lameboy::ppu::gpu::*;
 use crate::lameboy::ppu::palette::*;
 use crate::lameboy::ppu::registers::ControlFlags;
 use crate::lameboy::ppu::registers::Registers;
 use crate::lameboy::ppu::registers::StatusInterruptFlags;
 use crate::lameboy::ppu::sprite::{Sprite, SpritePriority};
 use crate::lameboy::ppu::tile::Tile;
+use crate::lameboy::state::{StateReader, StateWriter, Stateful};
+use crate::lameboy::timeline::{EventKind, Timeline};
 
-mod debug;
+pub mod debug;
 pub mod gpu;
+pub mod image;
 pub mod palette;
 pub mod registers;
 pub mod sprite;
 pub mod tile;
+pub mod tile_map;
 
 pub const SCREEN_WIDTH: usize = 160;
 pub const SCREEN_HEIGHT: usize = 144;
@@ -29,6 +29,18 @@ enum Mode {
     VBlank,
 }
 
+impl Mode {
+    /// The mode's number in the STAT register.
+    fn number(&self) -> u8 {
+        match self {
+            Mode::HBlank => 0,
+            Mode::VBlank => 1,
+            Mode::ReadOam => 2,
+            Mode::ReadVram => 3,
+        }
+    }
+}
+
 pub struct Ppu {
     /// Video RAM [0x8000 - 0x9FFF] (Bank 0-1 in CGB Mode)
     vram: Box<[u8; 0x2000]>,
@@ -37,21 +49,17 @@ pub struct Ppu {
     mode_clock: usize,
     mode: Mode,
     registers: Registers,
-    gpu: Gpu,
     screen_buffer: Box<[u8; SCREEN_WIDTH * SCREEN_HEIGHT]>,
 }
 
 impl Ppu {
-    pub fn new<F: Facade>(display: &F) -> Ppu {
-        let gpu = Gpu::new(display);
-
+    pub fn new() -> Ppu {
         Ppu {
             vram: Box::new([0; 0x2000]),
             oam: Box::new([0; 0x00A0]),
             mode_clock: 0,
             mode: Mode::HBlank,
             registers: Registers::new(),
-            gpu,
             screen_buffer: Box::new([0; SCREEN_WIDTH * SCREEN_HEIGHT]),
         }
     }
@@ -74,12 +82,26 @@ impl Ppu {
         }
 
         // Set mode flag
-        match self.mode {
-            Mode::HBlank => stat & 0b1111_1100,
-            Mode::VBlank => stat | 0b0000_0001,
-            Mode::ReadOam => stat | 0b0000_0010,
-            Mode::ReadVram => stat | 0b0000_0011,
+        stat | self.mode.number()
+    }
+
+    /// The scanline being drawn and how many dots into it the PPU is.
+    pub fn position(&self) -> (u8, u16) {
+        let start = match self.mode {
+            Mode::ReadOam | Mode::VBlank => 0,
+            Mode::ReadVram => 80,
+            Mode::HBlank => 80 + 172,
+        };
+        (self.registers.ly, (start + self.mode_clock) as u16)
+    }
+
+    /// Log entering a new mode, and LY reaching LYC if it just moved to a new line.
+    fn log_mode_change(&self, timeline: &mut Timeline, new_line: bool) {
+        let (ly, dot) = self.position();
+        if new_line && ly == self.registers.lyc {
+            timeline.log(ly, dot, EventKind::LycMatch(ly));
         }
+        timeline.log(ly, dot, EventKind::Mode(self.mode.number()));
     }
 
     pub fn is_vram_accessible(&self) -> bool {
@@ -92,7 +114,7 @@ impl Ppu {
 
     /// Cycle the PPU based on the how long the CPU spent since it last cycled.
     /// Return a byte containing the Interrupt Flag value from the PPU
-    pub fn cycle(&mut self, cpu_duration: u8) -> u8 {
+    pub fn cycle(&mut self, cpu_duration: u8, timeline: &mut Timeline) -> u8 {
         let mut int_flag = 0x00;
 
         if self
@@ -120,6 +142,7 @@ impl Ppu {
                         // Enter scanline Mode::ReadVram
                         self.mode_clock = 0;
                         self.mode = Mode::ReadVram;
+                        self.log_mode_change(timeline, false);
                     }
                 }
                 // VRAM read mode, scanline active
@@ -129,6 +152,7 @@ impl Ppu {
                         // Enter hblank
                         self.mode_clock = 0;
                         self.mode = Mode::HBlank;
+                        self.log_mode_change(timeline, false);
                         if status_int_flags.contains(StatusInterruptFlags::INT_ENABLE_HBLANK) {
                             // Set interrupt bit
                             int_flag |= INT_LCD_STAT;
@@ -155,7 +179,6 @@ impl Ppu {
                                 // Set interrupt bit
                                 int_flag |= INT_LCD_STAT;
                             }
-                            self.gpu.load_texture(self.screen_buffer.as_ref());
                         } else {
                             self.mode = Mode::ReadOam;
                             if status_int_flags.contains(StatusInterruptFlags::INT_ENABLE_OAM) {
@@ -163,6 +186,7 @@ impl Ppu {
                                 int_flag |= INT_LCD_STAT;
                             }
                         }
+                        self.log_mode_change(timeline, true);
                     }
                 }
 
@@ -176,6 +200,14 @@ impl Ppu {
                             // Restart scanning modes
                             self.mode = Mode::ReadOam;
                             self.registers.ly = 0;
+                            timeline.frame_finished();
+                            self.log_mode_change(timeline, true);
+                        } else if self.registers.ly == self.registers.lyc {
+                            timeline.log(
+                                self.registers.ly,
+                                0,
+                                EventKind::LycMatch(self.registers.ly),
+                            );
                         }
                     }
                 }
@@ -314,10 +346,13 @@ impl Ppu {
         }
     }
 
-    pub fn draw<S: Surface>(&mut self, target: &mut S) {
-        self.gpu.load_texture(self.screen_buffer.as_ref());
+    /// The palette indexed pixels of the last rendered frame, one byte per pixel.
+    pub fn get_screen_buffer(&self) -> &[u8] {
+        self.screen_buffer.as_ref()
+    }
 
-        self.gpu.draw(target);
+    pub fn get_vram(&self) -> &[u8] {
+        self.vram.as_ref()
     }
 }
 
@@ -364,3 +399,34 @@ impl MmuObject for Ppu {
         }
     }
 }
+
+impl Stateful for Ppu {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.bytes(&self.vram[..]);
+        state.bytes(&self.oam[..]);
+        state.u64(self.mode_clock as u64);
+        state.u8(match self.mode {
+            Mode::ReadOam => 0,
+            Mode::ReadVram => 1,
+            Mode::HBlank => 2,
+            Mode::VBlank => 3,
+        });
+        self.registers.save_state(state);
+        state.bytes(&self.screen_buffer[..]);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.bytes(&mut self.vram[..])?;
+        state.bytes(&mut self.oam[..])?;
+        self.mode_clock = state.u64()? as usize;
+        self.mode = match state.u8()? {
+            0 => Mode::ReadOam,
+            1 => Mode::ReadVram,
+            2 => Mode::HBlank,
+            3 => Mode::VBlank,
+            mode => return Err(format!("Invalid PPU mode in state: {mode}")),
+        };
+        self.registers.load_state(state)?;
+        state.bytes(&mut self.screen_buffer[..])
+    }
+}
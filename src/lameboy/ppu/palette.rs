@@ -1,3 +1,6 @@
+/// RGB colours for the four DMG shades, lightest first, matching the GUI's screen palette.
+pub const DMG_SHADES: [[u8; 3]; 4] = [[224, 248, 208], [136, 192, 112], [52, 104, 86], [8, 24, 32]];
+
 pub enum ObjectPalette {
     Palette0,
     Palette1,
@@ -28,6 +31,12 @@ pub fn unpack_palette(palette: u8) -> [u8; 4] {
     unpacked_palette
 }
 
+/// Change the shade a palette gives one of the four pixel values, keeping the others.
+pub fn set_palette_shade(palette: u8, colour: usize, shade: u8) -> u8 {
+    let shift = colour * 2;
+    (palette & !(0b11 << shift)) | ((shade & 0b11) << shift)
+}
+
 #[test]
 fn test_unpack_palette() {
     assert_eq!([0, 0, 0, 0], unpack_palette(0b0000_0000));
@@ -49,3 +58,14 @@ fn test_unpack_palette() {
 
     assert_eq!([3, 3, 3, 3], unpack_palette(0b1111_1111));
 }
+
+#[test]
+fn test_set_palette_shade() {
+    assert_eq!(0b0000_0011, set_palette_shade(0b0000_0000, 0, 3));
+    assert_eq!(0b1110_0100, set_palette_shade(0b1111_0100, 2, 2));
+    assert_eq!(0b0011_1111, set_palette_shade(0b1111_1111, 3, 0));
+    assert_eq!(
+        [1, 2, 0, 3],
+        unpack_palette(set_palette_shade(0b1100_1001, 2, 0))
+    );
+}
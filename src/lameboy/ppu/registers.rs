@@ -1,3 +1,5 @@
+use crate::lameboy::state::{StateReader, StateWriter, Stateful};
+
 bitflags! {
     pub struct ControlFlags: u8 {
         const BG_DISPLAY        = 0b_0000_0001;
@@ -69,3 +71,41 @@ impl Registers {
         self.window_x = 0;
     }
 }
+
+impl Stateful for Registers {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.control.bits());
+        for value in [
+            self.status,
+            self.scroll_y,
+            self.scroll_x,
+            self.ly,
+            self.lyc,
+            self.dma,
+            self.bg_palette,
+            self.obj0_palette,
+            self.obj1_palette,
+            self.window_y,
+            self.window_x,
+        ] {
+            state.u8(value);
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.control = ControlFlags::from_bits_truncate(state.u8()?);
+        self.status = state.u8()?;
+        self.scroll_y = state.u8()?;
+        self.scroll_x = state.u8()?;
+        self.ly = state.u8()?;
+        self.lyc = state.u8()?;
+        self.dma = state.u8()?;
+        self.bg_palette = state.u8()?;
+        self.obj0_palette = state.u8()?;
+        self.obj1_palette = state.u8()?;
+        self.window_y = state.u8()?;
+        self.window_x = state.u8()?;
+
+        Ok(())
+    }
+}
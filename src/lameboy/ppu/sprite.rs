@@ -1,7 +1,15 @@
 use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::ppu::image::Image;
+use crate::lameboy::ppu::registers::ControlFlags;
+use crate::lameboy::ppu::tile::Tile;
 use crate::lameboy::ppu::Ppu;
 
-use crate::lameboy::ppu::palette::ObjectPalette;
+use crate::lameboy::ppu::palette::{unpack_palette, ObjectPalette};
+
+/// How many sprites OAM holds.
+pub const SPRITE_COUNT: u8 = 40;
+/// How many sprites the PPU can draw on one line, any more being dropped.
+pub const SPRITES_PER_LINE: usize = 10;
 
 bitflags! {
     struct SpriteFlags: u8 {
@@ -58,3 +66,110 @@ impl Sprite {
         }
     }
 }
+
+impl Ppu {
+    /// How tall sprites are, 8 or 16 pixels depending on LCDC.
+    pub fn sprite_height(&self) -> u8 {
+        if self.registers.control.contains(ControlFlags::OBJ_SIZE) {
+            16
+        } else {
+            8
+        }
+    }
+
+    /// The sprites overlapping a line in OAM order, of which only the first `SPRITES_PER_LINE`
+    /// are drawn.
+    pub fn sprites_on_line(&self, ly: u8) -> Vec<u8> {
+        let height = i16::from(self.sprite_height());
+        let line = i16::from(ly);
+        (0..SPRITE_COUNT)
+            .filter(|&sprite_number| {
+                let top = i16::from(Sprite::new(self, sprite_number).y) - 16;
+                top <= line && line < top + height
+            })
+            .collect()
+    }
+
+    /// Draw a sprite as it would appear on screen, flipped and through its palette, with colour 0
+    /// left transparent.
+    pub fn sprite_image(&self, sprite_number: u8) -> Image {
+        let sprite = Sprite::new(self, sprite_number);
+        let height = usize::from(self.sprite_height());
+        let palette = unpack_palette(match sprite.palette {
+            ObjectPalette::Palette0 => self.registers.obj0_palette,
+            ObjectPalette::Palette1 => self.registers.obj1_palette,
+        });
+        let tiles = if height == 16 {
+            [
+                Tile::at(self, u16::from(sprite.tile_index & 0xFE)),
+                Tile::at(self, u16::from(sprite.tile_index | 0x01)),
+            ]
+        } else {
+            [
+                Tile::new(self, sprite.tile_index),
+                Tile::new(self, sprite.tile_index),
+            ]
+        };
+
+        let mut image = Image::new(8, height);
+        for y in 0..height {
+            let tile_y = if sprite.flip_y { height - 1 - y } else { y };
+            let row = tiles[tile_y / 8].rows[tile_y % 8];
+            for x in 0..8 {
+                let colour = row[if sprite.flip_x { 7 - x } else { x }];
+                if colour == 0 {
+                    image.set_pixel(x, y, [0, 0, 0, 0]);
+                } else {
+                    image.set_shade(x, y, palette[usize::from(colour)]);
+                }
+            }
+        }
+
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn sprites_over_the_line_limit() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let ppu = lameboy.get_ppu();
+        // Sprites 0, 2, 4... 22 all cover lines 20 to 27
+        for sprite_number in (0..24).step_by(2) {
+            ppu.write8(0xFE00 + (sprite_number << 2), 36);
+        }
+
+        let on_line = ppu.sprites_on_line(20);
+        assert_eq!(12, on_line.len());
+        assert_eq!(&[20, 22], &on_line[SPRITES_PER_LINE..]);
+        assert!(ppu.sprites_on_line(28).is_empty());
+
+        // Tall sprites reach down to line 35
+        ppu.write8(0xFF40, 0x95);
+        assert_eq!(12, ppu.sprites_on_line(35).len());
+    }
+
+    #[test]
+    fn sprite_image_is_flipped() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let ppu = lameboy.get_ppu();
+        // Tile 2's top row has a single colour 1 pixel on the left
+        ppu.write8(0x8020, 0b1000_0000);
+        ppu.write8(0xFE02, 0x02);
+        ppu.write8(0xFF48, 0b1110_0100);
+
+        let image = ppu.sprite_image(0);
+        assert_eq!(0xFF, image.get_pixel(0, 0)[3]);
+        assert_eq!(0x00, image.get_pixel(7, 0)[3]);
+
+        // Flipped both ways the pixel ends up in the bottom right
+        ppu.write8(0xFE03, 0b0110_0000);
+        let image = ppu.sprite_image(0);
+        assert_eq!(0x00, image.get_pixel(0, 0)[3]);
+        assert_eq!(0xFF, image.get_pixel(7, 7)[3]);
+    }
+}
@@ -1,13 +1,29 @@
 use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::ppu::image::Image;
+use crate::lameboy::ppu::palette::unpack_palette;
+use crate::lameboy::ppu::registers::ControlFlags;
 use crate::lameboy::ppu::Ppu;
 
+/// How many tiles fit in the tile data area of VRAM [0x8000 - 0x97FF].
+pub const TILE_COUNT: u16 = 384;
+/// Tiles across a row of the tile sheet.
+pub const TILE_SHEET_COLUMNS: usize = 16;
+
+/// The palette that shows tile data as its raw colour numbers, 0 lightest.
+pub const IDENTITY_PALETTE: u8 = 0b1110_0100;
+
 pub struct Tile {
     pub rows: [[u8; 8]; 8],
 }
 
 impl Tile {
     pub fn new(ppu: &Ppu, tile_index: u8) -> Tile {
-        let tile_address = 0x8000 | u16::from(tile_index) << 4;
+        Tile::at(ppu, u16::from(tile_index))
+    }
+
+    /// Read a tile by its number in VRAM, counting from 0x8000 up to `TILE_COUNT`.
+    pub fn at(ppu: &Ppu, tile_number: u16) -> Tile {
+        let tile_address = 0x8000 + (tile_number << 4);
         let mut rows = [[0u8; 8]; 8];
 
         for y in 0..8 {
@@ -21,4 +37,116 @@ impl Tile {
 
         Tile { rows }
     }
+
+    /// Draw the tile into an image with its top left at a position, through a palette.
+    pub fn draw(&self, image: &mut Image, left: usize, top: usize, palette: u8) {
+        let shades = unpack_palette(palette);
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, &colour) in row.iter().enumerate() {
+                image.set_shade(left + x, top + y, shades[usize::from(colour)]);
+            }
+        }
+    }
+}
+
+/// The tile number a BG or window map entry refers to, using the addressing mode set in LCDC.
+pub fn map_tile_number(control: ControlFlags, map_entry: u8) -> u16 {
+    if control.contains(ControlFlags::BG_WIN_TILE_SET) {
+        u16::from(map_entry)
+    } else {
+        (256 + i16::from(map_entry as i8)) as u16
+    }
+}
+
+/// Where a tile is used from.
+pub struct TileReferences {
+    /// Addresses of the BG/window map entries showing the tile.
+    pub map_entries: Vec<u16>,
+    /// Numbers of the sprites drawn with the tile.
+    pub sprites: Vec<u8>,
+}
+
+impl Ppu {
+    /// Draw every tile in VRAM through a palette, `TILE_SHEET_COLUMNS` to a row.
+    pub fn tile_sheet(&self, palette: u8) -> Image {
+        let rows = usize::from(TILE_COUNT).div_ceil(TILE_SHEET_COLUMNS);
+        let mut image = Image::new(TILE_SHEET_COLUMNS * 8, rows * 8);
+        for tile_number in 0..TILE_COUNT {
+            let column = usize::from(tile_number) % TILE_SHEET_COLUMNS;
+            let row = usize::from(tile_number) / TILE_SHEET_COLUMNS;
+            Tile::at(self, tile_number).draw(&mut image, column * 8, row * 8, palette);
+        }
+
+        image
+    }
+
+    /// Find the map entries and sprites that would draw a tile with the current LCDC settings.
+    pub fn tile_references(&self, tile_number: u16) -> TileReferences {
+        let control = self.registers.control;
+        let map_entries = (0x9800..=0x9FFF)
+            .filter(|&addr| map_tile_number(control, self.read8(addr)) == tile_number)
+            .collect();
+
+        let tall_sprites = control.contains(ControlFlags::OBJ_SIZE);
+        let sprites = (0..40)
+            .filter(|&sprite| {
+                let tile = u16::from(self.read8(0xFE02 + (u16::from(sprite) << 2)));
+                if tall_sprites {
+                    tile & 0xFE == tile_number & 0xFFFE
+                } else {
+                    tile == tile_number
+                }
+            })
+            .collect();
+
+        TileReferences {
+            map_entries,
+            sprites,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn tile_sheet_and_references() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let ppu = lameboy.get_ppu();
+        // Tile 1's top row is colours 0, 1, 2, 3, 0, 1, 2, 3 and tile 257 is solid colour 3
+        ppu.write8(0x8010, 0b0101_0101);
+        ppu.write8(0x8011, 0b0011_0011);
+        for addr in 0x9010..0x9020 {
+            ppu.write8(addr, 0xFF);
+        }
+        ppu.write8(0x9800, 0x01);
+        ppu.write8(0x9C00, 0x01);
+        ppu.write8(0xFE06, 0x01);
+
+        let sheet = ppu.tile_sheet(IDENTITY_PALETTE);
+        assert_eq!((128, 192), (sheet.width, sheet.height));
+        let shade = |x, y| {
+            let [r, g, b, _] = sheet.get_pixel(x, y);
+            crate::lameboy::ppu::palette::DMG_SHADES
+                .iter()
+                .position(|&colour| colour == [r, g, b])
+        };
+        assert_eq!(
+            vec![Some(0), Some(1), Some(2), Some(3)],
+            (8..12).map(|x| shade(x, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(3), shade(8, 16 * 8 + 7));
+
+        // LCDC from reset uses unsigned tile numbers, so both map entries show tile 1
+        let references = ppu.tile_references(1);
+        assert_eq!(vec![0x9800, 0x9C00], references.map_entries);
+        assert_eq!(vec![1], references.sprites);
+
+        // With signed tile numbers they show tile 257 instead
+        ppu.write8(0xFF40, 0x81);
+        assert!(ppu.tile_references(1).map_entries.is_empty());
+        assert_eq!(vec![0x9800, 0x9C00], ppu.tile_references(257).map_entries);
+    }
 }
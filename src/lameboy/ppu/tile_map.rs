@@ -0,0 +1,158 @@
+use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::ppu::image::Image;
+use crate::lameboy::ppu::registers::ControlFlags;
+use crate::lameboy::ppu::tile::{map_tile_number, Tile};
+use crate::lameboy::ppu::{Ppu, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// The two 32x32 tile maps the background and window can each use.
+pub const TILE_MAPS: [u16; 2] = [0x9800, 0x9C00];
+/// Width and height of a tile map in pixels.
+pub const TILE_MAP_SIZE: usize = 256;
+
+/// Outline of the part of the background on screen.
+const VIEWPORT_COLOUR: [u8; 4] = [0xE0, 0x20, 0x20, 0xFF];
+/// Outline of the part of the window on screen.
+const WINDOW_COLOUR: [u8; 4] = [0x20, 0x60, 0xE0, 0xFF];
+
+impl Ppu {
+    /// The tile map the background is drawn from.
+    pub fn bg_map(&self) -> u16 {
+        if self.registers.control.contains(ControlFlags::BG_TILE_MAP) {
+            TILE_MAPS[1]
+        } else {
+            TILE_MAPS[0]
+        }
+    }
+
+    /// The tile map the window is drawn from.
+    pub fn window_map(&self) -> u16 {
+        if self
+            .registers
+            .control
+            .contains(ControlFlags::WINDOW_TILE_MAP)
+        {
+            TILE_MAPS[1]
+        } else {
+            TILE_MAPS[0]
+        }
+    }
+
+    /// The tile number a map entry shows with the current LCDC addressing mode.
+    pub fn map_entry_tile(&self, entry_addr: u16) -> u16 {
+        map_tile_number(self.registers.control, self.read8(entry_addr))
+    }
+
+    /// Draw a whole tile map through the BG palette, optionally outlining the parts of it on
+    /// screen as the background or window.
+    pub fn tile_map(&self, map: u16, overlay: bool) -> Image {
+        let mut image = Image::new(TILE_MAP_SIZE, TILE_MAP_SIZE);
+        for row in 0..32 {
+            for column in 0..32 {
+                let tile = self.map_entry_tile(map + row * 32 + column);
+                Tile::at(self, tile).draw(
+                    &mut image,
+                    usize::from(column) * 8,
+                    usize::from(row) * 8,
+                    self.registers.bg_palette,
+                );
+            }
+        }
+
+        if overlay {
+            self.draw_map_overlay(&mut image, map);
+        }
+
+        image
+    }
+
+    fn draw_map_overlay(&self, image: &mut Image, map: u16) {
+        let control = self.registers.control;
+        if control.contains(ControlFlags::BG_DISPLAY) && map == self.bg_map() {
+            let left = usize::from(self.registers.scroll_x);
+            let top = usize::from(self.registers.scroll_y);
+            outline(
+                image,
+                left,
+                top,
+                SCREEN_WIDTH,
+                SCREEN_HEIGHT,
+                VIEWPORT_COLOUR,
+            );
+        }
+
+        // The window is drawn from the top left of its map, at WX-7, WY on screen
+        let window_x = usize::from(self.registers.window_x.saturating_sub(7));
+        let window_y = usize::from(self.registers.window_y);
+        if control.contains(ControlFlags::WINDOW_DISPLAY)
+            && map == self.window_map()
+            && window_x < SCREEN_WIDTH
+            && window_y < SCREEN_HEIGHT
+        {
+            let width = SCREEN_WIDTH - window_x;
+            let height = SCREEN_HEIGHT - window_y;
+            outline(image, 0, 0, width, height, WINDOW_COLOUR);
+        }
+    }
+}
+
+/// Draw a rectangle's outline on a tile map, wrapping around its edges as the PPU does.
+fn outline(
+    image: &mut Image,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    colour: [u8; 4],
+) {
+    let right = left + width - 1;
+    let bottom = top + height - 1;
+    for x in left..=right {
+        image.set_pixel(x % TILE_MAP_SIZE, top % TILE_MAP_SIZE, colour);
+        image.set_pixel(x % TILE_MAP_SIZE, bottom % TILE_MAP_SIZE, colour);
+    }
+    for y in top..=bottom {
+        image.set_pixel(left % TILE_MAP_SIZE, y % TILE_MAP_SIZE, colour);
+        image.set_pixel(right % TILE_MAP_SIZE, y % TILE_MAP_SIZE, colour);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::ppu::palette::DMG_SHADES;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn tile_map_with_wrapped_viewport() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let ppu = lameboy.get_ppu();
+        // Tile 1 is solid colour 3, shown at column 2, row 1 of the first map
+        for addr in 0x8010..0x8020 {
+            ppu.write8(addr, 0xFF);
+        }
+        ppu.write8(0x9800 + 32 + 2, 0x01);
+        ppu.write8(0xFF42, 200);
+        ppu.write8(0xFF43, 250);
+
+        let plain = ppu.tile_map(0x9800, false);
+        let shade = |image: &Image, x, y| {
+            let [r, g, b, _] = image.get_pixel(x, y);
+            DMG_SHADES.iter().position(|&colour| colour == [r, g, b])
+        };
+        assert_eq!(Some(3), shade(&plain, 16, 8));
+        assert_eq!(Some(0), shade(&plain, 24, 8));
+
+        // The viewport starts at 250, 200 and wraps round to the left and top
+        let overlaid = ppu.tile_map(0x9800, true);
+        assert_eq!(VIEWPORT_COLOUR, overlaid.get_pixel(250, 200));
+        assert_eq!(VIEWPORT_COLOUR, overlaid.get_pixel(4, 200));
+        assert_eq!(VIEWPORT_COLOUR, overlaid.get_pixel((250 + 159) % 256, 20));
+        assert_eq!(VIEWPORT_COLOUR, overlaid.get_pixel(20, (200 + 143) % 256));
+        assert_eq!(plain.get_pixel(20, 100), overlaid.get_pixel(20, 100));
+        // The window is off and the other map isn't the background's
+        assert_eq!(
+            ppu.tile_map(0x9C00, false).pixels,
+            ppu.tile_map(0x9C00, true).pixels
+        );
+    }
+}
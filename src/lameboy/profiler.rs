@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::dis::get_memory_comment;
+use crate::lameboy::cpu::Cpu;
+
+/// Cycles the CPU takes to push the PC and jump to an interrupt handler.
+const INTERRUPT_DISPATCH_CYCLES: u8 = 12;
+
+/// Where cycles are attributed to in the call graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Function {
+    /// Code not inside any call seen, the game's main loop.
+    Main,
+    /// Instructions run while the CPU is halted waiting for an interrupt.
+    Halt,
+    /// A routine entered by a call, restart or interrupt, by the bank and address it starts at.
+    Routine { bank: u16, addr: u16 },
+}
+
+impl Function {
+    /// A name for the function to show and export, its label if it has one, otherwise the name
+    /// of its vector or where it is.
+    pub fn name(&self, label: Option<&str>) -> String {
+        match (self, label) {
+            (Function::Main, _) => String::from("main"),
+            (Function::Halt, _) => String::from("HALT"),
+            (Function::Routine { .. }, Some(label)) => label.to_string(),
+            (Function::Routine { bank, addr }, None) => match get_memory_comment(*addr) {
+                Some(vector) if *addr < 0x0100 => vector.to_string(),
+                _ => format!("func_{bank:02X}_{addr:04X}"),
+            },
+        }
+    }
+}
+
+/// Cycles attributed to a function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FunctionStats {
+    pub calls: u64,
+    /// Cycles spent in the function itself.
+    pub self_cycles: u64,
+    /// Cycles spent in the function and everything it called.
+    pub total_cycles: u64,
+}
+
+/// The state of the CPU before a step, to attribute the step's cycles to.
+pub struct ProfileStart {
+    pc: u16,
+    bank: u16,
+    halted: bool,
+    in_interrupt: bool,
+    stack: Vec<Function>,
+}
+
+impl ProfileStart {
+    pub fn new(cpu: &Cpu) -> ProfileStart {
+        let frames = cpu.call_stack.frames();
+        let mut stack = Vec::with_capacity(frames.len() + 1);
+        stack.push(Function::Main);
+        stack.extend(frames.iter().map(|frame| Function::Routine {
+            bank: cpu.mmu.bank_of(frame.target),
+            addr: frame.target,
+        }));
+
+        ProfileStart {
+            pc: cpu.registers.pc,
+            bank: cpu.mmu.bank_of(cpu.registers.pc),
+            halted: cpu.is_halted(),
+            in_interrupt: frames.iter().any(|frame| frame.interrupt),
+            stack,
+        }
+    }
+}
+
+/// Attributes CPU cycles to each PC and, following the shadow call stack, to each function and
+/// each distinct call stack, for finding where a game's frames are going.
+pub struct Profiler {
+    pub enabled: bool,
+    pc_cycles: HashMap<(u16, u16), u64>,
+    functions: HashMap<Function, FunctionStats>,
+    stacks: HashMap<Vec<Function>, u64>,
+    pub total_cycles: u64,
+    pub halt_cycles: u64,
+    pub interrupt_cycles: u64,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            enabled: false,
+            pc_cycles: HashMap::new(),
+            functions: HashMap::new(),
+            stacks: HashMap::new(),
+            total_cycles: 0,
+            halt_cycles: 0,
+            interrupt_cycles: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pc_cycles.clear();
+        self.functions.clear();
+        self.stacks.clear();
+        self.total_cycles = 0;
+        self.halt_cycles = 0;
+        self.interrupt_cycles = 0;
+    }
+
+    /// Cycles spent on the instruction at each `(bank, pc)`.
+    pub fn pc_cycles(&self) -> &HashMap<(u16, u16), u64> {
+        &self.pc_cycles
+    }
+
+    pub fn functions(&self) -> &HashMap<Function, FunctionStats> {
+        &self.functions
+    }
+
+    /// Attribute the cycles a step took to where the CPU was before it, splitting off the time
+    /// taken to enter an interrupt handler if one was entered after the instruction.
+    pub fn record(&mut self, start: ProfileStart, cpu: &Cpu, cycles: u8) {
+        let frames = cpu.call_stack.frames();
+        let new_frames = frames.get(start.stack.len() - 1..).unwrap_or(&[]);
+        let dispatched = new_frames
+            .last()
+            .filter(|frame| frame.interrupt && frame.target == cpu.registers.pc)
+            .map(|frame| Function::Routine {
+                bank: cpu.mmu.bank_of(frame.target),
+                addr: frame.target,
+            });
+        for frame in new_frames {
+            let function = Function::Routine {
+                bank: cpu.mmu.bank_of(frame.target),
+                addr: frame.target,
+            };
+            self.functions.entry(function).or_default().calls += 1;
+        }
+
+        let dispatch_cycles = match dispatched {
+            Some(_) => INTERRUPT_DISPATCH_CYCLES.min(cycles),
+            None => 0,
+        };
+        let instruction_cycles = u64::from(cycles - dispatch_cycles);
+        self.total_cycles += u64::from(cycles);
+        *self.pc_cycles.entry((start.bank, start.pc)).or_default() += instruction_cycles;
+        if start.in_interrupt {
+            self.interrupt_cycles += instruction_cycles;
+        }
+
+        let mut stack = start.stack;
+        if let Some(handler) = dispatched {
+            if let Function::Routine { bank, addr } = handler {
+                *self.pc_cycles.entry((bank, addr)).or_default() += u64::from(dispatch_cycles);
+            }
+            self.interrupt_cycles += u64::from(dispatch_cycles);
+            stack.push(handler);
+            self.attribute(&stack, u64::from(dispatch_cycles));
+            stack.pop();
+        }
+
+        if start.halted {
+            self.halt_cycles += instruction_cycles;
+            stack.push(Function::Halt);
+        }
+        self.attribute(&stack, instruction_cycles);
+    }
+
+    fn attribute(&mut self, stack: &[Function], cycles: u64) {
+        if cycles == 0 {
+            return;
+        }
+
+        if let Some(&leaf) = stack.last() {
+            self.functions.entry(leaf).or_default().self_cycles += cycles;
+        }
+        // Recursive functions only count once towards their total
+        for (depth, function) in stack.iter().enumerate() {
+            if !stack[..depth].contains(function) {
+                self.functions.entry(*function).or_default().total_cycles += cycles;
+            }
+        }
+
+        match self.stacks.get_mut(stack) {
+            Some(stack_cycles) => *stack_cycles += cycles,
+            None => {
+                self.stacks.insert(stack.to_vec(), cycles);
+            }
+        }
+    }
+
+    /// Write every call stack seen and the cycles spent in it, one `main;outer;inner cycles` per
+    /// line, the folded format flame graph tools take.
+    pub fn write_folded<W: Write>(
+        &self,
+        writer: &mut W,
+        name: impl Fn(&Function) -> String,
+    ) -> std::io::Result<()> {
+        let mut lines: Vec<(String, u64)> = self
+            .stacks
+            .iter()
+            .map(|(stack, &cycles)| {
+                let names: Vec<String> = stack.iter().map(&name).collect();
+                (names.join(";"), cycles)
+            })
+            .collect();
+        lines.sort();
+
+        for (stack, cycles) in lines {
+            writeln!(writer, "{stack} {cycles}")?;
+        }
+        writer.flush()
+    }
+
+    pub fn export_folded(
+        &self,
+        path: &Path,
+        name: impl Fn(&Function) -> String,
+    ) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Unable to create profile {}: {e}", path.display()))?;
+        self.write_folded(&mut BufWriter::new(file), name)
+            .map_err(|e| format!("Unable to write profile {}: {e}", path.display()))?;
+        info!("Exported profile to {}", path.display());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn attributes_cycles_to_functions() {
+        let mut lameboy = Lameboy::with_code(&[
+            // call $0200; halt; nop
+            (0x0100, &[0xCD, 0x00, 0x02, 0x76, 0x00]),
+            // call $0300; ret
+            (0x0200, &[0xCD, 0x00, 0x03, 0xC9]),
+            // nop; ret
+            (0x0300, &[0x00, 0xC9]),
+            // reti
+            (0x0040, &[0xD9]),
+        ]);
+        lameboy.get_mmu().write8(0xFFFF, 0x00);
+        lameboy.get_profiler().enabled = true;
+
+        // Through both calls and back out, then halt and run the nop while halted
+        for _ in 0..7 {
+            lameboy.step();
+        }
+        // Take a VBlank from the halt, then return from it
+        lameboy.get_mmu().write8(0xFFFF, 0x01);
+        lameboy.get_mmu().write8(0xFF0F, 0x01);
+        lameboy.step();
+        lameboy.step();
+
+        let profiler = lameboy.get_profiler();
+        let outer = Function::Routine {
+            bank: 0,
+            addr: 0x0200,
+        };
+        let inner = Function::Routine {
+            bank: 0,
+            addr: 0x0300,
+        };
+        let vblank = Function::Routine {
+            bank: 0,
+            addr: 0x0040,
+        };
+
+        assert_eq!(
+            FunctionStats {
+                calls: 1,
+                self_cycles: 24 + 16,
+                total_cycles: 24 + 16 + 4 + 16,
+            },
+            profiler.functions()[&outer]
+        );
+        assert_eq!(
+            FunctionStats {
+                calls: 1,
+                self_cycles: 4 + 16,
+                total_cycles: 4 + 16,
+            },
+            profiler.functions()[&inner]
+        );
+        assert_eq!(4 + 4, profiler.halt_cycles);
+        assert_eq!(1, profiler.functions()[&vblank].calls);
+        assert_eq!(12 + 16, profiler.interrupt_cycles);
+        assert_eq!(Some(&24), profiler.pc_cycles().get(&(0, 0x0100)));
+
+        let mut folded = Vec::new();
+        profiler
+            .write_folded(&mut folded, |function| function.name(None))
+            .unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        assert!(folded.contains("main;func_00_0200;func_00_0300 20\n"));
+        assert!(folded.contains("main;VBlankInterrupt 28\n"));
+        assert!(folded.contains("main;HALT 8\n"));
+    }
+}
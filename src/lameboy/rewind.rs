@@ -0,0 +1,316 @@
+use std::collections::VecDeque;
+
+/// Frames between snapshots by default.
+pub const DEFAULT_REWIND_INTERVAL: u32 = 4;
+
+/// Memory the older snapshots may use by default.
+pub const DEFAULT_REWIND_BUDGET: usize = 32 * 1024 * 1024;
+
+/// A ring of machine state snapshots for rewinding.
+///
+/// Only the newest snapshot is kept whole. Every older one is stored as the XOR of itself and the
+/// snapshot after it, run-length encoded, so the mostly unchanged memory between snapshots takes
+/// next to no space. Rewinding undoes the deltas newest first, and the oldest snapshots are
+/// dropped once the budget is used up.
+pub struct RewindBuffer {
+    newest: Option<(u64, Vec<u8>)>,
+    older: VecDeque<(u64, Vec<u8>)>,
+    older_bytes: usize,
+    pub enabled: bool,
+    pub interval: u32,
+    pub budget: usize,
+    frames_since_snapshot: u32,
+}
+
+impl RewindBuffer {
+    pub fn new() -> RewindBuffer {
+        RewindBuffer {
+            newest: None,
+            older: VecDeque::new(),
+            older_bytes: 0,
+            enabled: true,
+            interval: DEFAULT_REWIND_INTERVAL,
+            budget: DEFAULT_REWIND_BUDGET,
+            frames_since_snapshot: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.newest = None;
+        self.older.clear();
+        self.older_bytes = 0;
+        self.frames_since_snapshot = 0;
+    }
+
+    /// How many snapshots can be rewound through.
+    pub fn len(&self) -> usize {
+        self.older.len() + usize::from(self.newest.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.newest.is_none()
+    }
+
+    /// Approximate memory used by the snapshots.
+    pub fn memory_used(&self) -> usize {
+        self.older_bytes + self.newest.as_ref().map_or(0, |(_, state)| state.len())
+    }
+
+    /// Cycle count of the oldest snapshot that can be restored.
+    pub fn oldest_cycle(&self) -> Option<u64> {
+        self.older
+            .front()
+            .or(self.newest.as_ref())
+            .map(|(cycle, _)| *cycle)
+    }
+
+    pub fn newest_cycle(&self) -> Option<u64> {
+        self.newest.as_ref().map(|(cycle, _)| *cycle)
+    }
+
+    /// Count a finished frame, returning true if a snapshot is due.
+    pub fn frame_finished(&mut self) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        self.frames_since_snapshot += 1;
+        if self.frames_since_snapshot >= self.interval.max(1) {
+            self.frames_since_snapshot = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn push(&mut self, cycle: u64, state: Vec<u8>) {
+        if let Some((newest_cycle, newest)) = self.newest.take() {
+            if newest.len() == state.len() {
+                let delta = encode_delta(&newest, &state);
+                self.older_bytes += delta.len();
+                self.older.push_back((newest_cycle, delta));
+            } else {
+                // Deltas only work between states of the same layout
+                self.older.clear();
+                self.older_bytes = 0;
+            }
+        }
+        self.newest = Some((cycle, state));
+
+        while self.older_bytes > self.budget {
+            match self.older.pop_front() {
+                Some((_, delta)) => self.older_bytes -= delta.len(),
+                None => break,
+            }
+        }
+    }
+
+    /// Take the newest snapshot, leaving the one before it as the newest.
+    pub fn pop(&mut self) -> Option<(u64, Vec<u8>)> {
+        let (cycle, state) = self.newest.take()?;
+
+        if let Some((older_cycle, delta)) = self.older.pop_back() {
+            self.older_bytes -= delta.len();
+            let mut older = state.clone();
+            apply_delta(&mut older, &delta);
+            self.newest = Some((older_cycle, older));
+        }
+        self.frames_since_snapshot = 0;
+
+        Some((cycle, state))
+    }
+}
+
+/// Run-length encode the XOR of two equal length states, as pairs of zero run and literal run
+/// lengths followed by the literal bytes.
+fn encode_delta(state: &[u8], next: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut position = 0;
+
+    while position < state.len() {
+        let zeros = (position..state.len())
+            .take_while(|&i| state[i] == next[i])
+            .count();
+        position += zeros;
+
+        let literal_start = position;
+        while position < state.len() && state[position] != next[position] {
+            position += 1;
+        }
+
+        write_length(&mut encoded, zeros);
+        write_length(&mut encoded, position - literal_start);
+        encoded.extend((literal_start..position).map(|i| state[i] ^ next[i]));
+    }
+
+    encoded
+}
+
+/// Turn a state back into the one a delta was made from.
+fn apply_delta(state: &mut [u8], delta: &[u8]) {
+    let mut position = 0;
+    let mut input = delta.iter().copied();
+
+    while let (Some(zeros), Some(literals)) = (read_length(&mut input), read_length(&mut input)) {
+        position += zeros;
+        for (byte, xor) in state[position..position + literals]
+            .iter_mut()
+            .zip(&mut input)
+        {
+            *byte ^= xor;
+        }
+        position += literals;
+    }
+}
+
+/// LEB128 style variable length encoding, as most runs are short.
+fn write_length(encoded: &mut Vec<u8>, mut length: usize) {
+    while length >= 0x80 {
+        encoded.push((length as u8 & 0x7F) | 0x80);
+        length >>= 7;
+    }
+    encoded.push(length as u8);
+}
+
+fn read_length(input: &mut impl Iterator<Item = u8>) -> Option<usize> {
+    let mut length = 0;
+    let mut shift = 0;
+    loop {
+        let byte = input.next()?;
+        length |= usize::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Some(length);
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::mmu::watchpoint::{WatchKind, Watchpoint};
+    use crate::lameboy::{Lameboy, COUNTER_PROGRAM};
+
+    #[test]
+    fn delta_round_trip() {
+        let state: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let mut next = state.clone();
+        next[0] ^= 0xFF;
+        next[500..700].fill(0x42);
+        next[999] = 0;
+
+        let delta = encode_delta(&state, &next);
+        assert!(delta.len() < 250, "{}", delta.len());
+
+        let mut restored = next.clone();
+        apply_delta(&mut restored, &delta);
+        assert_eq!(state, restored);
+
+        assert_eq!(vec![0xE8, 0x07, 0x00], encode_delta(&state, &state));
+    }
+
+    #[test]
+    fn pops_newest_first() {
+        let mut buffer = RewindBuffer::new();
+        for cycle in 0..5u64 {
+            let mut state = vec![0; 64];
+            state[cycle as usize] = cycle as u8 + 1;
+            buffer.push(cycle, state);
+        }
+        assert_eq!(5, buffer.len());
+        assert_eq!(Some(0), buffer.oldest_cycle());
+
+        for cycle in (0..5u64).rev() {
+            let (popped_cycle, state) = buffer.pop().unwrap();
+            assert_eq!(cycle, popped_cycle);
+            assert_eq!(cycle as u8 + 1, state[cycle as usize]);
+            assert_eq!(1, state.iter().filter(|&&b| b != 0).count());
+        }
+        assert!(buffer.pop().is_none());
+    }
+
+    #[test]
+    fn drops_oldest_over_budget() {
+        let mut buffer = RewindBuffer::new();
+        buffer.budget = 100;
+        for cycle in 0..50u64 {
+            buffer.push(cycle, vec![cycle as u8; 64]);
+        }
+
+        assert!(buffer.memory_used() <= 100 + 64);
+        assert!(buffer.oldest_cycle().unwrap() > 0);
+        assert_eq!(Some(49), buffer.newest_cycle());
+    }
+
+    #[test]
+    fn rewinds_frames() {
+        let mut lameboy = Lameboy::with_program(COUNTER_PROGRAM);
+        lameboy.get_rewind().interval = 1;
+        let mut states = Vec::new();
+        for _ in 0..3 {
+            lameboy.run_frame();
+            states.push(lameboy.save_state());
+        }
+        lameboy.step();
+
+        assert!(lameboy.rewind());
+        assert_eq!(states[2], lameboy.save_state());
+        assert!(lameboy.rewind());
+        assert_eq!(states[1], lameboy.save_state());
+        assert!(lameboy.rewind());
+        assert!(!lameboy.rewind());
+        assert_eq!(states[0], lameboy.save_state());
+    }
+
+    #[test]
+    fn reverse_step() {
+        let mut lameboy = Lameboy::with_program(COUNTER_PROGRAM);
+        lameboy.get_rewind().interval = 1;
+        lameboy.run_frame();
+        lameboy.run_frame();
+
+        let mut states = Vec::new();
+        for _ in 0..10 {
+            states.push(lameboy.save_state());
+            lameboy.step();
+        }
+        let history_length = lameboy.get_history().entries().len();
+
+        lameboy.reverse_step().unwrap();
+        assert_eq!(states[9], lameboy.save_state());
+        lameboy.reverse_step().unwrap();
+        assert_eq!(states[8], lameboy.save_state());
+        assert_eq!(history_length - 2, lameboy.get_history().entries().len());
+
+        // Back across the snapshot to the frame before
+        let cycle = lameboy.get_cycle_count();
+        lameboy.rewind_to_cycle(cycle - 70224).unwrap();
+        assert!(lameboy.get_cycle_count() < cycle - 70224);
+
+        let mut lameboy = Lameboy::with_program(COUNTER_PROGRAM);
+        assert!(lameboy.reverse_step().is_err());
+    }
+    #[test]
+    fn reverse_step_records_replay_once() {
+        let mut lameboy = Lameboy::with_program(COUNTER_PROGRAM);
+        lameboy.get_rewind().interval = 1;
+        lameboy.run_frame();
+        for _ in 0..10 {
+            lameboy.step();
+        }
+
+        let mut watchpoint = Watchpoint::new(0xC000, 0xDFFF, WatchKind::Write);
+        watchpoint.log_only = true;
+        lameboy.get_mmu().watchpoints.add(watchpoint);
+        lameboy.reverse_step().unwrap();
+
+        // Each write of the replay goes to the next address, so any repeat is from recording the
+        // counting run as well
+        let log = &lameboy.get_mmu().watchpoints.log;
+        assert!(!log.is_empty());
+        assert!(log
+            .iter()
+            .zip(log.iter().skip(1))
+            .all(|(a, b)| a.addr < b.addr));
+    }
+}
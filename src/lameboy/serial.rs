@@ -0,0 +1,145 @@
+use crate::lameboy::interrupts::INT_SERIAL;
+use crate::lameboy::mmu::mmuobject::MmuObject;
+use crate::lameboy::state::{StateReader, StateWriter, Stateful};
+
+const SC_TRANSFER_START: u8 = 0b1000_0000;
+const SC_INTERNAL_CLOCK: u8 = 0b0000_0001;
+const SC_UNUSED_BITS: u8 = 0b0111_1110;
+
+/// Serial port with nothing connected to the other end of the link cable.
+///
+/// Transfers using the internal clock complete immediately, shifting in 0xFF as if no other
+/// Game Boy was attached. When `record_output` is set every byte sent is kept, so test ROMs
+/// reporting their results over serial can be read back.
+pub struct Serial {
+    /// Serial transfer data [0xFF01]
+    sb: u8,
+    /// Serial transfer control [0xFF02]
+    sc: u8,
+    interrupt_pending: bool,
+    pub record_output: bool,
+    pub output: Vec<u8>,
+}
+
+impl Serial {
+    pub fn new() -> Serial {
+        Serial {
+            sb: 0x00,
+            sc: 0x00,
+            interrupt_pending: false,
+            record_output: false,
+            output: Vec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.sb = 0x00;
+        self.sc = 0x00;
+        self.interrupt_pending = false;
+        self.output.clear();
+    }
+
+    /// Return a byte containing the Interrupt Flag value from the serial port, clearing any
+    /// pending interrupt in the process.
+    pub fn cycle(&mut self) -> u8 {
+        if self.interrupt_pending {
+            self.interrupt_pending = false;
+            INT_SERIAL
+        } else {
+            0x00
+        }
+    }
+
+    /// Everything recorded from the serial port so far as text.
+    pub fn output_string(&self) -> String {
+        String::from_utf8_lossy(&self.output).to_string()
+    }
+}
+
+impl MmuObject for Serial {
+    fn read8(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF01 => self.sb,
+            0xFF02 => self.sc | SC_UNUSED_BITS,
+            _ => panic!("Attempted to access [RD] Serial from an invalid address: {addr:#X}"),
+        }
+    }
+
+    fn write8(&mut self, addr: u16, data: u8) {
+        match addr {
+            0xFF01 => self.sb = data,
+            0xFF02 => {
+                if data & (SC_TRANSFER_START | SC_INTERNAL_CLOCK)
+                    == SC_TRANSFER_START | SC_INTERNAL_CLOCK
+                {
+                    if self.record_output {
+                        self.output.push(self.sb);
+                    }
+                    self.sb = 0xFF;
+                    self.sc = data & !SC_TRANSFER_START;
+                    self.interrupt_pending = true;
+                } else {
+                    self.sc = data;
+                }
+            }
+            _ => panic!("Attempted to access [WR] Serial from an invalid address: {addr:#X}"),
+        }
+    }
+}
+
+impl Stateful for Serial {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.sb);
+        state.u8(self.sc);
+        state.bool(self.interrupt_pending);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.sb = state.u8()?;
+        self.sc = state.u8()?;
+        self.interrupt_pending = state.bool()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internal_clock_transfer_completes_immediately() {
+        let mut serial = Serial::new();
+        serial.record_output = true;
+        serial.write8(0xFF01, b'A');
+        serial.write8(0xFF02, 0x81);
+
+        assert_eq!(vec![b'A'], serial.output);
+        assert_eq!(0xFF, serial.read8(0xFF01));
+        assert_eq!(0x7F, serial.read8(0xFF02));
+        assert_eq!(INT_SERIAL, serial.cycle());
+        assert_eq!(0x00, serial.cycle());
+    }
+
+    #[test]
+    fn external_clock_transfer_waits() {
+        let mut serial = Serial::new();
+        serial.record_output = true;
+        serial.write8(0xFF01, b'A');
+        serial.write8(0xFF02, 0x80);
+
+        assert!(serial.output.is_empty());
+        assert_eq!(0xFE, serial.read8(0xFF02));
+        assert_eq!(0x00, serial.cycle());
+    }
+
+    #[test]
+    fn output_only_recorded_on_request() {
+        let mut serial = Serial::new();
+        serial.write8(0xFF01, b'A');
+        serial.write8(0xFF02, 0x81);
+
+        assert!(serial.output.is_empty());
+        assert_eq!(INT_SERIAL, serial.cycle());
+    }
+}
@@ -0,0 +1,149 @@
+/// Bumped whenever the layout of a saved state changes.
+pub const STATE_VERSION: u8 = 1;
+
+/// A component of the machine whose state can be saved and restored.
+///
+/// Only the emulated hardware is saved, debugger state such as breakpoints is left alone.
+pub trait Stateful {
+    fn save_state(&self, state: &mut StateWriter);
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String>;
+}
+
+/// Builds a flat little-endian byte buffer of machine state.
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new() -> StateWriter {
+        StateWriter { data: Vec::new() }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(u8::from(value));
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+}
+
+/// Reads back a buffer written by `StateWriter`, in the same order.
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [u8]) -> StateReader<'a> {
+        StateReader { data, position: 0 }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.data.len()
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self.position + length;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or_else(|| format!("State ended early at byte {}", self.position))?;
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, String> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, String> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    pub fn bytes(&mut self, into: &mut [u8]) -> Result<(), String> {
+        into.copy_from_slice(self.take(into.len())?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::{Lameboy, COUNTER_PROGRAM};
+
+    #[test]
+    fn round_trip() {
+        let mut writer = StateWriter::new();
+        writer.u8(0x12);
+        writer.bool(true);
+        writer.u16(0x3456);
+        writer.u64(0x0102_0304_0506_0708);
+        writer.bytes(&[9, 10]);
+        let data = writer.into_bytes();
+
+        let mut reader = StateReader::new(&data);
+        assert_eq!(Ok(0x12), reader.u8());
+        assert_eq!(Ok(true), reader.bool());
+        assert_eq!(Ok(0x3456), reader.u16());
+        assert_eq!(Ok(0x0102_0304_0506_0708), reader.u64());
+        let mut bytes = [0; 2];
+        reader.bytes(&mut bytes).unwrap();
+        assert_eq!([9, 10], bytes);
+        assert!(reader.is_finished());
+        assert!(reader.u8().is_err());
+    }
+
+    #[test]
+    fn restores_machine() {
+        let mut lameboy = Lameboy::with_program(COUNTER_PROGRAM);
+        for _ in 0..5_000 {
+            lameboy.step();
+        }
+        let state = lameboy.save_state();
+
+        for _ in 0..5_000 {
+            lameboy.step();
+        }
+        let later = lameboy.save_state();
+        assert_ne!(state, later);
+
+        lameboy.load_state(&state).unwrap();
+        assert_eq!(state, lameboy.save_state());
+        for _ in 0..5_000 {
+            lameboy.step();
+        }
+        assert_eq!(later, lameboy.save_state());
+
+        assert!(lameboy.load_state(&state[..state.len() - 1]).is_err());
+        assert!(lameboy.load_state(&[]).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::lameboy::cpu::opcodes::{Op, OPCODES};
+use crate::lameboy::cpu::Cpu;
+use crate::lameboy::mmu::watchpoint::WatchpointHit;
+use crate::lameboy::{CYCLES_PER_FRAME, CYCLES_PER_SCANLINE};
+
+/// LY value at the start of vblank.
+const VBLANK_LINE: u8 = 144;
+
+/// A temporary stop condition used by the debugger's stepping commands, checked before each
+/// instruction while the emulator is running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopCondition {
+    /// Stop when the PC reaches an address. With a stack pointer the stack must also be no deeper
+    /// than it, so recursive calls passing through the same address are skipped.
+    Address { addr: u16, sp: Option<u16> },
+    /// Stop once a return pops the return address stored at the stack pointer, or one further up
+    /// the stack, i.e. the current function returned.
+    Return { sp: u16 },
+    /// Stop when LY moves off a line, or at a deadline of two lines in case the display is off.
+    Scanline { ly: u8, deadline: u64 },
+    /// Stop at the start of the next vblank, or at a deadline of two frames in case the display is
+    /// off.
+    Frame { left_vblank: bool, deadline: u64 },
+}
+
+impl StopCondition {
+    /// The condition to step over the instruction at the PC, or None if it is not a call and a
+    /// single step will do.
+    pub fn step_over(cpu: &Cpu) -> Option<StopCondition> {
+        let pc = cpu.registers.pc;
+        let opcode = &OPCODES[cpu.mmu.read8_safe(pc) as usize];
+
+        match opcode.operation {
+            Op::CallD16 | Op::CallConditionalD16 | Op::Restart => Some(StopCondition::Address {
+                addr: pc.wrapping_add(u16::from(opcode.length)),
+                sp: Some(cpu.registers.sp),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The condition to run until the current function returns. Its return address is found from
+    /// the innermost call stack frame, so a `push hl; ret` jump inside the function isn't mistaken
+    /// for it returning. Without a frame it is somewhere at or above the stack pointer, while calls
+    /// the function makes push theirs below it.
+    pub fn step_out(cpu: &Cpu) -> StopCondition {
+        let sp = cpu.registers.sp;
+        let sp = match cpu.call_stack.frames().last() {
+            // A frame below the stack pointer was abandoned without returning
+            Some(frame) => frame.sp.max(sp),
+            None => sp,
+        };
+        StopCondition::Return { sp }
+    }
+
+    pub fn scanline(cpu: &Cpu, cycle_count: u64) -> StopCondition {
+        StopCondition::Scanline {
+            ly: current_ly(cpu),
+            deadline: cycle_count + 2 * u64::from(CYCLES_PER_SCANLINE),
+        }
+    }
+
+    pub fn frame(cpu: &Cpu, cycle_count: u64) -> StopCondition {
+        StopCondition::Frame {
+            left_vblank: current_ly(cpu) < VBLANK_LINE,
+            deadline: cycle_count + 2 * u64::from(CYCLES_PER_FRAME),
+        }
+    }
+
+    /// Check the condition against the state before the next instruction.
+    pub fn is_met(&mut self, cpu: &Cpu, cycle_count: u64) -> bool {
+        let registers = &cpu.registers;
+        match self {
+            StopCondition::Address { addr, sp } => {
+                registers.pc == *addr && sp.is_none_or(|sp| registers.sp >= sp)
+            }
+            StopCondition::Return { sp } => cpu.last_return.is_some_and(|popped| popped >= *sp),
+            StopCondition::Scanline { ly, deadline } => {
+                current_ly(cpu) != *ly || cycle_count >= *deadline
+            }
+            StopCondition::Frame {
+                left_vblank,
+                deadline,
+            } => {
+                let ly = current_ly(cpu);
+                if ly < VBLANK_LINE {
+                    *left_vblank = true;
+                }
+                (*left_vblank && ly == VBLANK_LINE) || cycle_count >= *deadline
+            }
+        }
+    }
+}
+
+/// Why the emulator last stopped running by itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Breakpoint(u16),
+    Watchpoint(WatchpointHit),
+    /// A stepping command's stop condition was met.
+    Condition,
+}
+
+fn current_ly(cpu: &Cpu) -> u8 {
+    cpu.mmu.read8_safe(0xFF44)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lameboy::Lameboy;
+
+    /// A recursive function counting A down to zero, one pushing and popping around a nop, and one
+    /// jumping to its own ret through a pushed address.
+    const CODE: &[(usize, &[u8])] = &[
+        // call 0x0200
+        (0x0100, &[0xCD, 0x00, 0x02]),
+        // call 0x0300
+        (0x0110, &[0xCD, 0x00, 0x03]),
+        // call 0x0310
+        (0x0120, &[0xCD, 0x10, 0x03]),
+        // jr -2
+        (0x0150, &[0x18, 0xFE]),
+        // dec a; jr z, +3; call 0x0200; ret
+        (0x0200, &[0x3D, 0x28, 0x03, 0xCD, 0x00, 0x02, 0xC9]),
+        // push bc; nop; pop bc; ret
+        (0x0300, &[0xC5, 0x00, 0xC1, 0xC9]),
+        // ld hl, 0x0315; push hl; ret; ret
+        (0x0310, &[0x21, 0x15, 0x03, 0xE5, 0xC9, 0xC9]),
+    ];
+
+    fn run_until_stopped(lameboy: &mut Lameboy) {
+        for _ in 0..10 {
+            if !lameboy.is_running() {
+                return;
+            }
+            lameboy.run_frame();
+        }
+        panic!("Emulator didn't stop");
+    }
+
+    #[test]
+    fn step_over_skips_recursion() {
+        let mut lameboy = Lameboy::with_code(CODE);
+        lameboy.get_cpu().registers.a = 3;
+        lameboy.step_over();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0x0103, lameboy.get_cpu().registers.pc);
+        assert_eq!(0xFFFE, lameboy.get_cpu().registers.sp);
+
+        // Deeper calls reach the same return address first
+        let mut lameboy = Lameboy::with_code(CODE);
+        lameboy.get_cpu().registers.a = 3;
+        lameboy.run_to(0x0203);
+        run_until_stopped(&mut lameboy);
+        let sp = lameboy.get_cpu().registers.sp;
+        lameboy.step_over();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0x0206, lameboy.get_cpu().registers.pc);
+        assert_eq!(sp, lameboy.get_cpu().registers.sp);
+        assert_eq!(0, lameboy.get_cpu().registers.a);
+        assert_eq!(None, lameboy.get_stop_condition());
+    }
+
+    #[test]
+    fn step_over_single_instruction() {
+        let mut lameboy = Lameboy::with_code(CODE);
+        lameboy.run_to(0x0200);
+        run_until_stopped(&mut lameboy);
+
+        lameboy.step_over();
+        assert!(!lameboy.is_running());
+        assert_eq!(0x0201, lameboy.get_cpu().registers.pc);
+    }
+
+    #[test]
+    fn step_out_of_recursion() {
+        let mut lameboy = Lameboy::with_code(CODE);
+        lameboy.run_to(0x0200);
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0xFFFC, lameboy.get_cpu().registers.sp);
+
+        lameboy.step_out();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0x0103, lameboy.get_cpu().registers.pc);
+        assert_eq!(0xFFFE, lameboy.get_cpu().registers.sp);
+    }
+
+    #[test]
+    fn step_out_past_pushes_and_pops() {
+        let mut lameboy = Lameboy::with_code(CODE);
+        lameboy.get_cpu().registers.pc = 0x0110;
+        lameboy.run_to(0x0301);
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0xFFFA, lameboy.get_cpu().registers.sp);
+
+        // The pop raises the stack pointer above where it was, but only the ret leaves the function
+        lameboy.step_out();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0x0113, lameboy.get_cpu().registers.pc);
+        assert_eq!(0xFFFE, lameboy.get_cpu().registers.sp);
+    }
+
+    #[test]
+    fn step_out_past_jump_through_ret() {
+        let mut lameboy = Lameboy::with_code(CODE);
+        lameboy.get_cpu().registers.pc = 0x0120;
+        lameboy.run_to(0x0314);
+        run_until_stopped(&mut lameboy);
+
+        // The call stack frame shows the first ret only pops the pushed jump target
+        lameboy.step_out();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(0x0123, lameboy.get_cpu().registers.pc);
+        assert_eq!(0xFFFE, lameboy.get_cpu().registers.sp);
+    }
+
+    #[test]
+    fn step_scanline_and_frame() {
+        let mut lameboy = Lameboy::with_code(CODE);
+        lameboy.get_cpu().registers.pc = 0x0150;
+
+        let ly = lameboy.get_mmu().read8_safe(0xFF44);
+        lameboy.step_scanline();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(ly.wrapping_add(1), lameboy.get_mmu().read8_safe(0xFF44));
+
+        lameboy.step_frame();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(144, lameboy.get_mmu().read8_safe(0xFF44));
+        let cycle_count = lameboy.get_cycle_count();
+
+        lameboy.step_frame();
+        run_until_stopped(&mut lameboy);
+        assert_eq!(144, lameboy.get_mmu().read8_safe(0xFF44));
+        let frame_cycles = lameboy.get_cycle_count() - cycle_count;
+        assert!(frame_cycles.abs_diff(70224) < 2000, "{frame_cycles}");
+
+        // With the display off LY never changes, so stop after two frames' worth of cycles
+        lameboy.get_mmu().write8(0xFF40, 0x00);
+        let cycle_count = lameboy.get_cycle_count();
+        lameboy.step_frame();
+        run_until_stopped(&mut lameboy);
+        assert!(lameboy.get_cycle_count() - cycle_count >= 2 * 70224);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::dis::get_memory_comment;
+use crate::lameboy::interrupts::*;
+
+/// Dots (cycles) the PPU takes to draw a scanline, the width of the timeline.
+pub const DOTS_PER_LINE: u16 = 456;
+/// Scanlines in a frame including vblank, the height of the timeline.
+pub const LINES_PER_FRAME: u8 = 154;
+
+/// Something the hardware did that's worth seeing against the frame's timing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// An interrupt flag was raised by the hardware.
+    InterruptRequest(u8),
+    /// The CPU jumped to an interrupt's handler.
+    InterruptService(u8),
+    /// The PPU entered a STAT mode, numbered as in the STAT register.
+    Mode(u8),
+    /// LY reached the line in LYC.
+    LycMatch(u8),
+    /// A PPU or APU register was written.
+    RegisterWrite { addr: u16, value: u8 },
+    /// An OAM DMA was started from a source page.
+    Dma(u8),
+    /// A write to the cart's MBC registers.
+    MbcWrite { addr: u16, value: u8 },
+}
+
+impl EventKind {
+    pub fn description(&self) -> String {
+        match *self {
+            EventKind::InterruptRequest(int_flag) => {
+                format!("{} interrupt requested", interrupt_name(int_flag))
+            }
+            EventKind::InterruptService(int_flag) => {
+                format!("{} interrupt serviced", interrupt_name(int_flag))
+            }
+            EventKind::Mode(mode) => format!("Mode {mode} ({})", mode_name(mode)),
+            EventKind::LycMatch(ly) => format!("LY = LYC ({ly})"),
+            EventKind::RegisterWrite { addr, value } => {
+                format!("Write ${value:02X} to {}", register_name(addr))
+            }
+            EventKind::Dma(source) => format!("OAM DMA from ${source:02X}00"),
+            EventKind::MbcWrite { addr, value } => {
+                let register = match addr {
+                    0x0000..=0x1FFF => "RAM enable",
+                    0x2000..=0x3FFF => "ROM bank",
+                    0x4000..=0x5FFF => "RAM bank",
+                    _ => "banking mode",
+                };
+                format!("MBC {register} ${value:02X} (${addr:04X})")
+            }
+        }
+    }
+}
+
+fn interrupt_name(int_flag: u8) -> &'static str {
+    match int_flag {
+        INT_VBLANK => "VBlank",
+        INT_LCD_STAT => "STAT",
+        INT_TIME => "Timer",
+        INT_SERIAL => "Serial",
+        INT_JOYPAD => "Joypad",
+        _ => "Unknown",
+    }
+}
+
+fn mode_name(mode: u8) -> &'static str {
+    match mode {
+        0 => "HBlank",
+        1 => "VBlank",
+        2 => "OAM scan",
+        _ => "Drawing",
+    }
+}
+
+/// The register's name as the disassembly shows it, along with its address.
+fn register_name(addr: u16) -> String {
+    match (addr, get_memory_comment(addr)) {
+        (0xFF30..=0xFF3F, _) => format!("wave RAM (${addr:04X})"),
+        (_, Some(name)) => format!("{name} (${addr:04X})"),
+        _ => format!("${addr:04X}"),
+    }
+}
+
+/// An event, where in the frame it happened and the instruction running when it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineEvent {
+    pub ly: u8,
+    pub dot: u16,
+    pub pc: u16,
+    pub kind: EventKind,
+}
+
+/// Events from the frame being drawn and the one before it, for plotting against the PPU's dot
+/// grid.
+pub struct Timeline {
+    pub enabled: bool,
+    /// The instruction being executed, for events to point back to.
+    pub pc: u16,
+    current: Vec<TimelineEvent>,
+    last: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    pub fn new() -> Timeline {
+        Timeline {
+            enabled: false,
+            pc: 0,
+            current: Vec::new(),
+            last: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.current.clear();
+        self.last.clear();
+    }
+
+    pub fn log(&mut self, ly: u8, dot: u16, kind: EventKind) {
+        if self.enabled {
+            self.current.push(TimelineEvent {
+                ly,
+                dot: dot.min(DOTS_PER_LINE - 1),
+                pc: self.pc,
+                kind,
+            });
+        }
+    }
+
+    /// Start a new frame, keeping the finished one as the last frame.
+    pub fn frame_finished(&mut self) {
+        if self.enabled {
+            std::mem::swap(&mut self.current, &mut self.last);
+            self.current.clear();
+        }
+    }
+
+    /// Events so far in the frame being drawn.
+    pub fn current(&self) -> &[TimelineEvent] {
+        &self.current
+    }
+
+    /// Events from the last full frame.
+    pub fn last(&self) -> &[TimelineEvent] {
+        &self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn logs_frame_events() {
+        let mut lameboy = Lameboy::with_code(&[
+            // ld a, $42; ldh [$FF43], a; ld [$2000], a; ldh [$FF46], a
+            (
+                0x0100,
+                &[0x3E, 0x42, 0xE0, 0x43, 0xEA, 0x00, 0x20, 0xE0, 0x46],
+            ),
+            // reti
+            (0x0040, &[0xD9]),
+        ]);
+        lameboy.get_mmu().write8(0xFFFF, INT_VBLANK);
+        lameboy.get_mmu().write8(0xFF45, 0x02);
+        lameboy.get_mmu().timeline.enabled = true;
+
+        for _ in 0..4 {
+            lameboy.step();
+        }
+        let events = lameboy.get_mmu().timeline.current().to_vec();
+        let kinds: Vec<EventKind> = events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            vec![
+                EventKind::RegisterWrite {
+                    addr: 0xFF43,
+                    value: 0x42
+                },
+                EventKind::MbcWrite {
+                    addr: 0x2000,
+                    value: 0x42
+                },
+                EventKind::Dma(0x42),
+            ],
+            kinds
+        );
+        assert_eq!("Write $42 to rSCX ($FF43)", kinds[0].description());
+        assert_eq!(0x0102, events[0].pc);
+        assert_eq!(0x0107, events[2].pc);
+
+        // Run through the rest of the frame into the next one
+        while lameboy.get_mmu().timeline.last().is_empty() {
+            lameboy.step();
+        }
+        let timeline = &lameboy.get_mmu().timeline;
+        let find = |kind: EventKind| timeline.last().iter().find(|event| event.kind == kind);
+        assert_eq!(Some(2), find(EventKind::LycMatch(2)).map(|event| event.ly));
+        assert_eq!(
+            Some(144),
+            find(EventKind::InterruptRequest(INT_VBLANK)).map(|event| event.ly)
+        );
+        assert!(find(EventKind::InterruptService(INT_VBLANK)).is_some());
+        assert!(find(EventKind::Mode(3)).is_some());
+    }
+    #[test]
+    fn names_registers() {
+        let write = |addr| EventKind::RegisterWrite { addr, value: 0x80 }.description();
+        assert_eq!("Write $80 to rNR52 ($FF26)", write(0xFF26));
+        assert_eq!("Write $80 to wave RAM ($FF34)", write(0xFF34));
+        assert_eq!("Write $80 to $FF27", write(0xFF27));
+    }
+}
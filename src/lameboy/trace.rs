@@ -0,0 +1,273 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::lameboy::cpu::Cpu;
+
+/// Build a single Gameboy Doctor trace line for the CPU's current state, including the four bytes
+/// of memory at PC.
+///
+/// ```text
+/// A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02
+/// ```
+pub fn format_trace_line(cpu: &Cpu) -> String {
+    let r = &cpu.registers;
+    let pc = r.pc;
+
+    format!(
+        "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+        r.a,
+        r.f.bits(),
+        r.b,
+        r.c,
+        r.d,
+        r.e,
+        r.h,
+        r.l,
+        r.sp,
+        pc,
+        cpu.mmu.read8_safe(pc),
+        cpu.mmu.read8_safe(pc.wrapping_add(1)),
+        cpu.mmu.read8_safe(pc.wrapping_add(2)),
+        cpu.mmu.read8_safe(pc.wrapping_add(3)),
+    )
+}
+
+/// Writes a Gameboy Doctor trace line before each instruction executed within a window of cycles.
+pub struct TraceWriter<W: Write = BufWriter<File>> {
+    writer: W,
+    start_cycle: u64,
+    end_cycle: Option<u64>,
+    pub lines_written: u64,
+}
+
+impl TraceWriter {
+    /// Create (or truncate) a trace file.
+    pub fn create(path: &Path) -> Result<TraceWriter, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Unable to create trace file {}: {e}", path.display()))?;
+
+        Ok(TraceWriter::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Create a trace writer that logs every instruction until it is stopped.
+    pub fn new(writer: W) -> TraceWriter<W> {
+        TraceWriter {
+            writer,
+            start_cycle: 0,
+            end_cycle: None,
+            lines_written: 0,
+        }
+    }
+
+    /// Only log instructions starting from `start_cycle` up to, but not including, `end_cycle`.
+    pub fn with_window(mut self, start_cycle: u64, end_cycle: Option<u64>) -> TraceWriter<W> {
+        self.start_cycle = start_cycle;
+        self.end_cycle = end_cycle;
+        self
+    }
+
+    /// Whether the window has been passed, so no more lines will ever be logged.
+    pub fn is_finished(&self, cycle: u64) -> bool {
+        self.end_cycle.is_some_and(|end| cycle >= end)
+    }
+
+    /// Log the CPU state if the cycle count is within the window.
+    pub fn log(&mut self, cpu: &Cpu, cycle: u64) -> Result<(), String> {
+        if cycle < self.start_cycle || self.is_finished(cycle) {
+            return Ok(());
+        }
+
+        writeln!(self.writer, "{}", format_trace_line(cpu))
+            .map_err(|e| format!("Unable to write trace: {e}"))?;
+        self.lines_written += 1;
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("Unable to write trace: {e}"))
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// The first point at which two traces disagree.
+#[derive(Debug, PartialEq)]
+pub struct TraceDivergence {
+    /// 1-based line number of the first differing line.
+    pub line: usize,
+    /// Matching lines leading up to the divergence, oldest first.
+    pub context: Vec<String>,
+    /// The reference line, or `None` if the reference trace ended first.
+    pub expected: Option<String>,
+    /// Our line, or `None` if our trace ended first.
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Traces diverge at line {}", self.line)?;
+
+        let first_context_line = self.line - self.context.len();
+        for (i, line) in self.context.iter().enumerate() {
+            writeln!(f, "  {: >8}   {line}", first_context_line + i)?;
+        }
+
+        let missing = String::from("<end of trace>");
+        writeln!(
+            f,
+            "- {: >8}   {}",
+            self.line,
+            self.expected.as_ref().unwrap_or(&missing)
+        )?;
+        write!(
+            f,
+            "+ {: >8}   {}",
+            self.line,
+            self.actual.as_ref().unwrap_or(&missing)
+        )
+    }
+}
+
+/// Compare our trace against a reference trace line by line, returning the first divergence with
+/// up to `context` matching lines before it, or `None` if the traces are identical.
+///
+/// Trailing whitespace is ignored so traces with differing line endings still compare equal.
+pub fn diff_traces<A: BufRead, B: BufRead>(
+    actual: A,
+    reference: B,
+    context: usize,
+) -> Result<Option<TraceDivergence>, String> {
+    let mut actual_lines = actual.lines();
+    let mut reference_lines = reference.lines();
+    let mut previous: Vec<String> = Vec::with_capacity(context + 1);
+    let mut line = 0;
+
+    loop {
+        line += 1;
+        let actual_line = actual_lines
+            .next()
+            .transpose()
+            .map_err(|e| format!("Unable to read trace: {e}"))?;
+        let expected_line = reference_lines
+            .next()
+            .transpose()
+            .map_err(|e| format!("Unable to read reference trace: {e}"))?;
+
+        match (actual_line, expected_line) {
+            (None, None) => return Ok(None),
+            (Some(actual), Some(expected)) if actual.trim_end() == expected.trim_end() => {
+                if context > 0 {
+                    if previous.len() == context {
+                        previous.remove(0);
+                    }
+                    previous.push(actual.trim_end().to_string());
+                }
+            }
+            (actual, expected) => {
+                return Ok(Some(TraceDivergence {
+                    line,
+                    context: previous,
+                    expected: expected.map(|l| l.trim_end().to_string()),
+                    actual: actual.map(|l| l.trim_end().to_string()),
+                }))
+            }
+        }
+    }
+}
+
+/// Compare two trace files, see `diff_traces`.
+pub fn diff_trace_files(
+    actual: &Path,
+    reference: &Path,
+    context: usize,
+) -> Result<Option<TraceDivergence>, String> {
+    let open = |path: &Path| {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| format!("Unable to open trace {}: {e}", path.display()))
+    };
+
+    diff_traces(open(actual)?, open(reference)?, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn doctor_line_format() {
+        let mut lameboy = Lameboy::with_program(&[0x00, 0xC3, 0x13, 0x02]);
+
+        assert_eq!(
+            "A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02",
+            format_trace_line(lameboy.get_cpu())
+        );
+    }
+
+    #[test]
+    fn trace_window() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let mut trace = TraceWriter::new(Vec::new()).with_window(4, Some(8));
+
+        for cycle in [0, 4, 6, 8] {
+            trace.log(lameboy.get_cpu(), cycle).unwrap();
+        }
+
+        assert_eq!(2, trace.lines_written);
+        assert!(trace.is_finished(8));
+        assert_eq!(
+            2,
+            String::from_utf8(trace.into_inner())
+                .unwrap()
+                .lines()
+                .count()
+        );
+    }
+
+    #[test]
+    fn identical_traces() {
+        let trace = "line 1\nline 2\n";
+
+        assert_eq!(
+            Ok(None),
+            diff_traces(trace.as_bytes(), "line 1\r\nline 2\r\n".as_bytes(), 3)
+        );
+    }
+
+    #[test]
+    fn first_divergence_with_context() {
+        let ours = "1\n2\n3\n4\nX\n6\n";
+        let reference = "1\n2\n3\n4\n5\n6\n";
+
+        assert_eq!(
+            Ok(Some(TraceDivergence {
+                line: 5,
+                context: vec![String::from("3"), String::from("4")],
+                expected: Some(String::from("5")),
+                actual: Some(String::from("X")),
+            })),
+            diff_traces(ours.as_bytes(), reference.as_bytes(), 2)
+        );
+    }
+
+    #[test]
+    fn trace_ends_early() {
+        let divergence = diff_traces("1\n".as_bytes(), "1\n2\n".as_bytes(), 0)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(2, divergence.line);
+        assert_eq!(None, divergence.actual);
+        assert_eq!(Some(String::from("2")), divergence.expected);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+
+use crate::lameboy::expression::{named_address, Expression, ExpressionContext};
+use crate::lameboy::mmu::Mmu;
+
+/// How many frames of history each watch keeps.
+pub const WATCH_HISTORY_SIZE: usize = 240;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchFormat {
+    U8,
+    I8,
+    U16,
+    I16,
+}
+
+impl WatchFormat {
+    pub const ALL: [WatchFormat; 4] = [
+        WatchFormat::U8,
+        WatchFormat::I8,
+        WatchFormat::U16,
+        WatchFormat::I16,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WatchFormat::U8 => "u8",
+            WatchFormat::I8 => "i8",
+            WatchFormat::U16 => "u16",
+            WatchFormat::I16 => "i16",
+        }
+    }
+
+    pub fn is_16_bit(&self) -> bool {
+        matches!(self, WatchFormat::U16 | WatchFormat::I16)
+    }
+
+    /// Interpret raw little-endian memory as this format.
+    fn value(&self, raw: u16) -> i32 {
+        match self {
+            WatchFormat::U8 => i32::from(raw as u8),
+            WatchFormat::I8 => i32::from(raw as u8 as i8),
+            WatchFormat::U16 => i32::from(raw),
+            WatchFormat::I16 => i32::from(raw as i16),
+        }
+    }
+}
+
+enum WatchTarget {
+    Address(u16),
+    Expression(Expression),
+}
+
+/// A memory location to watch, shown in a chosen width and signedness with a history of its value
+/// at the end of each frame.
+pub struct Watch {
+    source: String,
+    target: WatchTarget,
+    pub format: WatchFormat,
+    pub history: VecDeque<i32>,
+}
+
+impl Watch {
+    /// Create a watch on a named location (such as `rLY`) or an address expression (such as
+    /// `0xC123` or `hl + 2`), which is re-evaluated every time the watch is read.
+    pub fn new(source: &str, format: WatchFormat) -> Result<Watch, String> {
+        let source = source.trim();
+        let target = match named_address(source) {
+            Some(addr) => WatchTarget::Address(addr),
+            None => WatchTarget::Expression(Expression::parse(source)?),
+        };
+
+        Ok(Watch {
+            source: source.to_string(),
+            target,
+            format,
+            history: VecDeque::with_capacity(WATCH_HISTORY_SIZE),
+        })
+    }
+
+    /// Create a watch on a symbol file label, already resolved to its address.
+    pub fn labelled(label: &str, addr: u16, format: WatchFormat) -> Watch {
+        Watch {
+            source: label.trim().to_string(),
+            target: WatchTarget::Address(addr),
+            format,
+            history: VecDeque::with_capacity(WATCH_HISTORY_SIZE),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn address(&self, context: &dyn ExpressionContext) -> Result<u16, String> {
+        match &self.target {
+            WatchTarget::Address(addr) => Ok(*addr),
+            WatchTarget::Expression(expression) => Ok(expression.evaluate(context)? as u16),
+        }
+    }
+
+    /// Read the raw bits at the watched location, without side effects.
+    pub fn read_raw(&self, context: &dyn ExpressionContext) -> Result<u16, String> {
+        let addr = self.address(context)?;
+        let low = u16::from(context.read8(addr));
+        if self.format.is_16_bit() {
+            Ok(u16::from(context.read8(addr.wrapping_add(1))) << 8 | low)
+        } else {
+            Ok(low)
+        }
+    }
+
+    pub fn read(&self, context: &dyn ExpressionContext) -> Result<i32, String> {
+        self.read_raw(context).map(|raw| self.format.value(raw))
+    }
+
+    /// Write a value to the watched location through the MMU, low byte first.
+    pub fn write(&self, addr: u16, mmu: &mut Mmu, value: i32) {
+        mmu.write8(addr, value as u8);
+        if self.format.is_16_bit() {
+            mmu.write8(addr.wrapping_add(1), (value >> 8) as u8);
+        }
+    }
+
+    /// Record the current value in the history, called once per frame.
+    pub fn sample(&mut self, context: &dyn ExpressionContext) {
+        if let Ok(value) = self.read(context) {
+            if self.history.len() == WATCH_HISTORY_SIZE {
+                self.history.pop_front();
+            }
+            self.history.push_back(value);
+        }
+    }
+
+    /// The value formatted as hex, binary and decimal.
+    pub fn format_value(&self, raw: u16) -> (String, String, String) {
+        let decimal = self.format.value(raw).to_string();
+        if self.format.is_16_bit() {
+            (format!("0x{raw:04X}"), format!("{raw:016b}"), decimal)
+        } else {
+            (
+                format!("0x{raw:02X}"),
+                format!("{:08b}", raw as u8),
+                decimal,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lameboy::Lameboy;
+
+    #[test]
+    fn formats() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        lameboy.get_mmu().write8(0xC000, 0xFE);
+        lameboy.get_mmu().write8(0xC001, 0xFF);
+        let cpu = lameboy.get_cpu();
+
+        let read = |format| Watch::new("0xC000", format).unwrap().read(cpu).unwrap();
+        assert_eq!(254, read(WatchFormat::U8));
+        assert_eq!(-2, read(WatchFormat::I8));
+        assert_eq!(65534, read(WatchFormat::U16));
+        assert_eq!(-2, read(WatchFormat::I16));
+    }
+
+    #[test]
+    fn named_and_expression_targets() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        lameboy.get_cpu().registers.h = 0xC0;
+        lameboy.get_cpu().registers.l = 0x00;
+        let cpu = lameboy.get_cpu();
+
+        assert_eq!(
+            Ok(0xFF44),
+            Watch::new("rLY", WatchFormat::U8).unwrap().address(cpu)
+        );
+        assert_eq!(
+            Ok(0xC001),
+            Watch::new("hl + 1", WatchFormat::U8).unwrap().address(cpu)
+        );
+        assert!(Watch::new("hl +", WatchFormat::U8).is_err());
+    }
+
+    #[test]
+    fn write_and_history() {
+        let mut lameboy = Lameboy::with_program(&[]);
+        let mut watch = Watch::new("0xC000", WatchFormat::I16).unwrap();
+
+        watch.write(0xC000, lameboy.get_mmu(), -300);
+        assert_eq!(Ok(-300), watch.read(lameboy.get_cpu()));
+
+        for _ in 0..WATCH_HISTORY_SIZE + 5 {
+            watch.sample(lameboy.get_cpu());
+        }
+        assert_eq!(WATCH_HISTORY_SIZE, watch.history.len());
+        assert_eq!(
+            (
+                String::from("0xFED4"),
+                String::from("1111111011010100"),
+                String::from("-300")
+            ),
+            watch.format_value(0xFED4)
+        );
+    }
+}
@@ -18,12 +18,21 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 
+use crate::dis::{Annotations, SymbolTable};
+use crate::disasm::DisasmArgs;
+use crate::gdb::{GdbArgs, GdbServer};
 use crate::gui::Gui;
+use crate::headless::RunArgs;
+use crate::lameboy::cdl::CodeDataLog;
 use crate::lameboy::Lameboy;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
+mod conformance;
 mod dis;
+mod disasm;
+mod gdb;
 mod gui;
+mod headless;
 
 mod lameboy;
 
@@ -35,10 +44,39 @@ const PKG_AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
 const CLEAR_COLOR: (f32, f32, f32, f32) = (0.8784, 0.9725, 0.8156, 1.0);
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, args_conflicts_with_subcommands = true)]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     /// ROM file to load
-    file: String,
+    #[arg(required = true)]
+    file: Option<String>,
+    /// Listen for a GDB remote debugger on this local TCP port
+    #[arg(long)]
+    gdb: Option<u16>,
+    /// Start a code/data log, adding to this file if it exists, saved from the disassembly window
+    #[arg(long)]
+    cdl: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run a ROM without a window for a number of frames, then print hashes of the final state
+    Run(RunArgs),
+    /// Run a ROM without a window, controlled by a GDB remote debugger on a local TCP port
+    Gdb(GdbArgs),
+    /// Statically disassemble a ROM into RGBDS source that reassembles to the same ROM
+    Disasm(DisasmArgs),
+}
+
+fn load_rom(rom_file: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut f = File::open(rom_file).expect("Unable to open ROM");
+    f.read_to_end(&mut data).expect("Unable to read data");
+    info!("File length: {}", data.len());
+
+    data
 }
 
 fn main() {
@@ -46,21 +84,68 @@ fn main() {
 
     let args = Args::parse();
 
-    let rom_file = args.file.as_str();
-    let rom_file_name = Path::new(rom_file).file_name().unwrap().to_str().unwrap();
+    if let Some(command) = args.command {
+        let result = match command {
+            Command::Run(run_args) => {
+                let data = load_rom(&run_args.file);
+                headless::run_command(&run_args, data)
+            }
+            Command::Gdb(gdb_args) => {
+                let data = load_rom(&gdb_args.file);
+                gdb::run_command(&gdb_args, data)
+            }
+            Command::Disasm(disasm_args) => {
+                let data = load_rom(&disasm_args.file);
+                disasm::run_command(&disasm_args, &data)
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let rom_file = args.file.expect("ROM file is required");
+    let rom_file_name = Path::new(&rom_file).file_name().unwrap().to_str().unwrap();
     info!("Filename: {}", rom_file_name);
 
-    let mut data = Vec::new();
-    let mut f = File::open(rom_file).expect("Unable to open ROM");
-    f.read_to_end(&mut data).expect("Unable to read data");
-    info!("File length: {}", data.len());
+    let data = load_rom(&rom_file);
 
     let window_title = format!("{rom_file_name} - Lameboy - v{PKG_VERSION}");
     let gui = Gui::init((640f64, 576f64), window_title, CLEAR_COLOR);
 
     // Create all our hardware instances
-    let mut lameboy = Lameboy::new(data, &gui);
+    let mut lameboy = Lameboy::new(data);
     lameboy.reset();
 
-    gui.main_loop(lameboy);
+    if let Some(path) = SymbolTable::find_for_rom(Path::new(&rom_file)) {
+        match SymbolTable::load(&path) {
+            Ok(symbols) => lameboy.debug.symbols = symbols,
+            Err(e) => error!("{}", e),
+        }
+    }
+    let global_checksum = lameboy.get_cart().global_checksum;
+    match Annotations::load_for_rom(global_checksum) {
+        Ok(annotations) => lameboy.debug.annotations = annotations,
+        Err(e) => error!("{}", e),
+    }
+
+    if let Some(path) = args.cdl {
+        let rom_length = lameboy.get_cart().rom_length();
+        match CodeDataLog::load_or_new(Path::new(&path), rom_length) {
+            Ok(log) => lameboy.get_mmu().code_data_log = Some(log),
+            Err(e) => error!("{}", e),
+        }
+        lameboy.debug.cdl_path = path;
+    }
+
+    let gdb = args.gdb.map(|port| {
+        GdbServer::bind(port).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(1);
+        })
+    });
+
+    gui.main_loop(lameboy, gdb);
 }